@@ -0,0 +1,58 @@
+use std::{
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Returns the version of a locked dependency from Cargo.lock.
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    let mut lines = lock.lines();
+    lines.find(|line| *line == format!("name = \"{}\"", name))?;
+
+    lines
+        .next()?
+        .strip_prefix("version = \"")?
+        .strip_suffix('"')
+        .map(String::from)
+}
+
+/// Returns the trimmed output of a git command (None outside a git checkout).
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// build info for `dcprov version --json`
+fn main() {
+    let commit = git(&["rev-parse", "--short=12", "HEAD"]).unwrap_or_default();
+
+    // reproducible builds pin the build date via SOURCE_DATE_EPOCH – otherwise the commit date is
+    // used, so rebuilding the same commit yields the same build info
+    let build_epoch = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .or_else(|| git(&["log", "-1", "--format=%ct"]).and_then(|epoch| epoch.parse().ok()))
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default()
+        });
+
+    let dco3_version = std::fs::read_to_string("Cargo.lock")
+        .ok()
+        .and_then(|lock| locked_version(&lock, "dco3"))
+        .unwrap_or_default();
+
+    println!("cargo:rustc-env=DCPROV_BUILD_COMMIT={}", commit);
+    println!("cargo:rustc-env=DCPROV_BUILD_EPOCH={}", build_epoch);
+    println!("cargo:rustc-env=DCPROV_DCO3_VERSION={}", dco3_version);
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
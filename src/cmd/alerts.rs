@@ -0,0 +1,245 @@
+use super::attributes::{fetch_all_attributes, ATTRIBUTES_CONCURRENCY};
+use super::{
+    dracoon_error_to_string, fetch_all_customers, http_client, is_cancelled, print_csv_header,
+    print_json, PrintType,
+};
+use colored::*;
+use dco3::{auth::Provisioning, provisioning::Customer, Dracoon};
+use futures_util::{stream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+const ALERTS_CSV_HEADER: &str = "customerId,companyName,quotaPercent,userPercent,group";
+
+// digest group of customers without the grouping attribute
+const UNGROUPED: &str = "-";
+
+/// Customer with quota or user usage at or above the alert threshold
+struct Breach<'a> {
+    customer: &'a Customer,
+    quota_percent: f64,
+    user_percent: f64,
+    group: String,
+}
+
+impl Breach<'_> {
+    fn to_json(&self) -> Value {
+        json!({
+            "customerId": self.customer.id,
+            "companyName": self.customer.company_name,
+            "quotaUsed": self.customer.quota_used,
+            "quotaMax": self.customer.quota_max,
+            "quotaPercent": (self.quota_percent * 10.0).round() / 10.0,
+            "userUsed": self.customer.user_used,
+            "userMax": self.customer.user_max,
+            "userPercent": (self.user_percent * 10.0).round() / 10.0,
+        })
+    }
+}
+
+fn percent(used: u64, max: u64) -> f64 {
+    match max {
+        0 => 0.0,
+        max => used as f64 / max as f64 * 100.0,
+    }
+}
+
+/// Returns the value of the grouping attribute per customer – customers whose attributes can't be
+/// fetched end up in the ungrouped bucket (the alert is still sent).
+async fn fetch_groups(
+    provider: &Dracoon<Provisioning>,
+    ids: Vec<u64>,
+    key: &str,
+) -> BTreeMap<u64, String> {
+    stream::iter(ids)
+        .map(|id| async move {
+            let group = match fetch_all_attributes(provider, id).await {
+                Ok(attribs) => attribs
+                    .into_iter()
+                    .find(|attrib| attrib.key == key)
+                    .map(|attrib| attrib.value),
+                Err(e) => {
+                    eprintln!(
+                        "{} Could not get attributes of customer {}: {}",
+                        "Warning".black().on_yellow(),
+                        id,
+                        dracoon_error_to_string(e)
+                    );
+                    None
+                }
+            };
+            (id, group.unwrap_or_else(|| UNGROUPED.into()))
+        })
+        .buffer_unordered(ATTRIBUTES_CONCURRENCY)
+        .collect()
+        .await
+}
+
+async fn post_webhook(webhook: &str, payload: &Value) -> Result<(), String> {
+    match http_client().post(webhook).json(payload).send().await {
+        Ok(res) if res.status().is_success() => Ok(()),
+        Ok(res) => Err(format!("webhook returned {}", res.status())),
+        Err(e) => Err(format!("webhook failed: {}", e)),
+    }
+}
+
+/// Lists customers with quota or user usage at or above the threshold (percent) and notifies the
+/// webhook (if passed) – one POST per customer or, with `digest`, a single POST per run with the
+/// breaches grouped by the `group_by` attribute (avoids alert storms). Exits with 1 if a
+/// notification fails.
+pub async fn usage_alerts(
+    provider: Dracoon<Provisioning>,
+    threshold: f64,
+    webhook: Option<String>,
+    digest: bool,
+    group_by: Option<String>,
+    filter: Option<String>,
+    print_type: PrintType,
+) {
+    let customers = fetch_all_customers(&provider, filter, Some("id:asc".into())).await;
+    if is_cancelled() {
+        super::exit(1)
+    }
+
+    let mut breaches: Vec<_> = customers
+        .iter()
+        .map(|customer| Breach {
+            customer,
+            quota_percent: percent(customer.quota_used, customer.quota_max),
+            user_percent: percent(customer.user_used, customer.user_max),
+            group: UNGROUPED.into(),
+        })
+        .filter(|breach| breach.quota_percent >= threshold || breach.user_percent >= threshold)
+        .collect();
+
+    if let Some(key) = &group_by {
+        let ids = breaches.iter().map(|breach| breach.customer.id).collect();
+        let mut groups = fetch_groups(&provider, ids, key).await;
+        for breach in &mut breaches {
+            if let Some(group) = groups.remove(&breach.customer.id) {
+                breach.group = group;
+            }
+        }
+    }
+
+    // fullest customers first
+    breaches.sort_by(|a, b| {
+        let fill = |breach: &Breach| breach.quota_percent.max(breach.user_percent);
+        fill(b).total_cmp(&fill(a))
+    });
+
+    match print_type {
+        PrintType::Csv => {
+            print_csv_header(ALERTS_CSV_HEADER);
+            for breach in &breaches {
+                println!(
+                    "{},{},{:.1},{:.1},{}",
+                    breach.customer.id,
+                    breach.customer.company_name,
+                    breach.quota_percent,
+                    breach.user_percent,
+                    breach.group
+                );
+            }
+        }
+        PrintType::Pretty => {
+            for breach in &breaches {
+                println!(
+                    "company: {} | id: {} | quota: {:.1}% | users: {:.1}%{}",
+                    breach.customer.company_name,
+                    breach.customer.id,
+                    breach.quota_percent,
+                    breach.user_percent,
+                    match group_by {
+                        Some(ref key) => format!(" | {}: {}", key, breach.group),
+                        None => String::new(),
+                    }
+                );
+            }
+            println!(
+                "customers: {} | at or above {}%: {}",
+                customers.len(),
+                threshold,
+                breaches.len()
+            );
+        }
+        PrintType::Json => print_json(json!({
+            "customers": customers.len(),
+            "threshold": threshold,
+            "items": breaches
+                .iter()
+                .map(|breach| {
+                    let mut item = breach.to_json();
+                    if group_by.is_some() {
+                        item["group"] = json!(breach.group);
+                    }
+                    item
+                })
+                .collect::<Vec<_>>(),
+        })),
+    }
+
+    let Some(webhook) = webhook else {
+        return;
+    };
+    if breaches.is_empty() {
+        return;
+    }
+
+    let mut failed = 0;
+
+    if digest {
+        let mut groups: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+        for breach in &breaches {
+            groups
+                .entry(breach.group.as_str())
+                .or_default()
+                .push(breach.to_json());
+        }
+
+        let payload = json!({
+            "event": "usage-alerts",
+            "threshold": threshold,
+            "breaches": breaches.len(),
+            "groupBy": group_by,
+            "groups": groups
+                .into_iter()
+                .map(|(group, items)| json!({ "group": group, "items": items }))
+                .collect::<Vec<_>>(),
+        });
+
+        if let Err(e) = post_webhook(&webhook, &payload).await {
+            eprintln!("{} {}", "Error".white().on_red(), e);
+            failed += 1;
+        }
+    } else {
+        for breach in &breaches {
+            let mut payload = breach.to_json();
+            payload["event"] = json!("usage-alert");
+            payload["threshold"] = json!(threshold);
+            if group_by.is_some() {
+                payload["group"] = json!(breach.group);
+            }
+
+            if let Err(e) = post_webhook(&webhook, &payload).await {
+                eprintln!(
+                    "{} Customer {}: {}",
+                    "Error".white().on_red(),
+                    breach.customer.id,
+                    e
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    eprintln!(
+        "notifications: {} | failed: {}",
+        if digest { 1 } else { breaches.len() },
+        failed
+    );
+
+    if failed > 0 {
+        super::exit(1)
+    }
+}
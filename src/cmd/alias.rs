@@ -0,0 +1,221 @@
+use super::{DCProvCommand, DcProv};
+use crate::config;
+use clap::{CommandFactory, FromArgMatches};
+use colored::*;
+use std::{collections::BTreeMap, ffi::OsString};
+
+/// Returns the placeholder names (`{{name}}`) of a word in order.
+fn placeholders(word: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = word;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        names.push(rest[start + 2..start + end].to_string());
+        rest = &rest[start + end + 2..];
+    }
+
+    names
+}
+
+/// Expands the alias of the args once – returns the alias name and the expanded args, or None if
+/// the command is no alias (built-in command or plugin).
+fn expand_once(
+    args: &[OsString],
+    aliases: &BTreeMap<String, String>,
+) -> Result<Option<(String, Vec<OsString>)>, String> {
+    let Some(DCProvCommand::Plugin(command)) = DcProv::command()
+        .try_get_matches_from(args)
+        .ok()
+        .and_then(|matches| DcProv::from_arg_matches(&matches).ok())
+        .map(|opt| opt.cmd)
+    else {
+        return Ok(None);
+    };
+    let Some((name, alias_args)) = command.split_first() else {
+        return Ok(None);
+    };
+    let Some(template) = aliases.get(name) else {
+        return Ok(None);
+    };
+
+    // `{{ name }}` would be split into separate words
+    let template = template.replace("{{ ", "{{").replace(" }}", "}}");
+    let Some(words) = shlex::split(&template) else {
+        return Err(format!("Invalid alias {}: unbalanced quotes", name));
+    };
+
+    let mut rest = alias_args.iter();
+    let mut values: Vec<(String, String)> = Vec::new();
+
+    for placeholder in words.iter().flat_map(|word| placeholders(word)) {
+        if values.iter().any(|(known, _)| *known == placeholder) {
+            continue;
+        }
+        let Some(value) = rest.next() else {
+            return Err(format!(
+                "Alias {} expects <{}> – usage: dcprov {} {}",
+                name,
+                placeholder,
+                name,
+                words
+                    .iter()
+                    .flat_map(|word| placeholders(word))
+                    .map(|placeholder| format!("<{}>", placeholder))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ));
+        };
+        values.push((placeholder, value.to_string()));
+    }
+
+    let expanded = words.into_iter().map(|word| {
+        let word = values.iter().fold(word, |word, (placeholder, value)| {
+            word.replace(&format!("{{{{{}}}}}", placeholder), value)
+        });
+        OsString::from(word)
+    });
+
+    // the plugin args are the tail of the args – options before the alias are kept
+    let options = args.len() - command.len();
+
+    let args = args[..options]
+        .iter()
+        .cloned()
+        .chain(expanded)
+        .chain(rest.map(OsString::from))
+        .collect();
+
+    Ok(Some((name.clone(), args)))
+}
+
+/// Expands command aliases (`[alias]`): placeholders (e.g. `{{url}}`) are filled with the arguments
+/// following the alias in order of appearance, remaining arguments are appended. An alias may
+/// expand to another alias – fails on cycles. Aliases are parsed by clap like plugins (unknown
+/// commands), so global options may precede them and built-in commands can't be shadowed – other
+/// args are returned unchanged.
+fn expand_aliases(
+    mut args: Vec<OsString>,
+    aliases: &BTreeMap<String, String>,
+) -> Result<Vec<OsString>, String> {
+    let mut expanded: Vec<String> = Vec::new();
+
+    while let Some((name, next)) = expand_once(&args, aliases)? {
+        let cycle = expanded.contains(&name);
+        expanded.push(name);
+        if cycle {
+            return Err(format!("Alias cycle: {}", expanded.join(" -> ")));
+        }
+        args = next;
+    }
+
+    Ok(args)
+}
+
+/// Expands the command aliases of the config – exits on invalid aliases or missing arguments.
+pub fn expand_alias(args: Vec<OsString>) -> Vec<OsString> {
+    let aliases = config::load_config()
+        .map(|config| config.alias)
+        .unwrap_or_default();
+
+    match expand_aliases(args, &aliases) {
+        Ok(args) => args,
+        Err(msg) => {
+            println!("{} {}", "Error".white().on_red(), msg);
+            super::exit(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(aliases: &[(&str, &str)]) -> BTreeMap<String, String> {
+        aliases
+            .iter()
+            .map(|(name, template)| (name.to_string(), template.to_string()))
+            .collect()
+    }
+
+    fn expand(args: &str, aliases: &BTreeMap<String, String>) -> Result<String, String> {
+        let args = std::iter::once("dcprov")
+            .chain(args.split_whitespace())
+            .map(OsString::from)
+            .collect();
+
+        expand_aliases(args, aliases).map(|args| {
+            args[1..]
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+    }
+
+    #[test]
+    fn expand_placeholders_and_pass_remaining_args() {
+        let aliases = aliases(&[("seats", "update {{ url }} {{id}} user-max")]);
+
+        assert_eq!(
+            expand("seats prod 999 50", &aliases).unwrap(),
+            "update prod 999 user-max 50"
+        );
+        // global options before the alias are kept
+        assert_eq!(
+            expand("--verbose seats prod 999 50", &aliases).unwrap(),
+            "--verbose update prod 999 user-max 50"
+        );
+    }
+
+    #[test]
+    fn expand_missing_argument() {
+        let aliases = aliases(&[("seats", "update {{url}} {{id}} user-max")]);
+
+        let err = expand("seats prod", &aliases).unwrap_err();
+
+        assert!(err.contains("expects <id>"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_aliases_and_builtins_are_unchanged() {
+        let aliases = aliases(&[("list", "get {{url}} 1"), ("locked", "list {{url}} --all")]);
+
+        // no alias: passed on to the plugin lookup
+        assert_eq!(
+            expand("unknown prod 1", &aliases).unwrap(),
+            "unknown prod 1"
+        );
+        // built-in commands can't be shadowed
+        assert_eq!(
+            expand("list prod --csv", &aliases).unwrap(),
+            "list prod --csv"
+        );
+    }
+
+    #[test]
+    fn expand_recursively() {
+        let aliases = aliases(&[
+            ("locked", "list {{url}} --filter isLocked:eq:true"),
+            ("locked-csv", "locked {{url}} --csv"),
+        ]);
+
+        assert_eq!(
+            expand("locked-csv prod --all", &aliases).unwrap(),
+            "list prod --filter isLocked:eq:true --csv --all"
+        );
+    }
+
+    #[test]
+    fn expand_cycle() {
+        let aliases = aliases(&[("a", "b {{url}}"), ("b", "a {{url}}"), ("c", "c")]);
+
+        assert_eq!(
+            expand("a prod", &aliases).unwrap_err(),
+            "Alias cycle: a -> b -> a"
+        );
+        assert_eq!(expand("c", &aliases).unwrap_err(), "Alias cycle: c -> c");
+    }
+}
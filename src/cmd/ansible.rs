@@ -0,0 +1,355 @@
+use super::{
+    check_expected_host, dracoon_error_to_string, find_customer_by_name, init_provisioning,
+    try_create_customer, try_delete_customer, try_update_customer, try_update_customer_attributes,
+    ClientOptions, ContractType, UpdateCustomerPatch, UpdateType,
+};
+use dco3::{
+    auth::Provisioning,
+    provisioning::{Customer, CustomerAttributes, FirstAdminUser, NewCustomerRequest},
+    users::{AuthMethod, UserAuthData},
+    CustomerProvisioning, Dracoon,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fs};
+
+#[derive(Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum AnsibleState {
+    #[default]
+    Present,
+    Absent,
+}
+
+#[derive(Deserialize)]
+struct AnsibleAdminUser {
+    first_name: String,
+    last_name: String,
+    email: String,
+    user_name: Option<String>,
+}
+
+/// Module arguments as passed by Ansible (args file)
+#[derive(Deserialize)]
+struct AnsibleArgs {
+    url: String,
+    token: Option<String>,
+    #[serde(default)]
+    state: AnsibleState,
+    id: Option<u64>,
+    company_name: Option<String>,
+    contract_type: Option<ContractType>,
+    quota_max: Option<u64>,
+    user_max: Option<u64>,
+    first_admin_user: Option<AnsibleAdminUser>,
+    attributes: Option<BTreeMap<String, String>>,
+    #[serde(rename = "_ansible_check_mode", default)]
+    check_mode: bool,
+}
+
+struct ModuleResult {
+    changed: bool,
+    msg: String,
+    customer: Option<Customer>,
+}
+
+fn customer_to_json(customer: &Customer) -> Value {
+    json!({
+        "id": customer.id,
+        "company_name": customer.company_name,
+        "contract_type": customer.customer_contract_type,
+        "quota_max": customer.quota_max,
+        "quota_used": customer.quota_used,
+        "user_max": customer.user_max,
+        "user_used": customer.user_used,
+        "is_locked": customer.is_locked,
+        "created_at": customer.created_at,
+    })
+}
+
+async fn find_customer(
+    provider: &Dracoon<Provisioning>,
+    args: &AnsibleArgs,
+) -> Result<Option<Customer>, String> {
+    if let Some(id) = args.id {
+        return match provider.get_customer(id, None).await {
+            Ok(customer) => Ok(Some(customer)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(dracoon_error_to_string(e)),
+        };
+    }
+
+    let company_name = args
+        .company_name
+        .as_deref()
+        .ok_or("either id or company_name is required")?;
+
+    find_customer_by_name(provider, company_name)
+        .await
+        .map_err(dracoon_error_to_string)
+}
+
+async fn attributes_to_change(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+    wanted: &BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    let current = provider
+        .get_customer_attributes(id, None)
+        .await
+        .map_err(dracoon_error_to_string)?;
+
+    Ok(wanted
+        .iter()
+        .filter(|(key, value)| {
+            !current
+                .items
+                .iter()
+                .any(|attrib| &&attrib.key == key && &&attrib.value == value)
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect())
+}
+
+fn to_customer_attributes(attribs: &BTreeMap<String, String>) -> CustomerAttributes {
+    let mut customer_attribs = CustomerAttributes::new();
+    attribs.iter().for_each(|(key, value)| {
+        customer_attribs.add_attribute(key, value);
+    });
+    customer_attribs
+}
+
+async fn ensure_absent(
+    provider: &Dracoon<Provisioning>,
+    args: &AnsibleArgs,
+) -> Result<ModuleResult, String> {
+    let Some(customer) = find_customer(provider, args).await? else {
+        return Ok(ModuleResult {
+            changed: false,
+            msg: "customer absent".into(),
+            customer: None,
+        });
+    };
+
+    if !args.check_mode {
+        try_delete_customer(provider, customer.id)
+            .await
+            .map_err(dracoon_error_to_string)?;
+    }
+
+    Ok(ModuleResult {
+        changed: true,
+        msg: format!("deleted customer {}", customer.id),
+        customer: Some(customer),
+    })
+}
+
+async fn ensure_present(
+    provider: &Dracoon<Provisioning>,
+    args: &AnsibleArgs,
+) -> Result<ModuleResult, String> {
+    let Some(customer) = find_customer(provider, args).await? else {
+        return create(provider, args).await;
+    };
+
+    let mut update = UpdateCustomerPatch::default();
+    let mut changes = Vec::new();
+
+    if let Some(company_name) = args.company_name.as_ref() {
+        if company_name != &customer.company_name {
+            update.company_name = Some(company_name.clone());
+            changes.push("company_name");
+        }
+    }
+    if let Some(contract_type) = args.contract_type {
+        if !contract_type
+            .as_str()
+            .eq_ignore_ascii_case(&customer.customer_contract_type)
+        {
+            update.customer_contract_type = Some(contract_type);
+            changes.push("contract_type");
+        }
+    }
+    if let Some(quota_max) = args.quota_max {
+        if quota_max != customer.quota_max {
+            update.quota_max = Some(quota_max);
+            changes.push("quota_max");
+        }
+    }
+    if let Some(user_max) = args.user_max {
+        if user_max != customer.user_max {
+            update.user_max = Some(user_max);
+            changes.push("user_max");
+        }
+    }
+
+    let attribs = match args.attributes.as_ref() {
+        Some(wanted) => attributes_to_change(provider, customer.id, wanted).await?,
+        None => BTreeMap::new(),
+    };
+    if !attribs.is_empty() {
+        changes.push("attributes");
+    }
+
+    if changes.is_empty() {
+        return Ok(ModuleResult {
+            changed: false,
+            msg: "customer up to date".into(),
+            customer: Some(customer),
+        });
+    }
+
+    if args.check_mode {
+        return Ok(ModuleResult {
+            changed: true,
+            msg: format!("would update {}", changes.join(", ")),
+            customer: Some(customer),
+        });
+    }
+
+    if changes.iter().any(|change| *change != "attributes") {
+        try_update_customer(provider, customer.id, UpdateType::Patch(update))
+            .await
+            .map_err(dracoon_error_to_string)?;
+    }
+    if !attribs.is_empty() {
+        try_update_customer_attributes(provider, customer.id, to_customer_attributes(&attribs))
+            .await
+            .map_err(dracoon_error_to_string)?;
+    }
+
+    let customer = provider
+        .get_customer(customer.id, None)
+        .await
+        .map_err(dracoon_error_to_string)?;
+
+    Ok(ModuleResult {
+        changed: true,
+        msg: format!("updated {}", changes.join(", ")),
+        customer: Some(customer),
+    })
+}
+
+async fn create(
+    provider: &Dracoon<Provisioning>,
+    args: &AnsibleArgs,
+) -> Result<ModuleResult, String> {
+    let (Some(company_name), Some(quota_max), Some(user_max), Some(admin)) = (
+        args.company_name.as_ref(),
+        args.quota_max,
+        args.user_max,
+        args.first_admin_user.as_ref(),
+    ) else {
+        return Err(
+            "creating a customer requires company_name, quota_max, user_max and first_admin_user"
+                .into(),
+        );
+    };
+
+    if args.check_mode {
+        return Ok(ModuleResult {
+            changed: true,
+            msg: format!("would create customer {}", company_name),
+            customer: None,
+        });
+    }
+
+    let auth_data = UserAuthData::builder(AuthMethod::Basic)
+        .with_must_change_password(true)
+        .build();
+
+    let first_admin_user = FirstAdminUser {
+        first_name: admin.first_name.clone(),
+        last_name: admin.last_name.clone(),
+        user_name: admin.user_name.clone(),
+        email: Some(admin.email.clone()),
+        auth_data: Some(auth_data),
+        notify_user: Some(true),
+        receiver_language: None,
+        phone: None,
+    };
+
+    let req = NewCustomerRequest::builder(
+        args.contract_type.unwrap_or_default().as_str(),
+        quota_max,
+        user_max,
+        first_admin_user,
+    )
+    .with_company_name(company_name);
+
+    let req = match args.attributes.as_ref() {
+        Some(attribs) => req.with_customer_attributes(to_customer_attributes(attribs)),
+        None => req,
+    };
+
+    let created = try_create_customer(provider, req.build())
+        .await
+        .map_err(dracoon_error_to_string)?;
+
+    let customer = provider
+        .get_customer(created.id, None)
+        .await
+        .map_err(dracoon_error_to_string)?;
+
+    Ok(ModuleResult {
+        changed: true,
+        msg: format!("created customer {}", customer.id),
+        customer: Some(customer),
+    })
+}
+
+fn exit_json(result: Result<ModuleResult, String>) -> ! {
+    match result {
+        Ok(result) => {
+            let mut output = json!({
+                "changed": result.changed,
+                "failed": false,
+                "msg": result.msg,
+            });
+            if let Some(customer) = result.customer.as_ref() {
+                output["customer"] = customer_to_json(customer);
+            }
+            println!("{}", output);
+            super::exit(0)
+        }
+        Err(msg) => {
+            println!(
+                "{}",
+                json!({ "changed": false, "failed": true, "msg": msg })
+            );
+            super::exit(1)
+        }
+    }
+}
+
+/// Runs dcprov as Ansible module: reads the module args file, ensures the requested customer state
+/// idempotently (supports check mode) and prints the result as JSON following Ansible conventions.
+pub async fn run_ansible_module(args_file: &str, client_opts: &ClientOptions) -> ! {
+    let args = fs::read_to_string(args_file)
+        .map_err(|e| format!("could not read args file {}: {}", args_file, e))
+        .and_then(|raw| {
+            serde_json::from_str::<AnsibleArgs>(&raw)
+                .map_err(|e| format!("invalid module args: {}", e))
+        });
+
+    let args = match args {
+        Ok(args) => args,
+        Err(msg) => exit_json(Err(msg)),
+    };
+
+    if let Err(msg) = check_expected_host(&args.url) {
+        exit_json(Err(msg))
+    }
+
+    let provider = match init_provisioning(&args.url, args.token.clone(), client_opts).await {
+        Ok(provider) => provider,
+        Err(e) => exit_json(Err(format!("could not initialize client: {}", e))),
+    };
+
+    let result = match args.state {
+        AnsibleState::Present => ensure_present(&provider, &args).await,
+        AnsibleState::Absent => ensure_absent(&provider, &args).await,
+    };
+
+    exit_json(result)
+}
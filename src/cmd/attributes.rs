@@ -0,0 +1,557 @@
+use super::{
+    customer_field, dracoon_error_to_string, exclude::apply_exclusions, fetch_all_customers,
+    handle_dracoon_errors, install_cancel_handler, is_cancelled, print_csv_header, print_json,
+    run_lock::acquire_batch_lock, try_update_customer_attributes, BatchReport, PrintType,
+};
+use crate::config::{self, AttributeSchema};
+use colored::*;
+use dco3::{
+    auth::Provisioning,
+    provisioning::{Customer, CustomerAttributes},
+    CustomerProvisioning, Dracoon, DracoonClientError, KeyValueEntry, ListAllParams,
+};
+use futures_util::{stream, StreamExt};
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+};
+
+// max. page size for customer attributes
+const ATTRIBUTES_PAGE_SIZE: u64 = 500;
+
+// parallel requests to fetch the attributes of many customers (one request per customer)
+pub(super) const ATTRIBUTES_CONCURRENCY: usize = 8;
+
+/// Fetches all attributes of a customer (all pages).
+pub(super) async fn fetch_all_attributes(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+) -> Result<Vec<KeyValueEntry>, DracoonClientError> {
+    let mut attribs = Vec::new();
+
+    loop {
+        let params = ListAllParams::builder()
+            .with_offset(attribs.len() as u64)
+            .with_limit(ATTRIBUTES_PAGE_SIZE)
+            .build();
+        let page = provider.get_customer_attributes(id, Some(params)).await?;
+        let total = page.range.total;
+
+        if page.items.is_empty() {
+            break;
+        }
+        attribs.extend(page.items);
+
+        if attribs.len() as u64 >= total {
+            break;
+        }
+    }
+
+    Ok(attribs)
+}
+
+/// Fetches the attributes of all customers (one call per customer – not part of the customer
+/// list) with the given number of parallel requests and stores them in the customers.
+/// Returns false if fetching was interrupted (customers not fetched keep no attributes).
+pub(super) async fn include_attributes(
+    provider: &Dracoon<Provisioning>,
+    customers: &mut [Customer],
+    concurrency: usize,
+) -> Result<bool, DracoonClientError> {
+    let ids: Vec<_> = customers.iter().map(|customer| customer.id).collect();
+
+    let mut results = stream::iter(ids.into_iter().enumerate())
+        .map(|(index, id)| async move {
+            if is_cancelled() {
+                return (index, None);
+            }
+            (index, Some(fetch_all_attributes(provider, id).await))
+        })
+        .buffer_unordered(concurrency.max(1));
+
+    let mut complete = true;
+
+    while let Some((index, result)) = results.next().await {
+        let Some(attribs) = result.transpose()? else {
+            complete = false;
+            continue;
+        };
+        let mut customer_attribs = CustomerAttributes::new();
+        attribs.into_iter().for_each(|attrib| {
+            customer_attribs.add_attribute(attrib.key, attrib.value);
+        });
+        customers[index].customer_attributes = Some(customer_attribs);
+    }
+
+    Ok(complete)
+}
+
+/// Loads attributes from a JSON or YAML file containing a map of keys to (scalar) values.
+pub fn load_attributes_file(path: &str) -> Vec<(String, String)> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) => {
+            println!(
+                "{} Could not open file from path {}",
+                "Error".white().on_red(),
+                path
+            );
+            println!("{:?}", e);
+            super::exit(1)
+        }
+    };
+
+    // YAML is a superset of JSON – both formats are parsed as YAML
+    let attribs = match serde_yaml::from_str::<BTreeMap<String, serde_yaml::Value>>(&raw) {
+        Ok(attribs) => attribs,
+        Err(e) => {
+            println!(
+                "{} Could not parse attributes from file {}: {}",
+                "Error".white().on_red(),
+                path,
+                e
+            );
+            super::exit(1)
+        }
+    };
+
+    attribs
+        .into_iter()
+        .map(|(key, value)| match value {
+            serde_yaml::Value::String(value) => (key, value),
+            serde_yaml::Value::Number(value) => (key, value.to_string()),
+            serde_yaml::Value::Bool(value) => (key, value.to_string()),
+            _ => {
+                println!(
+                    "{} Invalid value for attribute {} in file {} (expected string, number or bool)",
+                    "Error".white().on_red(),
+                    key,
+                    path
+                );
+                super::exit(1)
+            }
+        })
+        .collect()
+}
+
+/// Maps a placeholder (snake_case customer field as in hooks, e.g. `company_name`, or
+/// `attribute:<key>`) to the customer field name used for columns.
+fn placeholder_field(name: &str) -> String {
+    if name.starts_with(super::ATTRIBUTE_PREFIX) {
+        return name.to_string();
+    }
+    if name == "contract_type" {
+        return "customerContractType".into();
+    }
+
+    let mut parts = name.split('_');
+    let first = parts.next().unwrap_or_default().to_string();
+    parts.fold(first, |field, part| {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(c) => field + &c.to_uppercase().collect::<String>() + chars.as_str(),
+            None => field,
+        }
+    })
+}
+
+/// Replaces `{{field}}` placeholders in an attribute value with the fields of the customer,
+/// e.g. `{{company_name}} ({{id}})`.
+fn render_attribute_value(template: &str, customer: &Customer) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| format!("unclosed placeholder in {}", template))?;
+        let name = rest[start + 2..start + end].trim();
+        let value = customer_field(customer, &placeholder_field(name))
+            .ok_or_else(|| format!("unknown or empty customer field {{{{{}}}}}", name))?;
+
+        result.push_str(&rest[..start]);
+        result.push_str(&value);
+        rest = &rest[start + end + 2..];
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+/// Resolves `{{field}}` placeholders in attribute values from the customer (fetched only if
+/// any value contains a placeholder) – exits on unknown fields.
+pub async fn render_attribute_templates(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+    attribs: Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    if !attribs.iter().any(|(_, value)| value.contains("{{")) {
+        return attribs;
+    }
+
+    let customer = match provider.get_customer(id, Some(true)).await {
+        Ok(customer) => customer,
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not get customer."));
+            super::exit(1)
+        }
+    };
+
+    attribs
+        .into_iter()
+        .map(
+            |(key, value)| match render_attribute_value(&value, &customer) {
+                Ok(value) => (key, value),
+                Err(e) => {
+                    println!(
+                        "{} Invalid value for attribute {}: {}",
+                        "Error".white().on_red(),
+                        key,
+                        e
+                    );
+                    super::exit(1)
+                }
+            },
+        )
+        .collect()
+}
+
+/// Returns the violations of the attributes against the schema.
+fn schema_violations(schema: &AttributeSchema, attribs: &[(String, String)]) -> Vec<String> {
+    attribs
+        .iter()
+        .filter_map(|(key, value)| match schema.keys.get(key) {
+            None if schema.strict => Some(format!("unknown attribute key {}", key)),
+            Some(rule) if !rule.values.is_empty() && !rule.values.contains(value) => Some(format!(
+                "invalid value {} for {} (allowed: {})",
+                value,
+                key,
+                rule.values.join(", ")
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Validates attributes against the attribute schema (if configured) – exits on violations.
+pub fn validate_attributes(attribs: &[(String, String)]) {
+    let Some(schema) = config::load_config().unwrap_or_default().attributes else {
+        return;
+    };
+
+    let violations = schema_violations(&schema, attribs);
+
+    if !violations.is_empty() {
+        for violation in &violations {
+            println!("{} {}", "Error".white().on_red(), violation);
+        }
+        println!(
+            "{} attribute(s) violate the attribute schema – nothing set.",
+            violations.len()
+        );
+        super::exit(1)
+    }
+}
+
+/// Copies the attributes of a customer (optionally only keys with given prefix) to other customers.
+pub async fn copy_customer_attributes(
+    provider: Dracoon<Provisioning>,
+    from: u64,
+    to: Vec<u64>,
+    prefix: Option<String>,
+) -> BatchReport {
+    let attribs = match fetch_all_attributes(&provider, from).await {
+        Ok(attribs) => attribs,
+        Err(e) => {
+            println!(
+                "{} Could not get attributes of customer {}: {}",
+                "Error".white().on_red(),
+                from,
+                dracoon_error_to_string(e)
+            );
+            super::exit(1)
+        }
+    };
+
+    let attribs: Vec<_> = attribs
+        .into_iter()
+        .filter(|attrib| {
+            prefix
+                .as_ref()
+                .is_none_or(|prefix| attrib.key.starts_with(prefix))
+        })
+        .collect();
+
+    if attribs.is_empty() {
+        println!("No attributes to copy from customer {}.", from);
+        return BatchReport::default();
+    }
+
+    let (to, excluded) = apply_exclusions(&provider, to, |id| *id).await;
+
+    let _batch_lock = acquire_batch_lock(&provider, "copy-attributes");
+    install_cancel_handler();
+
+    let (mut copied, mut failed) = (0, 0);
+
+    for id in to.iter().filter(|id| **id != from) {
+        if is_cancelled() {
+            println!(
+                "{} Interrupted – attributes not copied to remaining customers.",
+                "Warning".black().on_yellow()
+            );
+            break;
+        }
+
+        let mut customer_attribs = CustomerAttributes::new();
+        attribs.iter().for_each(|attrib| {
+            customer_attribs.add_attribute(&attrib.key, &attrib.value);
+        });
+
+        match try_update_customer_attributes(&provider, *id, customer_attribs).await {
+            Ok(_) => {
+                copied += 1;
+                println!(
+                    "{}Copied {} attribute(s) to customer {}",
+                    "Success ".green(),
+                    attribs.len(),
+                    id
+                );
+            }
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "{} Could not copy attributes to customer {}: {}",
+                    "Error".white().on_red(),
+                    id,
+                    dracoon_error_to_string(e)
+                );
+            }
+        }
+    }
+
+    println!(
+        "customers: {} | copied: {} | failed: {}",
+        to.len(),
+        copied,
+        failed
+    );
+
+    let targets = to.iter().filter(|id| **id != from).count();
+    BatchReport::new(copied, failed, targets - copied - failed).with_excluded(excluded)
+}
+
+/// Renames an attribute key (value is written under the new key, old key is deleted) for
+/// the given customers or all customers.
+pub async fn migrate_customer_attributes(
+    provider: Dracoon<Provisioning>,
+    rename: (String, String),
+    customers: Vec<u64>,
+    all_customers: bool,
+    dry_run: bool,
+) -> BatchReport {
+    let (old_key, new_key) = rename;
+
+    let ids = if all_customers {
+        fetch_all_customers(&provider, None, Some("id:asc".into()))
+            .await
+            .into_iter()
+            .map(|customer| customer.id)
+            .collect()
+    } else {
+        customers
+    };
+    let (ids, excluded) = apply_exclusions(&provider, ids, |id| *id).await;
+
+    // a dry run changes nothing
+    let _batch_lock = (!dry_run).then(|| acquire_batch_lock(&provider, "attributes migrate"));
+    install_cancel_handler();
+
+    let (mut migrated, mut skipped, mut failed) = (0, 0, 0);
+    let mut scanned = 0;
+
+    for id in &ids {
+        if is_cancelled() {
+            println!(
+                "{} Interrupted – remaining customers not scanned.",
+                "Warning".black().on_yellow()
+            );
+            break;
+        }
+        scanned += 1;
+
+        let attribs = match fetch_all_attributes(&provider, *id).await {
+            Ok(attribs) => attribs,
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "{} Could not get attributes of customer {}: {}",
+                    "Error".white().on_red(),
+                    id,
+                    dracoon_error_to_string(e)
+                );
+                continue;
+            }
+        };
+
+        let Some(old) = attribs.iter().find(|attrib| attrib.key == old_key) else {
+            continue;
+        };
+
+        // never silently overwrite a differing value already stored under the new key
+        if let Some(existing) = attribs.iter().find(|attrib| attrib.key == new_key) {
+            if existing.value != old.value {
+                skipped += 1;
+                println!(
+                    "{} customer {} already has {}={} – skipped",
+                    "Warning".black().on_yellow(),
+                    id,
+                    new_key,
+                    existing.value
+                );
+                continue;
+            }
+        }
+
+        if dry_run {
+            migrated += 1;
+            println!(
+                "would rename {} -> {} for customer {} (value: {})",
+                old_key, new_key, id, old.value
+            );
+            continue;
+        }
+
+        let mut customer_attribs = CustomerAttributes::new();
+        customer_attribs.add_attribute(&new_key, &old.value);
+
+        let result = match try_update_customer_attributes(&provider, *id, customer_attribs).await {
+            Ok(_) => {
+                provider
+                    .delete_customer_attribute(*id, old_key.clone())
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(_) => {
+                migrated += 1;
+                println!(
+                    "{}Renamed {} -> {} for customer {}",
+                    "Success ".green(),
+                    old_key,
+                    new_key,
+                    id
+                );
+            }
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "{} Could not rename attribute for customer {}: {}",
+                    "Error".white().on_red(),
+                    id,
+                    dracoon_error_to_string(e)
+                );
+            }
+        }
+    }
+
+    println!(
+        "scanned customers: {} of {} | {}: {} | skipped: {} | failed: {}",
+        scanned,
+        ids.len(),
+        if dry_run { "to migrate" } else { "migrated" },
+        migrated,
+        skipped,
+        failed
+    );
+
+    BatchReport::new(scanned - failed, failed, ids.len() - scanned).with_excluded(excluded)
+}
+
+/// Lists the distinct attribute keys in use across all (filtered) customers with the number of
+/// customers using each key – most used keys first.
+pub async fn list_attribute_keys(
+    provider: Dracoon<Provisioning>,
+    filter: Option<String>,
+    print_type: PrintType,
+) {
+    let customers = fetch_all_customers(&provider, filter, Some("id:asc".into())).await;
+
+    install_cancel_handler();
+
+    let mut usage: HashMap<String, u64> = HashMap::new();
+    let (mut scanned, mut failed) = (0, 0);
+
+    for customer in &customers {
+        if is_cancelled() {
+            eprintln!(
+                "{} Interrupted – partial results ({} of {} customers scanned).",
+                "Warning".black().on_yellow(),
+                scanned,
+                customers.len()
+            );
+            break;
+        }
+        scanned += 1;
+
+        match fetch_all_attributes(&provider, customer.id).await {
+            Ok(attribs) => attribs
+                .into_iter()
+                .for_each(|attrib| *usage.entry(attrib.key).or_default() += 1),
+            Err(e) => {
+                failed += 1;
+                eprintln!(
+                    "{} Could not get attributes of customer {}: {}",
+                    "Error".white().on_red(),
+                    customer.id,
+                    dracoon_error_to_string(e)
+                );
+            }
+        }
+    }
+
+    let mut keys: Vec<_> = usage.into_iter().collect();
+    keys.sort_by(|(a_key, a_count), (b_key, b_count)| {
+        b_count.cmp(a_count).then_with(|| a_key.cmp(b_key))
+    });
+
+    match print_type {
+        PrintType::Csv => {
+            print_csv_header("key,customers");
+            for (key, count) in &keys {
+                println!("{},{}", key, count);
+            }
+        }
+        PrintType::Pretty => {
+            for (key, count) in &keys {
+                println!("{} | customers: {}", key.bold(), count);
+            }
+            println!(
+                "keys: {} | scanned customers: {} of {} | failed: {}",
+                keys.len(),
+                scanned,
+                customers.len(),
+                failed
+            );
+        }
+        PrintType::Json => {
+            let items: Vec<_> = keys
+                .iter()
+                .map(|(key, count)| json!({ "key": key, "customers": count }))
+                .collect();
+            print_json(json!({
+                "scanned": scanned,
+                "total": customers.len(),
+                "failed": failed,
+                "items": items,
+            }))
+        }
+    }
+
+    if failed > 0 || is_cancelled() {
+        super::exit(1)
+    }
+}
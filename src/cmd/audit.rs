@@ -0,0 +1,111 @@
+use super::{fetch_all_customers, format_number, print_csv_header, print_json, PrintType};
+use crate::config;
+use colored::*;
+use dco3::{auth::Provisioning, provisioning::Customer, Dracoon};
+use serde_json::json;
+
+// default: quota max below 1 GB is most likely a unit typo (MB instead of GB)
+const DEFAULT_MIN_QUOTA: u64 = 1 << 30;
+
+const QUOTA_AUDIT_CSV_HEADER: &str = "id,companyName,quotaMax,quotaUsed,userMax,issue";
+
+/// Returns the issues of a customer's quota / user limits.
+fn quota_issues(customer: &Customer, min_quota: u64, granularity: Option<u64>) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    if customer.quota_max < min_quota {
+        issues.push(format!("quota max below {} bytes", min_quota));
+    }
+    if let Some(granularity) = granularity.filter(|granularity| *granularity > 0) {
+        if !customer.quota_max.is_multiple_of(granularity) {
+            issues.push(format!("quota max not a multiple of {} bytes", granularity));
+        }
+    }
+    if customer.quota_used > customer.quota_max {
+        issues.push("quota used exceeds quota max".into());
+    }
+    if customer.user_max == 0 {
+        issues.push("user max is 0 – no users can be created".into());
+    }
+
+    issues
+}
+
+/// Lists customers with suspicious quota / user limits – exits with 1 if any are found.
+pub async fn audit_quotas(
+    provider: Dracoon<Provisioning>,
+    min_quota: Option<u64>,
+    granularity: Option<u64>,
+    filter: Option<String>,
+    print_type: PrintType,
+) {
+    // flags take precedence over the config file
+    let audit = config::load_config().unwrap_or_default().audit;
+    let min_quota = min_quota.or(audit.min_quota).unwrap_or(DEFAULT_MIN_QUOTA);
+    let granularity = granularity.or(audit.quota_granularity);
+
+    let customers = fetch_all_customers(&provider, filter, Some("companyName:asc".into())).await;
+
+    let findings: Vec<_> = customers
+        .iter()
+        .map(|customer| (customer, quota_issues(customer, min_quota, granularity)))
+        .filter(|(_, issues)| !issues.is_empty())
+        .collect();
+
+    match print_type {
+        PrintType::Csv => {
+            print_csv_header(QUOTA_AUDIT_CSV_HEADER);
+            for (customer, issues) in &findings {
+                for issue in issues {
+                    println!(
+                        "{},{},{},{},{},{}",
+                        customer.id,
+                        customer.company_name,
+                        customer.quota_max,
+                        customer.quota_used,
+                        customer.user_max,
+                        issue
+                    );
+                }
+            }
+        }
+        PrintType::Pretty => {
+            for (customer, issues) in &findings {
+                println!(
+                    "{} (id: {}) | quota max: {} | quota used: {} | users max: {}",
+                    customer.company_name.bold(),
+                    customer.id,
+                    format_number(customer.quota_max),
+                    format_number(customer.quota_used),
+                    format_number(customer.user_max)
+                );
+                for issue in issues {
+                    println!("  {}", issue.yellow());
+                }
+            }
+            println!(
+                "customers: {} | flagged: {}",
+                customers.len(),
+                findings.len()
+            );
+        }
+        PrintType::Json => print_json(json!({
+            "customers": customers.len(),
+            "items": findings
+                .iter()
+                .map(|(customer, issues)| json!({
+                    "id": customer.id,
+                    "companyName": customer.company_name,
+                    "quotaMax": customer.quota_max,
+                    "quotaUsed": customer.quota_used,
+                    "userMax": customer.user_max,
+                    "issues": issues,
+                }))
+                .collect::<Vec<_>>(),
+        })),
+    }
+
+    if !findings.is_empty() {
+        super::exit(1)
+    }
+}
@@ -0,0 +1,85 @@
+use super::{deadline_exceeded, DEADLINE_EXIT_CODE};
+use colored::*;
+
+// exit code of batch commands if some items failed (or were not processed)
+const BATCH_PARTIAL_EXIT_CODE: i32 = 3;
+
+// exit code of batch commands if all items failed
+const BATCH_FAILED_EXIT_CODE: i32 = 4;
+
+/// Result of a batch command (items succeeded, failed and not processed) – rendered by `finish_batch`.
+/// Items not processed (interrupted) count as failed for the exit code, excluded customers
+/// (--exclude-attribute, --exclude-ids) are listed but not counted.
+#[derive(Clone, Default)]
+#[must_use]
+pub struct BatchReport {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub not_processed: usize,
+    pub excluded: Vec<u64>,
+}
+
+impl BatchReport {
+    pub fn new(succeeded: usize, failed: usize, not_processed: usize) -> Self {
+        Self {
+            succeeded,
+            failed,
+            not_processed,
+            excluded: Vec::new(),
+        }
+    }
+
+    pub fn with_excluded(mut self, excluded: Vec<u64>) -> Self {
+        self.excluded = excluded;
+        self
+    }
+
+    pub fn total(&self) -> usize {
+        self.succeeded + self.failed + self.not_processed
+    }
+
+    /// 0 if all items succeeded, 3 if some failed, 4 if all failed.
+    pub fn exit_code(&self) -> i32 {
+        match (self.succeeded, self.failed + self.not_processed) {
+            (_, 0) => 0,
+            (0, _) => BATCH_FAILED_EXIT_CODE,
+            _ => BATCH_PARTIAL_EXIT_CODE,
+        }
+    }
+}
+
+/// Prints the final summary line of a batch command (to stderr, output stays parseable) and exits
+/// with the exit code of the report unless all items succeeded – a stopped deadline keeps exit
+/// code 124. Nothing is printed if no items were processed or excluded (e.g. dry run or nothing
+/// to do).
+pub fn finish_batch(report: BatchReport) {
+    if report.total() > 0 || !report.excluded.is_empty() {
+        let summary = match report.exit_code() {
+            0 => " all succeeded ".black().on_green(),
+            BATCH_FAILED_EXIT_CODE => " all failed ".white().on_red(),
+            _ => " some failed ".black().on_yellow(),
+        };
+
+        eprintln!(
+            "{} succeeded: {} | failed: {} | not processed: {} | total: {} | excluded: {}",
+            summary,
+            report.succeeded,
+            report.failed,
+            report.not_processed,
+            report.total(),
+            report.excluded.len()
+        );
+
+        if !report.excluded.is_empty() {
+            let ids: Vec<_> = report.excluded.iter().map(u64::to_string).collect();
+            eprintln!("excluded ids: {}", ids.join(","));
+        }
+    }
+
+    if deadline_exceeded() {
+        super::exit(DEADLINE_EXIT_CODE)
+    }
+    if report.exit_code() != 0 {
+        super::exit(report.exit_code())
+    }
+}
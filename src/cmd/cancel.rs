@@ -0,0 +1,74 @@
+use colored::*;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+// exit code used when aborting on a second interrupt (128 + SIGINT)
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+// exit code used when the deadline is exceeded (same as timeout(1))
+pub const DEADLINE_EXIT_CODE: i32 = 124;
+
+// time granted after the deadline to finish in-flight requests and print partial results
+const DEADLINE_GRACE_SECS: u64 = 30;
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+static DEADLINE_EXCEEDED: AtomicBool = AtomicBool::new(false);
+
+/// Installs a Ctrl-C handler for bulk operations: the first interrupt requests a graceful stop
+/// (no new requests, in-flight requests finish), a second interrupt aborts immediately.
+/// Only install for non-interactive commands – prompts should remain interruptible.
+pub fn install_cancel_handler() {
+    tokio::spawn(async {
+        loop {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+
+            if CANCELLED.swap(true, Ordering::SeqCst) {
+                eprintln!("{} Aborted.", "Error".white().on_red());
+                super::exit(INTERRUPTED_EXIT_CODE)
+            }
+
+            eprintln!(
+                "{}",
+                "Interrupt received – finishing in-flight requests (press Ctrl-C again to abort)"
+                    .yellow()
+            );
+        }
+    });
+}
+
+/// Bounds the run time of a command: once the deadline is reached, a graceful stop is requested
+/// (like on Ctrl-C, partial results are printed) – after a grace period, the command is aborted.
+pub fn set_deadline(deadline: Duration) {
+    tokio::spawn(async move {
+        tokio::time::sleep(deadline).await;
+
+        DEADLINE_EXCEEDED.store(true, Ordering::SeqCst);
+        CANCELLED.store(true, Ordering::SeqCst);
+        eprintln!(
+            "{}",
+            format!(
+                "Deadline of {}s reached – finishing in-flight requests (partial results)",
+                deadline.as_secs()
+            )
+            .yellow()
+        );
+
+        tokio::time::sleep(Duration::from_secs(DEADLINE_GRACE_SECS)).await;
+        eprintln!("{} Deadline exceeded – aborted.", "Error".white().on_red());
+        super::exit(DEADLINE_EXIT_CODE)
+    });
+}
+
+/// Returns true if the deadline was reached (results are partial).
+pub fn deadline_exceeded() -> bool {
+    DEADLINE_EXCEEDED.load(Ordering::SeqCst)
+}
+
+/// Returns true once a graceful stop has been requested.
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,111 @@
+use super::{
+    dry_run_active, fetch_all_customers, handle_errors, is_cancelled, NewCustomerRequestDco3,
+};
+use crate::config;
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+
+/// Allocated (sum of quota max / user max of all customers) vs. contract ceiling of an instance
+struct Allocation {
+    name: &'static str,
+    allocated: u64,
+    requested: u64,
+    ceiling: u64,
+}
+
+impl Allocation {
+    fn exceeded(&self) -> bool {
+        self.allocated.saturating_add(self.requested) > self.ceiling
+    }
+}
+
+/// Checks the remaining capacity of the instance before creating customers – if the profile of
+/// the url (or alias) sets a contract ceiling (`quota_ceiling`, `user_ceiling`) and the new customers
+/// would exceed it, asks to confirm (unless `yes`). Exits if not confirmed.
+pub async fn check_capacity(
+    provider: &Dracoon<Provisioning>,
+    url_or_alias: &str,
+    new_customers: &[NewCustomerRequestDco3],
+    yes: bool,
+) {
+    // a dry run creates nothing
+    if dry_run_active() {
+        return;
+    }
+
+    let profile = match config::find_profile(url_or_alias) {
+        Ok(profile) => profile.map(|(_, profile)| profile),
+        Err(ref e) => handle_errors(e),
+    };
+    let Some(profile) = profile else {
+        return;
+    };
+    if profile.quota_ceiling.is_none() && profile.user_ceiling.is_none() {
+        return;
+    }
+
+    let customers = fetch_all_customers(provider, None, Some("id:asc".into())).await;
+    // a partial sum would understate the allocation
+    if is_cancelled() {
+        super::exit(1)
+    }
+
+    let allocations = [
+        profile.quota_ceiling.map(|ceiling| Allocation {
+            name: "quota max",
+            allocated: customers.iter().map(|customer| customer.quota_max).sum(),
+            requested: new_customers
+                .iter()
+                .map(|customer| customer.quota_max)
+                .sum(),
+            ceiling,
+        }),
+        profile.user_ceiling.map(|ceiling| Allocation {
+            name: "user max",
+            allocated: customers.iter().map(|customer| customer.user_max).sum(),
+            requested: new_customers.iter().map(|customer| customer.user_max).sum(),
+            ceiling,
+        }),
+    ];
+
+    let exceeded: Vec<_> = allocations
+        .iter()
+        .flatten()
+        .filter(|allocation| allocation.exceeded())
+        .collect();
+
+    if exceeded.is_empty() {
+        return;
+    }
+
+    let subject = match new_customers.len() {
+        1 => "New customer exceeds",
+        _ => "New customers exceed",
+    };
+    for allocation in &exceeded {
+        eprintln!(
+            "{} {} the contract ceiling – {}: allocated {} + new {} > ceiling {}",
+            "Warning".black().on_yellow(),
+            subject,
+            allocation.name,
+            allocation.allocated,
+            allocation.requested,
+            allocation.ceiling
+        );
+    }
+
+    if yes {
+        return;
+    }
+
+    let confirmed = dialoguer::Confirm::new()
+        .with_prompt("Create anyway?")
+        .default(false)
+        .interact()
+        .unwrap_or(false);
+
+    if !confirmed {
+        println!("{} Aborted.", "Error".white().on_red());
+        super::exit(1)
+    }
+}
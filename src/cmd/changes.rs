@@ -0,0 +1,160 @@
+use super::{
+    fetch_all_customers, handle_errors, is_cancelled, print_csv_header, print_json, PrintType,
+};
+use crate::state::{self, Snapshot, SnapshotCustomer, Tombstone};
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use serde_json::json;
+
+const CHANGES_CSV_HEADER: &str = "change,id,companyName,lastSeenAt,detectedAt";
+
+/// Change of a customer between two snapshots
+struct Change {
+    kind: &'static str,
+    id: u64,
+    company_name: String,
+    last_seen_at: Option<String>,
+}
+
+/// Lists customers created, updated or deleted since the last run (snapshot per url). Customers
+/// missing from the current list are stored as tombstones – `deleted_only` lists deletions only.
+pub async fn list_changes(
+    provider: Dracoon<Provisioning>,
+    deleted_only: bool,
+    print_type: PrintType,
+) {
+    let url = provider.build_api_url("/").to_string();
+
+    let previous = match state::get_snapshot(&url) {
+        Ok(previous) => previous,
+        Err(ref e) => handle_errors(e),
+    };
+
+    let customers = fetch_all_customers(&provider, None, Some("id:asc".into())).await;
+
+    // customers missing from a partial list would be marked as deleted
+    if is_cancelled() {
+        eprintln!(
+            "{} Interrupted – snapshot not updated.",
+            "Warning".black().on_yellow()
+        );
+        super::exit(1)
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let current = customers
+        .iter()
+        .map(|customer| {
+            let snapshot_customer = SnapshotCustomer {
+                company_name: customer.company_name.clone(),
+                updated_at: customer.updated_at.clone(),
+            };
+            (customer.id, snapshot_customer)
+        })
+        .collect();
+
+    let Some(previous) = previous else {
+        let snapshot = Snapshot {
+            taken_at: now,
+            customers: current,
+            tombstones: Default::default(),
+        };
+        eprintln!(
+            "No previous snapshot – stored {} customer(s) as baseline.",
+            snapshot.customers.len()
+        );
+        if let Err(ref e) = state::set_snapshot(&url, snapshot) {
+            handle_errors(e)
+        }
+        return;
+    };
+
+    let mut changes = Vec::new();
+    let mut tombstones = previous.tombstones;
+
+    for (id, customer) in &previous.customers {
+        if !current.contains_key(id) {
+            changes.push(Change {
+                kind: "deleted",
+                id: *id,
+                company_name: customer.company_name.clone(),
+                last_seen_at: Some(previous.taken_at.clone()),
+            });
+            tombstones.insert(
+                *id,
+                Tombstone {
+                    company_name: customer.company_name.clone(),
+                    last_seen_at: previous.taken_at.clone(),
+                    detected_at: now.clone(),
+                },
+            );
+        }
+    }
+
+    if !deleted_only {
+        for (id, customer) in &current {
+            let kind = match previous.customers.get(id) {
+                None => "created",
+                Some(before) if before.updated_at != customer.updated_at => "updated",
+                Some(_) => continue,
+            };
+            changes.push(Change {
+                kind,
+                id: *id,
+                company_name: customer.company_name.clone(),
+                last_seen_at: None,
+            });
+        }
+    }
+
+    let snapshot = Snapshot {
+        taken_at: now.clone(),
+        customers: current,
+        tombstones,
+    };
+    if let Err(ref e) = state::set_snapshot(&url, snapshot) {
+        handle_errors(e)
+    }
+
+    match print_type {
+        PrintType::Csv => {
+            print_csv_header(CHANGES_CSV_HEADER);
+            for change in &changes {
+                println!(
+                    "{},{},{},{},{}",
+                    change.kind,
+                    change.id,
+                    change.company_name,
+                    change.last_seen_at.as_deref().unwrap_or_default(),
+                    now
+                );
+            }
+        }
+        PrintType::Json => {
+            let items: Vec<_> = changes
+                .iter()
+                .map(|change| {
+                    json!({
+                        "change": change.kind,
+                        "id": change.id,
+                        "companyName": change.company_name,
+                        "lastSeenAt": change.last_seen_at,
+                        "detectedAt": now,
+                    })
+                })
+                .collect();
+            print_json(json!({ "since": previous.taken_at, "items": items }));
+        }
+        PrintType::Pretty => {
+            println!("changes since {}: {}", previous.taken_at, changes.len());
+            for change in &changes {
+                let kind = match change.kind {
+                    "deleted" => change.kind.red(),
+                    "created" => change.kind.green(),
+                    _ => change.kind.normal(),
+                };
+                println!("{} | {} (id: {})", kind, change.company_name, change.id);
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use super::{
+    format_timestamp, get_json, http_client, json_query_active, print_json, NewCustomerRequestDco3,
+    UpdateCustomerPatch,
+};
+use crate::state::{self, Capabilities};
+use chrono::{DateTime, Duration, Utc};
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use tokio::sync::OnceCell;
+
+// cached capabilities are probed again after this time
+const CAPABILITIES_MAX_AGE_HOURS: i64 = 24;
+
+/// Customer fields not known to all DRACOON servers (older on-prem instances) – probed per server.
+const OPTIONAL_FIELDS: [&str; 4] = [
+    "webhooksMax",
+    "cntInternalUser",
+    "cntGuestUser",
+    "customerUuid",
+];
+
+// optional fields supported by the server (probed on first use) – none if unknown (all fields are
+// used)
+static SUPPORTED_FIELDS: OnceCell<Option<BTreeSet<String>>> = OnceCell::const_new();
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SoftwareVersion {
+    rest_api_version: String,
+}
+
+/// Reads the API version from the public software version endpoint (informational only).
+async fn detect_api_version(url: &str) -> Result<String, String> {
+    let version_url = format!("{}/api/v4/public/software/version", url);
+
+    match http_client().get(&version_url).send().await {
+        Ok(res) if res.status().is_success() => res
+            .json::<SoftwareVersion>()
+            .await
+            .map(|version| version.rest_api_version)
+            .map_err(|e| e.to_string()),
+        Ok(res) => Err(res.status().to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Returns the optional fields contained in the first customer of a customer list response – none
+/// if the list is empty (nothing to probe). Servers return the fields they know (even if null).
+fn probe_fields(list: &Value) -> Option<BTreeSet<String>> {
+    let customer = list["items"].get(0)?.as_object()?;
+
+    Some(
+        OPTIONAL_FIELDS
+            .iter()
+            .filter(|field| customer.contains_key(**field))
+            .map(|field| field.to_string())
+            .collect(),
+    )
+}
+
+/// Returns whether a field is supported given the probed optional fields (none: all fields).
+fn field_supported(supported: Option<&BTreeSet<String>>, field: &str) -> bool {
+    supported.is_none_or(|fields| !OPTIONAL_FIELDS.contains(&field) || fields.contains(field))
+}
+
+fn base_url(provider: &Dracoon<Provisioning>) -> String {
+    provider
+        .build_api_url("/")
+        .as_str()
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Returns the capabilities of the server – probed via a customer list request (limit 1) and
+/// cached per url for 24h (`refresh` probes again). Not cached if there is no customer to probe.
+async fn cached_capabilities(
+    provider: &Dracoon<Provisioning>,
+    refresh: bool,
+) -> Result<Capabilities, String> {
+    let url = base_url(provider);
+
+    if let (false, Ok(Some(capabilities))) = (refresh, state::get_capabilities(&url)) {
+        let fresh = DateTime::parse_from_rfc3339(&capabilities.detected_at).is_ok_and(|at| {
+            Utc::now().signed_duration_since(at) < Duration::hours(CAPABILITIES_MAX_AGE_HOURS)
+        });
+        if fresh && capabilities.fields.is_some() {
+            return Ok(capabilities);
+        }
+    }
+
+    let list = get_json(provider, "", &[("limit", Some("1".into()))]).await?;
+    let capabilities = Capabilities {
+        api_version: detect_api_version(&url).await.ok(),
+        detected_at: Utc::now().to_rfc3339(),
+        fields: probe_fields(&list).map(|fields| fields.into_iter().collect()),
+    };
+    // a missing cache only costs another probe
+    if capabilities.fields.is_some() {
+        let _ = state::set_capabilities(&url, capabilities.clone());
+    }
+
+    Ok(capabilities)
+}
+
+/// Probes the optional fields the server supports – falls back to all fields if they cannot be
+/// determined (no customer to probe or the probe failed).
+async fn negotiate_fields(provider: &Dracoon<Provisioning>) -> Option<BTreeSet<String>> {
+    match cached_capabilities(provider, false).await {
+        Ok(Capabilities {
+            fields: Some(fields),
+            ..
+        }) => Some(fields.into_iter().collect()),
+        Ok(_) => {
+            eprintln!(
+                "{} No customer to probe the supported fields – using all fields.",
+                "Warning".black().on_yellow()
+            );
+            None
+        }
+        Err(e) => {
+            eprintln!(
+                "{} Could not probe the supported fields ({}) – using all fields.",
+                "Warning".black().on_yellow(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Prints the API version of the server and which optional fields it supports.
+/// `refresh` ignores the cached capabilities.
+pub async fn print_capabilities(provider: Dracoon<Provisioning>, refresh: bool, json: bool) {
+    let capabilities = match cached_capabilities(&provider, refresh).await {
+        Ok(capabilities) => capabilities,
+        Err(e) => {
+            println!(
+                "{} Could not probe the server: {}",
+                "Error".white().on_red(),
+                e
+            );
+            super::exit(1)
+        }
+    };
+    let supported = capabilities
+        .fields
+        .as_ref()
+        .map(|fields| fields.iter().cloned().collect::<BTreeSet<_>>());
+
+    if json || json_query_active() {
+        let fields: Vec<_> = OPTIONAL_FIELDS
+            .iter()
+            .map(|field| {
+                json!({
+                    "field": field,
+                    "supported": supported.as_ref().map(|fields| fields.contains(*field)),
+                })
+            })
+            .collect();
+        print_json(json!({
+            "url": base_url(&provider),
+            "apiVersion": capabilities.api_version,
+            "detectedAt": capabilities.detected_at,
+            "fields": fields,
+        }));
+        return;
+    }
+
+    println!(
+        "API version: {} (probed at {})",
+        capabilities.api_version.as_deref().unwrap_or("unknown"),
+        format_timestamp(&capabilities.detected_at)
+    );
+    let Some(supported) = supported else {
+        println!("No customer to probe the supported fields – all fields are used.");
+        return;
+    };
+    for field in OPTIONAL_FIELDS {
+        let status = match supported.contains(field) {
+            true => " OK ".black().on_green(),
+            false => " NO ".white().on_red(),
+        };
+        println!("{} {}", status, field);
+    }
+}
+
+/// Returns whether a field is supported by the server – the server is probed (once per run) only
+/// when an optional field is about to be sent.
+async fn supports_field(provider: &Dracoon<Provisioning>, field: &str) -> bool {
+    if !OPTIONAL_FIELDS.contains(&field) {
+        return true;
+    }
+
+    let supported = SUPPORTED_FIELDS
+        .get_or_init(|| negotiate_fields(provider))
+        .await;
+
+    field_supported(supported.as_ref(), field)
+}
+
+fn warn_dropped_field(field: &str) {
+    eprintln!(
+        "{} {} is not supported by the server – ignored.",
+        "Warning".black().on_yellow(),
+        field
+    );
+}
+
+/// Removes fields from a new customer request the server doesn't support.
+pub(super) async fn compat_new_customer(
+    provider: &Dracoon<Provisioning>,
+    mut req: NewCustomerRequestDco3,
+) -> NewCustomerRequestDco3 {
+    if req.webhooks_max.is_some() && !supports_field(provider, "webhooksMax").await {
+        warn_dropped_field("webhooksMax");
+        req.webhooks_max = None;
+    }
+
+    req
+}
+
+/// Removes fields from a customer update the server doesn't support.
+pub(super) async fn compat_update_patch(
+    provider: &Dracoon<Provisioning>,
+    mut patch: UpdateCustomerPatch,
+) -> UpdateCustomerPatch {
+    if patch.webhooks_max.is_some() && !supports_field(provider, "webhooksMax").await {
+        warn_dropped_field("webhooksMax");
+        patch.webhooks_max = None;
+    }
+
+    patch
+}
+
+/// Removes optional fields the server didn't return (null) from JSON output (objects) – needs no
+/// probe, servers not supporting a field never return it.
+pub(super) fn compat_output(mut value: Value) -> Value {
+    if let Some(object) = value.as_object_mut() {
+        object
+            .retain(|field, value| !(OPTIONAL_FIELDS.contains(&field.as_str()) && value.is_null()));
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_fields_of_current_server() {
+        let list = json!({
+            "range": { "offset": 0, "limit": 1, "total": 12 },
+            "items": [{
+                "id": 1,
+                "companyName": "Acme",
+                "webhooksMax": null,
+                "cntInternalUser": 3,
+                "cntGuestUser": 0,
+                "customerUuid": "0c4f5b1e",
+            }],
+        });
+
+        let fields = probe_fields(&list).unwrap();
+
+        assert_eq!(fields.len(), OPTIONAL_FIELDS.len());
+        assert!(fields.contains("webhooksMax"));
+    }
+
+    #[test]
+    fn probe_fields_of_older_server() {
+        let list = json!({
+            "range": { "offset": 0, "limit": 1, "total": 12 },
+            "items": [{ "id": 1, "companyName": "Acme", "cntInternalUser": 3, "cntGuestUser": 0 }],
+        });
+
+        let fields = probe_fields(&list).unwrap();
+
+        assert!(field_supported(Some(&fields), "cntInternalUser"));
+        assert!(!field_supported(Some(&fields), "webhooksMax"));
+        assert!(!field_supported(Some(&fields), "customerUuid"));
+        // fields that are not optional are always supported
+        assert!(field_supported(Some(&fields), "quotaMax"));
+    }
+
+    #[test]
+    fn probe_fields_without_customers() {
+        let list = json!({ "range": { "offset": 0, "limit": 1, "total": 0 }, "items": [] });
+
+        assert_eq!(probe_fields(&list), None);
+        assert!(field_supported(None, "webhooksMax"));
+    }
+}
@@ -0,0 +1,144 @@
+use super::{
+    attributes::fetch_all_attributes, dracoon_error_to_string, handle_dracoon_errors, http_client,
+    json_query_active, print_update_outcome, try_update_customer, ContractType,
+    UpdateCustomerPatch, UpdateType,
+};
+use colored::*;
+use dco3::{auth::Provisioning, CustomerProvisioning, Dracoon};
+use serde_json::json;
+
+/// Converts a trial (demo) customer to pay: sets contract type, quota max and user max in one
+/// update, deletes the trial attributes (keys starting with `attribute_prefix`) and notifies the
+/// webhook (if passed). Aborts before any change if the limits are below the current usage.
+pub async fn convert_trial(
+    provider: Dracoon<Provisioning>,
+    id: u64,
+    quota_max: u64,
+    user_max: u64,
+    attribute_prefix: String,
+    webhook: Option<String>,
+    yes: bool,
+) {
+    let customer = match provider.get_customer(id, None).await {
+        Ok(customer) => customer,
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not get customer."));
+            super::exit(1)
+        }
+    };
+
+    if customer.customer_contract_type != ContractType::Demo.as_str() {
+        println!(
+            "{} Customer {} (id: {}) is no trial – contract type is {}.",
+            "Error".white().on_red(),
+            customer.company_name,
+            id,
+            customer.customer_contract_type
+        );
+        super::exit(1)
+    }
+
+    if quota_max < customer.quota_used || user_max < customer.user_used {
+        println!(
+            "{} Limits below current usage (quota used: {} | users used: {}) – nothing converted.",
+            "Error".white().on_red(),
+            customer.quota_used,
+            customer.user_used
+        );
+        super::exit(1)
+    }
+
+    if !yes {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Convert {} (id: {}) to pay with quota max {} and user max {}?",
+                customer.company_name, id, quota_max, user_max
+            ))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+
+        if !confirmed {
+            println!("{} Aborted.", "Error".white().on_red());
+            super::exit(1)
+        }
+    }
+
+    let patch = UpdateCustomerPatch {
+        customer_contract_type: Some(ContractType::Pay),
+        quota_max: Some(quota_max),
+        user_max: Some(user_max),
+        ..Default::default()
+    };
+    let outcome = match try_update_customer(&provider, id, UpdateType::Patch(patch)).await {
+        Ok(outcome) => outcome,
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not convert customer."));
+            super::exit(1)
+        }
+    };
+    print_update_outcome(&outcome, json_query_active());
+
+    // the customer is converted – remaining steps only warn and set the exit code
+    let mut failed = false;
+
+    let trial_keys: Vec<_> = match fetch_all_attributes(&provider, id).await {
+        Ok(attribs) => attribs
+            .into_iter()
+            .map(|attrib| attrib.key)
+            .filter(|key| key.starts_with(&attribute_prefix))
+            .collect(),
+        Err(e) => {
+            eprintln!(
+                "{} Could not get attributes – trial attributes not cleared: {}",
+                "Warning".black().on_yellow(),
+                dracoon_error_to_string(e)
+            );
+            failed = true;
+            Vec::new()
+        }
+    };
+
+    let mut cleared = Vec::new();
+    for key in trial_keys {
+        match provider.delete_customer_attribute(id, key.clone()).await {
+            Ok(_) => cleared.push(key),
+            Err(e) => {
+                eprintln!(
+                    "{} Could not delete attribute {}: {}",
+                    "Warning".black().on_yellow(),
+                    key,
+                    dracoon_error_to_string(e)
+                );
+                failed = true;
+            }
+        }
+    }
+    eprintln!("cleared trial attributes: {}", cleared.len());
+
+    if let Some(webhook) = webhook {
+        let payload = json!({
+            "event": "convert-trial",
+            "id": outcome.id,
+            "companyName": outcome.company_name,
+            "customerContractType": outcome.customer_contract_type,
+            "quotaMax": outcome.quota_max,
+            "userMax": outcome.user_max,
+            "clearedAttributes": cleared,
+        });
+
+        let result = match http_client().post(&webhook).json(&payload).send().await {
+            Ok(res) if res.status().is_success() => Ok(()),
+            Ok(res) => Err(format!("webhook returned {}", res.status())),
+            Err(e) => Err(format!("webhook failed: {}", e)),
+        };
+        if let Err(e) = result {
+            eprintln!("{} {}", "Warning".black().on_yellow(), e);
+            failed = true;
+        }
+    }
+
+    if failed {
+        super::exit(1)
+    }
+}
@@ -0,0 +1,197 @@
+use super::models::{ContractType, NewCustomerRequest};
+use super::{
+    compat_new_customer, dracoon_error_to_string, dry_run_active, install_cancel_handler,
+    is_cancelled, print_created_customer, print_dry_run, run_lock::acquire_batch_lock,
+    try_create_customer, BatchReport, NewCustomerRequestDco3,
+};
+use colored::*;
+use dco3::{
+    auth::Provisioning,
+    provisioning::FirstAdminUser,
+    users::{AuthMethod, UserAuthData},
+    Dracoon,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::path::Path;
+
+/// Row of a customer batch CSV (header names as in the JSON payload, first admin flattened).
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomerRow {
+    company_name: Option<String>,
+    customer_contract_type: Option<String>,
+    quota_max: u64,
+    user_max: u64,
+    first_name: String,
+    last_name: String,
+    email: Option<String>,
+    user_name: Option<String>,
+    phone: Option<String>,
+    trial_days: Option<u64>,
+    is_locked: Option<bool>,
+    provider_customer_id: Option<String>,
+    webhooks_max: Option<u64>,
+}
+
+impl TryFrom<CustomerRow> for NewCustomerRequestDco3 {
+    type Error = String;
+
+    fn try_from(row: CustomerRow) -> Result<Self, Self::Error> {
+        let contract_type = match row.customer_contract_type.as_deref() {
+            Some(contract_type) if !contract_type.is_empty() => contract_type.parse()?,
+            _ => ContractType::default(),
+        };
+        let non_empty = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+
+        // TODO: remove manual build once dco3 fixes bug with must_change_password
+        let auth_data = UserAuthData::builder(AuthMethod::Basic)
+            .with_must_change_password(true)
+            .build();
+
+        let first_admin_user = FirstAdminUser {
+            first_name: row.first_name,
+            last_name: row.last_name,
+            user_name: non_empty(row.user_name),
+            email: non_empty(row.email),
+            auth_data: Some(auth_data),
+            notify_user: Some(true),
+            receiver_language: None,
+            phone: non_empty(row.phone),
+        };
+
+        let mut new_customer = NewCustomerRequestDco3::builder(
+            contract_type.as_str(),
+            row.quota_max,
+            row.user_max,
+            first_admin_user,
+        )
+        .build();
+        new_customer.company_name = non_empty(row.company_name);
+        new_customer.trial_days = row.trial_days;
+        new_customer.is_locked = row.is_locked;
+        new_customer.provider_customer_id = non_empty(row.provider_customer_id);
+        new_customer.webhooks_max = row.webhooks_max;
+
+        Ok(new_customer)
+    }
+}
+
+fn is_csv_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+}
+
+/// Returns true if the file holds a batch of customers – a CSV file or a JSON array.
+pub fn is_customer_batch_file(path: &str) -> bool {
+    is_csv_file(path)
+        || std::fs::read_to_string(path).is_ok_and(|raw| raw.trim_start().starts_with('['))
+}
+
+fn parse_csv_customers(path: &str) -> Result<Vec<NewCustomerRequestDco3>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| e.to_string())?;
+
+    reader
+        .deserialize::<CustomerRow>()
+        .enumerate()
+        .map(|(idx, row)| {
+            row.map_err(|e| e.to_string())
+                .and_then(NewCustomerRequestDco3::try_from)
+                .map_err(|e| format!("row {}: {}", idx + 1, e))
+        })
+        .collect()
+}
+
+fn parse_json_customers(path: &str) -> Result<Vec<NewCustomerRequestDco3>, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let customers: Vec<NewCustomerRequest> =
+        serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+
+    Ok(customers.into_iter().map(Into::into).collect())
+}
+
+/// Parses a customer batch file (JSON array or CSV with header) – exits if any row is invalid,
+/// nothing is created from a broken file.
+pub fn parse_customers_from_file(path: &str) -> Vec<NewCustomerRequestDco3> {
+    let customers = if is_csv_file(path) {
+        parse_csv_customers(path)
+    } else {
+        parse_json_customers(path)
+    };
+
+    match customers {
+        Ok(customers) if !customers.is_empty() => customers,
+        Ok(_) => {
+            println!("{} No customers in file {}", "Error".white().on_red(), path);
+            super::exit(1)
+        }
+        Err(e) => {
+            println!(
+                "{} Could not parse customers from file {}: {}",
+                "Error".white().on_red(),
+                path,
+                e
+            );
+            super::exit(1)
+        }
+    }
+}
+
+/// Creates the customers of a batch file one after another – a failed row doesn't stop the batch.
+/// Prints the outcome per row and returns the summary (rows left after Ctrl-C are not processed).
+pub async fn create_customers(
+    provider: &Dracoon<Provisioning>,
+    customers: Vec<NewCustomerRequestDco3>,
+) -> BatchReport {
+    if dry_run_active() {
+        for new_customer in customers {
+            print_dry_run(
+                provider,
+                "POST",
+                "",
+                Some(json!(compat_new_customer(provider, new_customer).await)),
+            );
+        }
+        return BatchReport::default();
+    }
+
+    let _batch_lock = acquire_batch_lock(provider, "create from-file");
+    install_cancel_handler();
+
+    let total = customers.len();
+    let (mut succeeded, mut failed) = (0, 0);
+
+    for (idx, new_customer) in customers.into_iter().enumerate() {
+        if is_cancelled() {
+            break;
+        }
+
+        let row = idx + 1;
+        let company_name = new_customer.company_name.clone().unwrap_or_default();
+
+        match try_create_customer(provider, new_customer).await {
+            Ok(customer) => {
+                succeeded += 1;
+                println!("row {}/{}:", row, total);
+                print_created_customer(&customer);
+            }
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "row {}/{}: {} Could not create customer {}: {}",
+                    row,
+                    total,
+                    "Error".white().on_red(),
+                    company_name,
+                    dracoon_error_to_string(e)
+                );
+            }
+        }
+    }
+
+    BatchReport::new(succeeded, failed, total - succeeded - failed)
+}
@@ -0,0 +1,60 @@
+use crate::config;
+use colored::*;
+use std::sync::OnceLock;
+
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Sets the profile (--profile) commands run without url target instead of the default url.
+pub fn set_profile(profile: String) {
+    let _ = PROFILE.set(profile);
+}
+
+/// Returns the url (or alias) commands run without url target: the profile passed via --profile
+/// or the default url of the config (`config set-default-url`). Exits if the profile is unknown.
+pub(super) fn default_url(profile: Option<&str>) -> Option<String> {
+    let config = config::load_config().unwrap_or_default();
+
+    match profile {
+        Some(profile) if config.profiles.contains_key(profile) => Some(profile.to_string()),
+        Some(profile) => {
+            println!(
+                "{} Unknown profile {} – store it via config set --alias {}",
+                "Error".white().on_red(),
+                profile,
+                profile
+            );
+            super::exit(2)
+        }
+        None => config.default_url,
+    }
+}
+
+/// Returns the url (or alias) to run a command against: the passed url or – if omitted – the
+/// profile passed via --profile or the default url. Exits if none is configured.
+pub fn target_url(url: Option<String>) -> String {
+    match url.or_else(|| default_url(PROFILE.get().map(String::as_str))) {
+        Some(url) => url,
+        None => {
+            println!(
+                "{} DRACOON url (or alias) required – pass it or store a default via config <url> set-default-url.",
+                "Error".white().on_red()
+            );
+            super::exit(2)
+        }
+    }
+}
+
+/// Returns true if a value passed as url is a customer id: numeric and no configured alias.
+pub(super) fn is_customer_id(url: &str) -> bool {
+    url.parse::<u64>().is_ok()
+        && !config::load_config().is_ok_and(|config| config.profiles.contains_key(url))
+}
+
+/// Returns the url and customer id of commands taking both as optional positionals – a customer
+/// id passed without url (e.g. `dcprov get 999`) is parsed as url and taken as id here.
+pub fn target_url_and_id(url: Option<String>, id: Option<u64>) -> (String, Option<u64>) {
+    match (url, id) {
+        (Some(url), None) if is_customer_id(&url) => (target_url(None), url.parse().ok()),
+        (url, id) => (target_url(url), id),
+    }
+}
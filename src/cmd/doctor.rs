@@ -0,0 +1,174 @@
+use super::{ClientOptions, TOKEN_ENV};
+use crate::config;
+use crate::credentials::{credentials_file_path, CredentialBackend};
+use colored::*;
+use keyring::Entry;
+
+// account used to probe the keyring (removed after the check)
+const PROBE_ACCOUNT: &str = "dcprov-doctor-probe";
+
+/// Returns the keyring backend used on this platform.
+fn platform_backend() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "macOS Keychain"
+    } else if cfg!(target_os = "windows") {
+        "Windows Credential Manager"
+    } else if cfg!(any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "openbsd"
+    )) {
+        "Secret Service (D-Bus, e.g. GNOME Keyring / KWallet)"
+    } else {
+        "none (unsupported platform)"
+    }
+}
+
+/// Returns true if running inside Windows Subsystem for Linux.
+fn is_wsl() -> bool {
+    std::fs::read_to_string("/proc/version")
+        .map(|version| version.to_lowercase().contains("microsoft"))
+        .unwrap_or(false)
+}
+
+/// Writes, reads and deletes a probe secret – returns a hint if the keyring is not usable.
+fn probe_keyring(service: &str) -> Result<(), String> {
+    let entry = Entry::new(service, PROBE_ACCOUNT).map_err(|e| e.to_string())?;
+
+    let hint = |err: keyring::Error| match err {
+        keyring::Error::NoStorageAccess(e) => {
+            format!("no access to credential store – keychain locked or no secret service running ({e})")
+        }
+        keyring::Error::PlatformFailure(e) => format!("platform credential store failed ({e})"),
+        e => e.to_string(),
+    };
+
+    entry.set_password("probe").map_err(hint)?;
+    let secret = entry.get_password().map_err(hint);
+    let _ = entry.delete_password();
+
+    match secret? == "probe" {
+        true => Ok(()),
+        false => Err("stored secret could not be read back".to_string()),
+    }
+}
+
+fn print_check(label: &str, ok: bool, detail: &str) {
+    let status = match ok {
+        true => " OK ".black().on_green(),
+        false => "FAIL".white().on_red(),
+    };
+    println!("{} {}: {}", status, label, detail);
+}
+
+/// Diagnoses the credential store and suggests a fallback if the keyring is not available.
+pub fn config_doctor(url: Option<&str>, client_opts: &ClientOptions) {
+    match config::config_path() {
+        Ok(path) => {
+            let valid = config::load_config().is_ok();
+            let detail = match (path.exists(), valid) {
+                (false, _) => format!("{} (not present)", path.display()),
+                (true, true) => path.display().to_string(),
+                (true, false) => format!("{} (invalid)", path.display()),
+            };
+            print_check("Config file", valid, &detail);
+        }
+        Err(_) => print_check("Config file", false, "no config directory found"),
+    }
+
+    println!(
+        "Configured backend: {:?} (service: {})",
+        client_opts.credential_backend, client_opts.keyring_service
+    );
+    println!("Platform keyring: {}", platform_backend());
+
+    let disabled = client_opts.credential_backend == CredentialBackend::None;
+
+    if cfg!(target_os = "linux") && !disabled {
+        let dbus = std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some();
+        print_check(
+            "D-Bus session",
+            dbus,
+            match dbus {
+                true => "DBUS_SESSION_BUS_ADDRESS set",
+                false => "DBUS_SESSION_BUS_ADDRESS not set (headless / SSH session?)",
+            },
+        );
+
+        if is_wsl() {
+            println!(
+                "{} WSL detected – a secret service is usually not running, the Windows Credential Manager is not accessible.",
+                "Warning".black().on_yellow()
+            );
+        }
+    }
+
+    let backend = client_opts.credential_backend;
+
+    // the keyring must not be touched if disabled (e.g. D-Bus access forbidden by policy)
+    let keyring_available = match backend {
+        CredentialBackend::None => {
+            println!("Keyring: not probed (credential store disabled)");
+            false
+        }
+        _ => match probe_keyring(&client_opts.keyring_service) {
+            Ok(_) => {
+                print_check("Keyring", true, "secret stored, read and deleted");
+                true
+            }
+            Err(hint) => {
+                print_check("Keyring", false, &hint);
+                false
+            }
+        },
+    };
+
+    if let (Ok(path), false) = (credentials_file_path(), disabled) {
+        let detail = match path.exists() {
+            true => path.display().to_string(),
+            false => format!("{} (not present)", path.display()),
+        };
+        print_check("Credentials file", true, &detail);
+    }
+
+    if disabled {
+        let env_set = std::env::var(TOKEN_ENV).is_ok_and(|token| !token.is_empty());
+        print_check(
+            "Token",
+            env_set,
+            match env_set {
+                true => "passed via env",
+                false => "not set – pass --token or set DCPROV_TOKEN",
+            },
+        );
+    } else if let Some(url) = url {
+        let stored = config::resolve_url(url)
+            .and_then(|url| client_opts.credential_store(&url))
+            .and_then(|store| store.get())
+            .is_ok();
+        print_check(
+            "Token",
+            stored,
+            match stored {
+                true => "stored",
+                false => "not stored (use config set)",
+            },
+        );
+    }
+
+    println!(
+        "Backend in use: {}",
+        match backend {
+            CredentialBackend::Keyring => platform_backend(),
+            CredentialBackend::File => "file",
+            CredentialBackend::None => "none (--token / DCPROV_TOKEN only)",
+        }
+    );
+
+    if backend == CredentialBackend::Keyring && !keyring_available {
+        println!(
+            "{} No keyring available. Store tokens in a file (readable by the current user only) by adding the following to the config file:\n\n[credentials]\nbackend = \"file\"\n\nAlternatively pass the token via --token.",
+            "Hint".black().on_yellow()
+        );
+    }
+}
@@ -0,0 +1,349 @@
+use super::attributes::{fetch_all_attributes, ATTRIBUTES_CONCURRENCY};
+use super::{
+    dracoon_error_to_string, fetch_all_customers, is_cancelled, print_csv_header, print_json,
+    PrintType,
+};
+use colored::*;
+use dco3::{auth::Provisioning, provisioning::Customer, Dracoon};
+use futures_util::{stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+
+const DRIFT_CSV_HEADER: &str = "status,customerId,companyName,field,expected,actual";
+
+/// Desired state of a customer – only the listed fields (and attribute keys) are compared.
+/// Customers are matched by id if set, else by company name.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DesiredCustomer {
+    id: Option<u64>,
+    company_name: String,
+    contract_type: Option<String>,
+    quota_max: Option<u64>,
+    user_max: Option<u64>,
+    is_locked: Option<bool>,
+    #[serde(default)]
+    attributes: BTreeMap<String, String>,
+}
+
+/// Baseline file (YAML) committed next to the provisioning pipeline
+#[derive(Deserialize)]
+struct Baseline {
+    customers: Vec<DesiredCustomer>,
+}
+
+/// Field of a live customer diverging from the baseline
+struct FieldDrift {
+    field: String,
+    expected: Value,
+    actual: Value,
+}
+
+enum DriftStatus {
+    /// live customer differs from the baseline
+    Changed,
+    /// customer in the baseline does not exist
+    Missing,
+    /// live customer is not in the baseline
+    Unmanaged,
+}
+
+impl DriftStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DriftStatus::Changed => "changed",
+            DriftStatus::Missing => "missing",
+            DriftStatus::Unmanaged => "unmanaged",
+        }
+    }
+}
+
+struct Drift {
+    status: DriftStatus,
+    id: Option<u64>,
+    company_name: String,
+    fields: Vec<FieldDrift>,
+}
+
+fn load_baseline(path: &str) -> Baseline {
+    let raw = match std::fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) => {
+            println!(
+                "{} Could not open file from path {}: {}",
+                "Error".white().on_red(),
+                path,
+                e
+            );
+            super::exit(1)
+        }
+    };
+
+    match serde_yaml::from_str(&raw) {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            println!(
+                "{} Could not parse baseline {}: {}",
+                "Error".white().on_red(),
+                path,
+                e
+            );
+            super::exit(1)
+        }
+    }
+}
+
+fn field_drifts(desired: &DesiredCustomer, customer: &Customer) -> Vec<FieldDrift> {
+    let mut fields = Vec::new();
+    let mut compare = |field: &str, expected: Option<Value>, actual: Value| {
+        if let Some(expected) = expected.filter(|expected| *expected != actual) {
+            fields.push(FieldDrift {
+                field: field.to_string(),
+                expected,
+                actual,
+            });
+        }
+    };
+
+    compare(
+        "companyName",
+        desired.id.map(|_| json!(desired.company_name)),
+        json!(customer.company_name),
+    );
+    compare(
+        "customerContractType",
+        desired.contract_type.as_ref().map(|value| json!(value)),
+        json!(customer.customer_contract_type),
+    );
+    compare(
+        "quotaMax",
+        desired.quota_max.map(|value| json!(value)),
+        json!(customer.quota_max),
+    );
+    compare(
+        "userMax",
+        desired.user_max.map(|value| json!(value)),
+        json!(customer.user_max),
+    );
+    compare(
+        "isLocked",
+        desired.is_locked.map(|value| json!(value)),
+        json!(customer.is_locked.unwrap_or_default()),
+    );
+
+    fields
+}
+
+/// Compares the attribute keys listed in the baseline with the live attributes of a customer.
+async fn attribute_drifts(
+    provider: &Dracoon<Provisioning>,
+    desired: &DesiredCustomer,
+    id: u64,
+) -> Result<Vec<FieldDrift>, String> {
+    if desired.attributes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let live: BTreeMap<_, _> = fetch_all_attributes(provider, id)
+        .await
+        .map_err(dracoon_error_to_string)?
+        .into_iter()
+        .map(|attrib| (attrib.key, attrib.value))
+        .collect();
+
+    Ok(desired
+        .attributes
+        .iter()
+        .filter(|(key, value)| live.get(*key) != Some(*value))
+        .map(|(key, value)| FieldDrift {
+            field: format!("attributes.{}", key),
+            expected: json!(value),
+            actual: live
+                .get(key)
+                .map(|value| json!(value))
+                .unwrap_or(Value::Null),
+        })
+        .collect())
+}
+
+fn print_drifts(drifts: &[Drift], customers: usize, print_type: PrintType) {
+    match print_type {
+        PrintType::Csv => {
+            print_csv_header(DRIFT_CSV_HEADER);
+            for drift in drifts {
+                let id = drift.id.map(|id| id.to_string()).unwrap_or_default();
+                if drift.fields.is_empty() {
+                    println!("{},{},{},,,", drift.status.as_str(), id, drift.company_name);
+                }
+                for field in &drift.fields {
+                    println!(
+                        "{},{},{},{},{},{}",
+                        drift.status.as_str(),
+                        id,
+                        drift.company_name,
+                        field.field,
+                        field.expected,
+                        field.actual
+                    );
+                }
+            }
+        }
+        PrintType::Pretty => {
+            for drift in drifts {
+                println!(
+                    "{} | company: {} | id: {}",
+                    drift.status.as_str().bold(),
+                    drift.company_name,
+                    drift
+                        .id
+                        .map(|id| id.to_string())
+                        .unwrap_or_else(|| "-".into())
+                );
+                for field in &drift.fields {
+                    println!(
+                        "  {}: expected {} – actual {}",
+                        field.field, field.expected, field.actual
+                    );
+                }
+            }
+            println!("customers: {} | drifted: {}", customers, drifts.len());
+        }
+        PrintType::Json => print_json(json!({
+            "customers": customers,
+            "drifted": drifts.len(),
+            "items": drifts
+                .iter()
+                .map(|drift| json!({
+                    "status": drift.status.as_str(),
+                    "id": drift.id,
+                    "companyName": drift.company_name,
+                    "fields": drift
+                        .fields
+                        .iter()
+                        .map(|field| json!({
+                            "field": field.field,
+                            "expected": field.expected,
+                            "actual": field.actual,
+                        }))
+                        .collect::<Vec<_>>(),
+                }))
+                .collect::<Vec<_>>(),
+        })),
+    }
+}
+
+/// Compares the live customers with the desired state of a baseline file (read-only) and prints
+/// a drift report: changed fields, missing customers and (unless `ignore_unmanaged`) customers
+/// not in the baseline. Exits with 1 on drift if `fail_on_drift` (CI) and if a customer could
+/// not be checked.
+pub async fn drift_check(
+    provider: Dracoon<Provisioning>,
+    baseline: String,
+    fail_on_drift: bool,
+    ignore_unmanaged: bool,
+    print_type: PrintType,
+) {
+    let baseline = load_baseline(&baseline);
+
+    let customers = fetch_all_customers(&provider, None, Some("id:asc".into())).await;
+    // a partial list would report missing customers
+    if is_cancelled() {
+        super::exit(1)
+    }
+
+    let by_id: BTreeMap<_, _> = customers
+        .iter()
+        .map(|customer| (customer.id, customer))
+        .collect();
+    let by_name: BTreeMap<_, _> = customers
+        .iter()
+        .map(|customer| (customer.company_name.as_str(), customer))
+        .collect();
+
+    let matched: Vec<_> = baseline
+        .customers
+        .iter()
+        .map(|desired| {
+            let customer = match desired.id {
+                Some(id) => by_id.get(&id),
+                None => by_name.get(desired.company_name.as_str()),
+            };
+            (desired, customer.copied())
+        })
+        .collect();
+
+    let provider = &provider;
+    let results: Vec<_> = stream::iter(&matched)
+        .map(|(desired, customer)| async move {
+            let Some(customer) = customer else {
+                return Ok(Some(Drift {
+                    status: DriftStatus::Missing,
+                    id: desired.id,
+                    company_name: desired.company_name.clone(),
+                    fields: Vec::new(),
+                }));
+            };
+
+            let mut fields = field_drifts(desired, customer);
+            fields.extend(
+                attribute_drifts(provider, desired, customer.id)
+                    .await
+                    .map_err(|e| (customer.id, e))?,
+            );
+
+            Ok(Some(Drift {
+                status: DriftStatus::Changed,
+                id: Some(customer.id),
+                company_name: customer.company_name.clone(),
+                fields,
+            })
+            .filter(|drift| !drift.fields.is_empty()))
+        })
+        .buffered(ATTRIBUTES_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut drifts = Vec::new();
+    let mut failed = 0;
+
+    for result in results {
+        match result {
+            Ok(drift) => drifts.extend(drift),
+            Err((id, e)) => {
+                failed += 1;
+                eprintln!(
+                    "{} Could not get attributes of customer {}: {}",
+                    "Error".white().on_red(),
+                    id,
+                    e
+                );
+            }
+        }
+    }
+
+    if !ignore_unmanaged {
+        let managed: HashSet<_> = matched
+            .iter()
+            .filter_map(|(_, customer)| customer.map(|customer| customer.id))
+            .collect();
+
+        drifts.extend(
+            customers
+                .iter()
+                .filter(|customer| !managed.contains(&customer.id))
+                .map(|customer| Drift {
+                    status: DriftStatus::Unmanaged,
+                    id: Some(customer.id),
+                    company_name: customer.company_name.clone(),
+                    fields: Vec::new(),
+                }),
+        );
+    }
+
+    print_drifts(&drifts, customers.len(), print_type);
+
+    if failed > 0 || (fail_on_drift && !drifts.is_empty()) {
+        super::exit(1)
+    }
+}
@@ -0,0 +1,40 @@
+use super::raw::PROVISIONING_CUSTOMERS_PATH;
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Enables the dry run (--dry-run) – create, update, delete and set-attributes print their request
+/// instead of sending it.
+pub fn enable_dry_run() {
+    DRY_RUN.store(true, Ordering::SeqCst);
+}
+
+pub(super) fn dry_run_active() -> bool {
+    DRY_RUN.load(Ordering::SeqCst)
+}
+
+/// Prints the request a mutating command would send: method, endpoint (path relative to the
+/// customers endpoint, e.g. `999/customerAttributes`) and JSON payload (if any).
+pub(super) fn print_dry_run(
+    provider: &Dracoon<Provisioning>,
+    method: &str,
+    path: &str,
+    payload: Option<Value>,
+) {
+    let url = provider.build_api_url(&match path {
+        "" => PROVISIONING_CUSTOMERS_PATH.to_string(),
+        path => format!("{}/{}", PROVISIONING_CUSTOMERS_PATH, path),
+    });
+
+    eprintln!("{} Request not sent.", "Dry run".black().on_cyan());
+    println!("{} {}", method, url);
+    if let Some(payload) = payload {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&payload).unwrap_or_default()
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use super::attributes::{fetch_all_attributes, ATTRIBUTES_CONCURRENCY};
+use super::dracoon_error_to_string;
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use futures_util::{stream, StreamExt};
+use std::{collections::BTreeSet, sync::OnceLock};
+
+/// Customers skipped by all batch commands (--exclude-attribute, --exclude-ids)
+#[derive(Default)]
+struct Exclusions {
+    attributes: Vec<(String, String)>,
+    ids: BTreeSet<u64>,
+}
+
+static EXCLUSIONS: OnceLock<Exclusions> = OnceLock::new();
+
+/// Parses an ids file – one customer id per line, empty lines and `#` comments are ignored.
+fn parse_ids_file(path: &str) -> Result<BTreeSet<u64>, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    raw.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse::<u64>()
+                .map_err(|_| format!("invalid customer id `{}`", line))
+        })
+        .collect()
+}
+
+/// Sets the customers skipped by batch commands: customers with any of the attributes (key, value)
+/// and the ids listed in the file. Exits if the file can't be read.
+pub fn set_exclusions(attributes: Vec<(String, String)>, ids_file: Option<String>) {
+    let ids = match ids_file.as_deref().map(parse_ids_file).transpose() {
+        Ok(ids) => ids.unwrap_or_default(),
+        Err(e) => {
+            println!(
+                "{} Could not read excluded ids {}: {}",
+                "Error".white().on_red(),
+                ids_file.unwrap_or_default(),
+                e
+            );
+            super::exit(1)
+        }
+    };
+
+    let _ = EXCLUSIONS.set(Exclusions { attributes, ids });
+}
+
+/// Returns the excluded ids among the given customer ids. Attributes are fetched per customer (in
+/// parallel) only if --exclude-attribute is passed – customers whose attributes can't be fetched are
+/// excluded as well (protected tenants are never touched by accident).
+async fn excluded_ids(
+    provider: &Dracoon<Provisioning>,
+    exclusions: &Exclusions,
+    ids: Vec<u64>,
+) -> BTreeSet<u64> {
+    let (excluded, candidates): (Vec<u64>, Vec<u64>) =
+        ids.into_iter().partition(|id| exclusions.ids.contains(id));
+    let mut excluded: BTreeSet<u64> = excluded.into_iter().collect();
+
+    if !exclusions.attributes.is_empty() {
+        let matched: Vec<_> = stream::iter(candidates)
+            .map(|id| async move {
+                match fetch_all_attributes(provider, id).await {
+                    Ok(attribs) => attribs
+                        .iter()
+                        .any(|attrib| {
+                            exclusions
+                                .attributes
+                                .iter()
+                                .any(|(key, value)| attrib.key == *key && attrib.value == *value)
+                        })
+                        .then_some(id),
+                    Err(e) => {
+                        eprintln!(
+                            "{} Could not get attributes of customer {} – excluded: {}",
+                            "Warning".black().on_yellow(),
+                            id,
+                            dracoon_error_to_string(e)
+                        );
+                        Some(id)
+                    }
+                }
+            })
+            .buffer_unordered(ATTRIBUTES_CONCURRENCY)
+            .collect()
+            .await;
+
+        excluded.extend(matched.into_iter().flatten());
+    }
+
+    excluded
+}
+
+/// Removes excluded customers from the items of a batch command and returns the excluded ids.
+pub(super) async fn apply_exclusions<T>(
+    provider: &Dracoon<Provisioning>,
+    items: Vec<T>,
+    id_of: impl Fn(&T) -> u64,
+) -> (Vec<T>, Vec<u64>) {
+    let Some(exclusions) = EXCLUSIONS.get() else {
+        return (items, Vec::new());
+    };
+
+    let excluded = excluded_ids(provider, exclusions, items.iter().map(&id_of).collect()).await;
+
+    if !excluded.is_empty() {
+        eprintln!("excluded customers: {}", excluded.len());
+    }
+
+    let items = items
+        .into_iter()
+        .filter(|item| !excluded.contains(&id_of(item)))
+        .collect();
+
+    (items, excluded.into_iter().collect())
+}
+
+/// Returns true if a single customer is excluded (e.g. the customer of a playbook step).
+pub(super) async fn is_excluded(provider: &Dracoon<Provisioning>, id: u64) -> bool {
+    match EXCLUSIONS.get() {
+        Some(exclusions) => !excluded_ids(provider, exclusions, vec![id])
+            .await
+            .is_empty(),
+        None => false,
+    }
+}
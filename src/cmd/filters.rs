@@ -0,0 +1,236 @@
+use super::DcProv;
+use clap::CommandFactory;
+use colored::*;
+
+/// A filter field of a provisioning endpoint.
+struct FilterField {
+    name: &'static str,
+    operators: &'static str,
+    values: &'static str,
+    description: &'static str,
+}
+
+/// A provisioning list endpoint with its filter and sort fields.
+struct FilterEndpoint {
+    name: &'static str,
+    commands: &'static str,
+    filters: &'static [FilterField],
+    sort: &'static [&'static str],
+    examples: &'static [&'static str],
+}
+
+const fn field(
+    name: &'static str,
+    operators: &'static str,
+    values: &'static str,
+    description: &'static str,
+) -> FilterField {
+    FilterField {
+        name,
+        operators,
+        values,
+        description,
+    }
+}
+
+// as documented for the DRACOON provisioning API (GET /provisioning/customers/...)
+const FILTER_ENDPOINTS: [FilterEndpoint; 3] = [
+    FilterEndpoint {
+        name: "Customers",
+        commands: "list, lock-all, report, audit quotas, sync, attributes keys",
+        filters: &[
+            field("id", "eq", "positive integer", "customer id"),
+            field("companyName", "cn, eq", "string", "company name"),
+            field(
+                "customerContractType",
+                "eq",
+                "demo | free | pay",
+                "contract type",
+            ),
+            field("activationCode", "cn, eq", "string", "activation code"),
+            field("lockStatus", "eq", "true | false", "lock status"),
+            field(
+                "trialDaysLeft",
+                "le, ge",
+                "integer",
+                "remaining trial days (demo customers)",
+            ),
+            field(
+                "providerCustomerId",
+                "cn, eq",
+                "string",
+                "provider customer id",
+            ),
+            field("quotaMax", "le, ge", "integer (bytes)", "max. quota"),
+            field("quotaUsed", "le, ge", "integer (bytes)", "used quota"),
+            field("userMax", "le, ge", "integer", "max. users"),
+            field("userUsed", "le, ge", "integer", "registered users"),
+            field("cntGuestUser", "le, ge", "integer", "guest users"),
+            field("createdAt", "le, ge", "date (yyyy-MM-dd)", "creation date"),
+            field(
+                "updatedAt",
+                "le, ge",
+                "date (yyyy-MM-dd)",
+                "last modification date",
+            ),
+            field("lastLoginAt", "le, ge", "date (yyyy-MM-dd)", "last login"),
+            field(
+                "userLogin",
+                "cn, eq",
+                "string",
+                "user login of a customer user",
+            ),
+            field("attributeKey", "cn, eq, sw", "string", "attribute key"),
+            field("attributeValue", "cn, eq, sw", "string", "attribute value"),
+        ],
+        sort: &[
+            "companyName",
+            "customerContractType",
+            "lockStatus",
+            "trialDaysLeft",
+            "providerCustomerId",
+            "quotaMax",
+            "quotaUsed",
+            "userMax",
+            "userUsed",
+            "cntGuestUser",
+            "createdAt",
+            "updatedAt",
+            "lastLoginAt",
+        ],
+        examples: &[
+            "dcprov list https://dracoon.team -f companyName:cn:DRACOON",
+            "dcprov list https://dracoon.team -f 'customerContractType:eq:demo|trialDaysLeft:le:7'",
+            "dcprov list https://dracoon.team -f createdAt:ge:2024-01-01 -s quotaUsed:desc",
+            "dcprov list https://dracoon.team -f attributeKey:eq:region --all",
+        ],
+    },
+    FilterEndpoint {
+        name: "Customer users",
+        commands: "get-users",
+        filters: &[
+            field("email", "cn", "string", "email"),
+            field("userName", "cn", "string", "user login"),
+            field("firstName", "cn", "string", "first name"),
+            field("lastName", "cn", "string", "last name"),
+            field("isLocked", "eq", "true | false", "lock status"),
+            field(
+                "effectiveRoles",
+                "eq",
+                "true | false",
+                "include effective roles (not a restriction)",
+            ),
+        ],
+        sort: &[
+            "userName",
+            "email",
+            "firstName",
+            "lastName",
+            "isLocked",
+            "lastLoginAt",
+        ],
+        examples: &[
+            "dcprov get-users https://dracoon.team 999 -f email:cn:@example.com",
+            "dcprov get-users https://dracoon.team 999 -f isLocked:eq:true -s lastLoginAt:asc",
+        ],
+    },
+    FilterEndpoint {
+        name: "Customer attributes",
+        commands: "get-attributes",
+        filters: &[
+            field("key", "cn, eq, sw", "string", "attribute key"),
+            field("value", "cn, eq, sw", "string", "attribute value"),
+        ],
+        sort: &["key", "value"],
+        examples: &[
+            "dcprov get-attributes https://dracoon.team 999 -f key:sw:crm_",
+            "dcprov get-attributes https://dracoon.team 999 -s key:asc",
+        ],
+    },
+];
+
+// operators used in the filter table
+const FILTER_OPERATORS: [(&str, &str); 5] = [
+    ("eq", "equals"),
+    ("cn", "contains"),
+    ("sw", "starts with"),
+    ("ge", "greater or equal"),
+    ("le", "less or equal"),
+];
+
+/// Prints help for a topic (`filters`) or a (nested) command – replaces clap's help subcommand.
+pub fn print_help(topic: Vec<String>) {
+    if let [topic] = topic.as_slice() {
+        if topic == "filters" {
+            print_filter_help();
+            return;
+        }
+    }
+
+    let mut cmd = DcProv::command();
+    // propagates the bin name to subcommands (usage: dcprov report expiring-users ...)
+    cmd.build();
+    for name in &topic {
+        let Some(sub) = cmd.find_subcommand(name).cloned() else {
+            eprintln!(
+                "{} Unknown command or help topic: {} (available topics: filters)",
+                "Error".white().on_red(),
+                topic.join(" ")
+            );
+            super::exit(2)
+        };
+        cmd = sub;
+    }
+
+    let _ = cmd.print_help();
+}
+
+/// Prints the supported filter and sort fields and operators of the provisioning endpoints.
+fn print_filter_help() {
+    println!("{}", "Filter syntax".bold());
+    println!("  -f FIELD:OPERATOR:VALUE – multiple filters are combined (AND) with |");
+    println!("  e.g. -f 'companyName:cn:DRACOON|lockStatus:eq:false'");
+    println!();
+    println!("{}", "Operators".bold());
+    for (op, description) in FILTER_OPERATORS {
+        println!("  {:<4}{}", op, description);
+    }
+    println!();
+    println!("{}", "Sort syntax".bold());
+    println!("  -s FIELD:asc or -s FIELD:desc – multiple fields are combined with |");
+    println!("  e.g. -s 'customerContractType:asc|companyName:desc'");
+
+    for endpoint in FILTER_ENDPOINTS {
+        println!();
+        println!("{} ({})", endpoint.name.bold(), endpoint.commands);
+
+        let width = endpoint
+            .filters
+            .iter()
+            .map(|field| field.name.len())
+            .max()
+            .unwrap_or_default();
+        for field in endpoint.filters {
+            println!(
+                "  {:<width$}  {:<11} {:<20} {}",
+                field.name,
+                field.operators,
+                field.values,
+                field.description.dimmed(),
+                width = width
+            );
+        }
+
+        println!("  sort: {}", endpoint.sort.join(", "));
+        println!("  examples:");
+        for example in endpoint.examples {
+            println!("    {}", example);
+        }
+    }
+
+    println!();
+    println!(
+        "{}",
+        "Reference: Provisioning API docs of your instance (<url>/api/swagger-ui)".dimmed()
+    );
+}
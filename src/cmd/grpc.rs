@@ -0,0 +1,567 @@
+//! gRPC customer service (see `proto/dcprov.proto`).
+//! Messages and routing are written by hand to not require `protoc` at build time –
+//! keep them in sync with the proto definition.
+
+// tonic::Status is large by design, handlers mirror the generated tonic signatures
+#![allow(clippy::result_large_err)]
+
+use super::{
+    build_params, dracoon_error_to_string, serve::token_matches, try_create_customer,
+    try_delete_customer, try_update_customer, ContractType, UpdateCustomerPatch, UpdateType,
+};
+use colored::*;
+use dco3::{
+    auth::Provisioning,
+    provisioning::{
+        Customer as CustomerDco3, FirstAdminUser as FirstAdminUserDco3, NewCustomerRequest,
+    },
+    users::{AuthMethod, UserAuthData},
+    CustomerProvisioning, Dracoon, DracoonClientError,
+};
+use std::{
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tonic::{
+    body::BoxBody,
+    codec::ProstCodec,
+    codegen::{empty_body, http, Body, Service, StdError},
+    server::{Grpc, NamedService, UnaryService},
+    Code, Request, Response, Status,
+};
+
+const SERVICE_NAME: &str = "dcprov.v1.CustomerService";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Customer {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, tag = "2")]
+    pub company_name: String,
+    #[prost(string, tag = "3")]
+    pub contract_type: String,
+    #[prost(uint64, tag = "4")]
+    pub quota_max: u64,
+    #[prost(uint64, tag = "5")]
+    pub quota_used: u64,
+    #[prost(uint64, tag = "6")]
+    pub user_max: u64,
+    #[prost(uint64, tag = "7")]
+    pub user_used: u64,
+    #[prost(string, tag = "8")]
+    pub created_at: String,
+    #[prost(string, optional, tag = "9")]
+    pub updated_at: Option<String>,
+    #[prost(bool, optional, tag = "10")]
+    pub is_locked: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListCustomersRequest {
+    #[prost(string, optional, tag = "1")]
+    pub filter: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub sort: Option<String>,
+    #[prost(uint64, optional, tag = "3")]
+    pub offset: Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub limit: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListCustomersResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub customers: Vec<Customer>,
+    #[prost(uint64, tag = "2")]
+    pub total: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetCustomerRequest {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FirstAdminUser {
+    #[prost(string, tag = "1")]
+    pub first_name: String,
+    #[prost(string, tag = "2")]
+    pub last_name: String,
+    #[prost(string, tag = "3")]
+    pub email: String,
+    #[prost(string, optional, tag = "4")]
+    pub user_name: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CreateCustomerRequest {
+    #[prost(string, tag = "1")]
+    pub company_name: String,
+    #[prost(string, tag = "2")]
+    pub contract_type: String,
+    #[prost(uint64, tag = "3")]
+    pub quota_max: u64,
+    #[prost(uint64, tag = "4")]
+    pub user_max: u64,
+    #[prost(message, optional, tag = "5")]
+    pub first_admin_user: Option<FirstAdminUser>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UpdateCustomerRequest {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, optional, tag = "2")]
+    pub company_name: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub contract_type: Option<String>,
+    #[prost(uint64, optional, tag = "4")]
+    pub quota_max: Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    pub user_max: Option<u64>,
+    #[prost(bool, optional, tag = "6")]
+    pub is_locked: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteCustomerRequest {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteCustomerResponse {}
+
+impl From<CustomerDco3> for Customer {
+    fn from(customer: CustomerDco3) -> Self {
+        Self {
+            id: customer.id,
+            company_name: customer.company_name,
+            contract_type: customer.customer_contract_type,
+            quota_max: customer.quota_max,
+            quota_used: customer.quota_used,
+            user_max: customer.user_max,
+            user_used: customer.user_used,
+            created_at: customer.created_at,
+            updated_at: customer.updated_at,
+            is_locked: customer.is_locked,
+        }
+    }
+}
+
+fn to_status(err: DracoonClientError) -> Status {
+    let code = match &err {
+        DracoonClientError::Http(err) => match err.code() {
+            400 => Code::InvalidArgument,
+            401 => Code::Unauthenticated,
+            402 | 403 => Code::PermissionDenied,
+            404 => Code::NotFound,
+            409 => Code::AlreadyExists,
+            429 => Code::ResourceExhausted,
+            _ => Code::Internal,
+        },
+        DracoonClientError::ConnectionFailed(_) => Code::Unavailable,
+        _ => Code::Internal,
+    };
+
+    Status::new(code, dracoon_error_to_string(err))
+}
+
+struct GrpcState {
+    provider: Dracoon<Provisioning>,
+    auth_token: String,
+}
+
+impl GrpcState {
+    fn authorize<T>(&self, req: &Request<T>) -> Result<(), Status> {
+        let authorized = req
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token_matches(token, &self.auth_token))
+            .unwrap_or(false);
+
+        match authorized {
+            true => Ok(()),
+            false => Err(Status::unauthenticated("invalid auth token")),
+        }
+    }
+
+    async fn list_customers(
+        &self,
+        req: ListCustomersRequest,
+    ) -> Result<ListCustomersResponse, Status> {
+        let params = build_params(req.filter, req.sort, req.offset, req.limit);
+        let customers = self
+            .provider
+            .get_customers(Some(params))
+            .await
+            .map_err(to_status)?;
+
+        Ok(ListCustomersResponse {
+            total: customers.range.total,
+            customers: customers.items.into_iter().map(Customer::from).collect(),
+        })
+    }
+
+    async fn get_customer(&self, req: GetCustomerRequest) -> Result<Customer, Status> {
+        self.provider
+            .get_customer(req.id, None)
+            .await
+            .map(Customer::from)
+            .map_err(to_status)
+    }
+
+    async fn create_customer(&self, req: CreateCustomerRequest) -> Result<Customer, Status> {
+        let admin = req
+            .first_admin_user
+            .ok_or_else(|| Status::invalid_argument("first_admin_user is required"))?;
+
+        if req.quota_max == 0 || req.user_max == 0 {
+            return Err(Status::invalid_argument(
+                "quota_max and user_max must be positive",
+            ));
+        }
+
+        let auth_data = UserAuthData::builder(AuthMethod::Basic)
+            .with_must_change_password(true)
+            .build();
+
+        let first_admin_user = FirstAdminUserDco3 {
+            first_name: admin.first_name,
+            last_name: admin.last_name,
+            user_name: admin.user_name,
+            email: Some(admin.email),
+            auth_data: Some(auth_data),
+            notify_user: Some(true),
+            receiver_language: None,
+            phone: None,
+        };
+
+        let contract_type = match req.contract_type.as_str() {
+            "" => ContractType::default(),
+            contract_type => contract_type
+                .parse::<ContractType>()
+                .map_err(Status::invalid_argument)?,
+        };
+
+        let new_customer = NewCustomerRequest::builder(
+            contract_type.as_str(),
+            req.quota_max,
+            req.user_max,
+            first_admin_user,
+        )
+        .with_company_name(req.company_name)
+        .build();
+
+        let created = try_create_customer(&self.provider, new_customer)
+            .await
+            .map_err(to_status)?;
+
+        self.get_customer(GetCustomerRequest { id: created.id })
+            .await
+    }
+
+    async fn update_customer(&self, req: UpdateCustomerRequest) -> Result<Customer, Status> {
+        let customer_contract_type = req
+            .contract_type
+            .map(|contract_type| contract_type.parse::<ContractType>())
+            .transpose()
+            .map_err(Status::invalid_argument)?;
+
+        let patch = UpdateCustomerPatch {
+            company_name: req.company_name,
+            customer_contract_type,
+            quota_max: req.quota_max,
+            user_max: req.user_max,
+            is_locked: req.is_locked,
+            ..Default::default()
+        };
+
+        try_update_customer(&self.provider, req.id, UpdateType::Patch(patch))
+            .await
+            .map_err(to_status)?;
+
+        self.get_customer(GetCustomerRequest { id: req.id }).await
+    }
+
+    async fn delete_customer(
+        &self,
+        req: DeleteCustomerRequest,
+    ) -> Result<DeleteCustomerResponse, Status> {
+        try_delete_customer(&self.provider, req.id)
+            .await
+            .map_err(to_status)?;
+
+        Ok(DeleteCustomerResponse {})
+    }
+}
+
+type BoxFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'static>>;
+
+// adapter to use an async closure as tonic unary service
+struct UnaryFn<F>(F);
+
+impl<Req, Res, F, Fut> UnaryService<Req> for UnaryFn<F>
+where
+    F: FnMut(Request<Req>) -> Fut,
+    Fut: Future<Output = Result<Response<Res>, Status>> + Send + 'static,
+{
+    type Response = Res;
+    type Future = Fut;
+
+    fn call(&mut self, req: Request<Req>) -> Self::Future {
+        (self.0)(req)
+    }
+}
+
+macro_rules! unary {
+    ($state:expr, $req:expr, $method:ident, $req_type:ty) => {{
+        let state = $state.clone();
+        let svc = UnaryFn(move |req: Request<$req_type>| {
+            let state = state.clone();
+            async move {
+                state.authorize(&req)?;
+                state.$method(req.into_inner()).await.map(Response::new)
+            }
+        });
+        let req = $req;
+        Box::pin(async move {
+            let mut grpc = Grpc::new(ProstCodec::default());
+            Ok(grpc.unary(svc, req).await)
+        })
+    }};
+}
+
+#[derive(Clone)]
+struct CustomerServiceServer(Arc<GrpcState>);
+
+impl NamedService for CustomerServiceServer {
+    const NAME: &'static str = SERVICE_NAME;
+}
+
+impl<B> Service<http::Request<B>> for CustomerServiceServer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let state = &self.0;
+        let method = req
+            .uri()
+            .path()
+            .strip_prefix(&format!("/{}/", SERVICE_NAME))
+            .unwrap_or_default()
+            .to_string();
+
+        match method.as_str() {
+            "ListCustomers" => unary!(state, req, list_customers, ListCustomersRequest),
+            "GetCustomer" => unary!(state, req, get_customer, GetCustomerRequest),
+            "CreateCustomer" => unary!(state, req, create_customer, CreateCustomerRequest),
+            "UpdateCustomer" => unary!(state, req, update_customer, UpdateCustomerRequest),
+            "DeleteCustomer" => unary!(state, req, delete_customer, DeleteCustomerRequest),
+            _ => Box::pin(async move {
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", (Code::Unimplemented as i32).to_string())
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .expect("valid response"))
+            }),
+        }
+    }
+}
+
+/// Serves the gRPC customer service with the configured DRACOON client.
+/// Requests need to pass the auth token as `authorization: Bearer <token>` metadata.
+pub async fn serve_grpc(provider: Dracoon<Provisioning>, port: u16, auth_token: String) {
+    let svc = CustomerServiceServer(Arc::new(GrpcState {
+        provider,
+        auth_token,
+    }));
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+    println!("Listening on {} (gRPC {})", addr, SERVICE_NAME);
+
+    if let Err(e) = tonic::transport::Server::builder()
+        .add_service(svc)
+        .serve(addr)
+        .await
+    {
+        println!("{} Server error: {}", "Error".white().on_red(), e);
+        super::exit(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::{
+        bytes::Buf,
+        encoding::{decode_key, skip_field, DecodeContext, WireType},
+        Message,
+    };
+    use std::fmt::Debug;
+
+    const PROTO: &str = include_str!("../../proto/dcprov.proto");
+
+    /// Fields (name, number, wire type) of a message in the proto definition.
+    fn proto_fields(message: &str) -> Vec<(String, u32, WireType)> {
+        let start = format!("message {} {{", message);
+        // empty messages are declared on one line
+        if PROTO
+            .lines()
+            .any(|line| line.trim() == format!("{}}}", start))
+        {
+            return Vec::new();
+        }
+        assert!(
+            PROTO.lines().any(|line| line.trim() == start),
+            "message {} not in proto",
+            message
+        );
+
+        PROTO
+            .lines()
+            .skip_while(|line| line.trim() != start)
+            .skip(1)
+            .take_while(|line| line.trim() != "}")
+            .map(|line| {
+                let (decl, number) = line.trim().trim_end_matches(';').split_once(" = ").unwrap();
+                let mut decl: Vec<_> = decl.split_whitespace().collect();
+                let name = decl.pop().unwrap().to_string();
+                let wire_type = match decl.pop().unwrap() {
+                    "uint64" | "bool" => WireType::Varint,
+                    _ => WireType::LengthDelimited,
+                };
+                (name, number.parse().unwrap(), wire_type)
+            })
+            .collect()
+    }
+
+    /// Encodes the message (all fields set) and checks field names, numbers and wire types against
+    /// the proto definition – then decodes it again.
+    fn assert_matches_proto<M: Message + Default + PartialEq + Debug>(message: &str, msg: M) {
+        let expected = proto_fields(message);
+
+        // field names in declaration order (top level of the pretty debug output)
+        let debug = format!("{:#?}", msg);
+        let names: Vec<_> = debug
+            .lines()
+            .filter_map(|line| line.strip_prefix("    "))
+            .filter(|line| !line.starts_with(' '))
+            .filter_map(|line| line.split_once(':').map(|(name, _)| name.to_string()))
+            .collect();
+
+        let encoded = msg.encode_to_vec();
+        let mut buf = encoded.as_slice();
+        let mut tags = Vec::new();
+        while buf.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut buf).unwrap();
+            tags.push((tag, wire_type));
+            skip_field(wire_type, tag, &mut buf, DecodeContext::default()).unwrap();
+        }
+
+        assert_eq!(
+            names,
+            expected
+                .iter()
+                .map(|(name, _, _)| name.clone())
+                .collect::<Vec<_>>(),
+            "field names of {}",
+            message
+        );
+        assert_eq!(
+            tags,
+            expected
+                .iter()
+                .map(|(_, number, wire_type)| (*number, *wire_type))
+                .collect::<Vec<_>>(),
+            "field numbers of {}",
+            message
+        );
+        assert_eq!(M::decode(encoded.as_slice()).unwrap(), msg);
+    }
+
+    fn customer() -> Customer {
+        Customer {
+            id: 1,
+            company_name: "Acme".into(),
+            contract_type: "pay".into(),
+            quota_max: 2,
+            quota_used: 3,
+            user_max: 4,
+            user_used: 5,
+            created_at: "2024-01-01T00:00:00Z".into(),
+            updated_at: Some("2024-01-02T00:00:00Z".into()),
+            is_locked: Some(false),
+        }
+    }
+
+    #[test]
+    fn messages_match_proto() {
+        assert_matches_proto("Customer", customer());
+        assert_matches_proto(
+            "ListCustomersRequest",
+            ListCustomersRequest {
+                filter: Some("companyName:cn:acme".into()),
+                sort: Some("id:asc".into()),
+                offset: Some(0),
+                limit: Some(10),
+            },
+        );
+        assert_matches_proto(
+            "ListCustomersResponse",
+            ListCustomersResponse {
+                customers: vec![customer()],
+                total: 1,
+            },
+        );
+        assert_matches_proto("GetCustomerRequest", GetCustomerRequest { id: 1 });
+        let first_admin_user = FirstAdminUser {
+            first_name: "Jane".into(),
+            last_name: "Doe".into(),
+            email: "jane@acme.test".into(),
+            user_name: Some("jdoe".into()),
+        };
+        assert_matches_proto("FirstAdminUser", first_admin_user.clone());
+        assert_matches_proto(
+            "CreateCustomerRequest",
+            CreateCustomerRequest {
+                company_name: "Acme".into(),
+                contract_type: "pay".into(),
+                quota_max: 2,
+                user_max: 4,
+                first_admin_user: Some(first_admin_user),
+            },
+        );
+        assert_matches_proto(
+            "UpdateCustomerRequest",
+            UpdateCustomerRequest {
+                id: 1,
+                company_name: Some("Acme".into()),
+                contract_type: Some("free".into()),
+                quota_max: Some(2),
+                user_max: Some(4),
+                is_locked: Some(true),
+            },
+        );
+        assert_matches_proto("DeleteCustomerRequest", DeleteCustomerRequest { id: 1 });
+        assert_matches_proto("DeleteCustomerResponse", DeleteCustomerResponse {});
+    }
+}
@@ -0,0 +1,107 @@
+use super::{dry_run_active, handle_errors};
+use crate::config;
+use colored::*;
+use std::sync::OnceLock;
+
+// host mutating commands must target (--expect-host)
+static EXPECTED_HOST: OnceLock<String> = OnceLock::new();
+
+/// Sets the host mutating commands are expected to target (overrides `expect_host` of the profile).
+pub fn set_expected_host(host: String) {
+    let _ = EXPECTED_HOST.set(host);
+}
+
+/// Returns an error if the url (or alias) does not resolve to the expected host (--expect-host or
+/// `expect_host` of the profile) – guards against copy-pasted commands carrying the wrong url.
+fn check_host(url_or_alias: &str, profile: Option<&config::Profile>) -> Result<(), String> {
+    let Some(expected) = EXPECTED_HOST
+        .get()
+        .cloned()
+        .or_else(|| profile.and_then(|profile| profile.expect_host.clone()))
+    else {
+        return Ok(());
+    };
+
+    let url = config::resolve_url(url_or_alias).map_err(|e| e.to_string())?;
+    let host = reqwest::Url::parse(&url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase));
+
+    match host.as_deref() == Some(expected.trim().to_lowercase().as_str()) {
+        true => Ok(()),
+        false => Err(format!(
+            "{} does not target the expected host {} – aborted.",
+            url, expected
+        )),
+    }
+}
+
+/// Exits if the url (or alias) does not resolve to the expected host.
+fn verify_host(url_or_alias: &str, profile: Option<&config::Profile>) {
+    if let Err(msg) = check_host(url_or_alias, profile) {
+        println!("{} {}", "Error".white().on_red(), msg);
+        super::exit(1)
+    }
+}
+
+/// Verifies the expected host (if any) without asking for confirmation – for unattended commands
+/// mutating customers (serve, worker, tf-resource, ansible). Returns an error if the host does not match.
+pub fn check_expected_host(url_or_alias: &str) -> Result<(), String> {
+    let profile = config::find_profile(url_or_alias).map_err(|e| e.to_string())?;
+    check_host(url_or_alias, profile.as_ref().map(|(_, profile)| profile))
+}
+
+/// Exits if the url (or alias) of an unattended command does not resolve to the expected host.
+pub fn require_expected_host(url_or_alias: &str) {
+    if let Err(msg) = check_expected_host(url_or_alias) {
+        println!("{} {}", "Error".white().on_red(), msg);
+        super::exit(1)
+    }
+}
+
+/// Prints a banner if the url or alias belongs to a production profile.
+pub fn print_production_banner(url_or_alias: &str) {
+    if let Ok(Some((alias, profile))) = config::find_profile(url_or_alias) {
+        if profile.production {
+            eprintln!(
+                "{}",
+                format!(" PRODUCTION – {} ({}) ", alias, profile.url)
+                    .white()
+                    .bold()
+                    .on_red()
+            );
+        }
+    }
+}
+
+/// Verifies the expected host (if any) and asks to type the alias before mutating a customer if the
+/// profile requires confirmation (not in a dry run). Exits if the host or the confirmation does not match.
+pub fn confirm_mutation(url_or_alias: &str) {
+    let profile = match config::find_profile(url_or_alias) {
+        Ok(profile) => profile,
+        Err(ref e) => handle_errors(e),
+    };
+
+    verify_host(url_or_alias, profile.as_ref().map(|(_, profile)| profile));
+
+    let Some((alias, profile)) =
+        profile.filter(|(_, profile)| profile.confirm && !dry_run_active())
+    else {
+        return;
+    };
+
+    let confirmation = dialoguer::Input::<String>::new()
+        .with_prompt(format!(
+            "Type {} to confirm changes on {}",
+            alias, profile.url
+        ))
+        .interact_text();
+
+    if confirmation.ok().as_deref() != Some(alias.as_str()) {
+        println!(
+            "{} Confirmation failed – aborted.",
+            "Error".white().on_red()
+        );
+        super::exit(1)
+    }
+}
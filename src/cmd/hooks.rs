@@ -0,0 +1,100 @@
+use super::otel::traceparent;
+use crate::config;
+use colored::*;
+use serde_json::json;
+use std::{collections::BTreeMap, process::Stdio};
+use tokio::{io::AsyncWriteExt, process::Command};
+
+/// Events hooks can be configured for (config: [hooks] on_create, on_update, on_delete)
+#[derive(Clone, Copy)]
+pub(super) enum HookEvent {
+    Create,
+    Update,
+    Delete,
+}
+
+impl HookEvent {
+    fn name(self) -> &'static str {
+        match self {
+            HookEvent::Create => "create",
+            HookEvent::Update => "update",
+            HookEvent::Delete => "delete",
+        }
+    }
+}
+
+/// Runs the configured hook for an event (if any) – values are passed as env vars (DCPROV_<NAME>)
+/// and JSON on stdin, never as part of the command. A failing hook only prints a warning.
+pub(super) async fn run_hook(event: HookEvent, values: BTreeMap<&str, String>) {
+    let hooks = config::load_config().unwrap_or_default().hooks;
+    let template = match event {
+        HookEvent::Create => hooks.on_create,
+        HookEvent::Update => hooks.on_update,
+        HookEvent::Delete => hooks.on_delete,
+    };
+    let Some(command) = template else {
+        return;
+    };
+
+    // splicing values into the shell command can't be made injection-safe (quoted placeholders, %VAR% on Windows)
+    if command.contains("{{") {
+        eprintln!(
+            "{} on_{} hook not run – placeholders are not supported, read DCPROV_<NAME> or the JSON on stdin: {}",
+            "Warning".black().on_yellow(),
+            event.name(),
+            command
+        );
+        return;
+    }
+
+    #[cfg(not(windows))]
+    let mut cmd = Command::new("sh");
+    #[cfg(not(windows))]
+    cmd.arg("-c").arg(&command);
+    #[cfg(windows)]
+    let mut cmd = Command::new("cmd");
+    #[cfg(windows)]
+    cmd.arg("/C").arg(&command);
+
+    cmd.env("DCPROV_EVENT", event.name());
+    if let Some(traceparent) = traceparent() {
+        cmd.env("TRACEPARENT", traceparent);
+    }
+    for (name, value) in &values {
+        cmd.env(format!("DCPROV_{}", name.to_uppercase()), value);
+    }
+
+    let mut payload = json!(values);
+    payload["event"] = event.name().into();
+
+    // hook output goes to stderr – stdout of dcprov stays machine-readable (JSON, CSV, tf-resource)
+    let child = cmd.stdin(Stdio::piped()).stdout(std::io::stderr()).spawn();
+
+    let result = match child {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                // hooks not reading stdin close the pipe – not an error
+                let _ = stdin.write_all(payload.to_string().as_bytes()).await;
+            }
+            child.wait().await
+        }
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(status) if status.success() => (),
+        Ok(status) => eprintln!(
+            "{} on_{} hook failed ({}): {}",
+            "Warning".black().on_yellow(),
+            event.name(),
+            status,
+            command
+        ),
+        Err(e) => eprintln!(
+            "{} Could not run on_{} hook: {}",
+            "Warning".black().on_yellow(),
+            event.name(),
+            e
+        ),
+    }
+}
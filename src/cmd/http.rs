@@ -0,0 +1,55 @@
+use super::otel::traceparent;
+use reqwest::{
+    header::{HeaderMap, HeaderValue},
+    Client, Request, Response,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static NO_COMPRESSION: AtomicBool = AtomicBool::new(false);
+
+/// Disables gzip / deflate compressed responses for requests sent by dcprov itself (CMDB sync,
+/// webhooks, telemetry, ...) – dco3 builds its own client, compressed via reqwest feature unification.
+pub fn disable_compression() {
+    NO_COMPRESSION.store(true, Ordering::SeqCst);
+}
+
+/// Returns an HTTP client requesting compressed responses (unless disabled via --no-compression).
+/// Requests carry the traceparent of the command (if any) so receivers can join the trace.
+pub(super) fn http_client() -> Client {
+    let compression = !NO_COMPRESSION.load(Ordering::SeqCst);
+
+    let mut headers = HeaderMap::new();
+    if let Some(traceparent) = traceparent().and_then(|value| HeaderValue::from_str(&value).ok()) {
+        headers.insert("traceparent", traceparent);
+    }
+
+    Client::builder()
+        .gzip(compression)
+        .deflate(compression)
+        .default_headers(headers)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Prints method, url, status and payload sizes of a request (verbose output).
+/// The response size is unknown if the response is compressed (decoded on the fly) or chunked.
+pub(super) fn log_payload(req: &Request, res: &Response) {
+    let sent = req
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map_or(0, <[u8]>::len);
+    let received = res
+        .content_length()
+        .map_or("unknown (compressed or chunked)".to_string(), |len| {
+            format!("{} bytes", len)
+        });
+
+    eprintln!(
+        "{} {}: {} | sent: {} bytes | received: {}",
+        req.method(),
+        req.url(),
+        res.status(),
+        sent,
+        received
+    );
+}
@@ -0,0 +1,244 @@
+use super::attributes::{fetch_all_attributes, include_attributes, ATTRIBUTES_CONCURRENCY};
+use super::{
+    dracoon_error_to_string, fetch_all_customers, handle_dracoon_errors, is_cancelled,
+    print_csv_header, print_json, target_url, try_update_customer_attributes, DcProv, PrintType,
+};
+use clap::{error::ErrorKind, CommandFactory, ValueEnum};
+use colored::*;
+use dco3::{
+    auth::Provisioning,
+    provisioning::{Customer, CustomerAttributes},
+    Dracoon,
+};
+use serde_json::json;
+
+// customer attribute holding the lifecycle state (managed by dcprov)
+pub(super) const LIFECYCLE_ATTRIBUTE: &str = "lifecycle";
+
+const LIFECYCLE_CSV_HEADER: &str = "id,companyName,lifecycle";
+
+/// Lifecycle state of a customer – stored in the `lifecycle` attribute
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum LifecycleState {
+    Active,
+    Suspended,
+    PendingDeletion,
+}
+
+impl LifecycleState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LifecycleState::Active => "active",
+            LifecycleState::Suspended => "suspended",
+            LifecycleState::PendingDeletion => "pending-deletion",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        LifecycleState::from_str(value, true).ok()
+    }
+
+    /// Transitions allowed without --force: active ↔ suspended ↔ pending-deletion (customers are
+    /// suspended before they are marked for deletion). Customers without a state may enter any.
+    fn can_transition_to(self, next: Self) -> bool {
+        use LifecycleState::*;
+
+        matches!(
+            (self, next),
+            (Active, Suspended)
+                | (Suspended, Active)
+                | (Suspended, PendingDeletion)
+                | (PendingDeletion, Suspended)
+        ) || self == next
+    }
+
+    /// Customer filter matching customers in this state – see `dcprov help filters`.
+    pub fn filter(self) -> String {
+        format!(
+            "attributeKey:eq:{}|attributeValue:eq:{}",
+            LIFECYCLE_ATTRIBUTE,
+            self.as_str()
+        )
+    }
+}
+
+/// Returns the lifecycle state of a customer (if its attributes were fetched and carry one).
+pub(super) fn lifecycle_of(customer: &Customer) -> Option<&str> {
+    customer
+        .customer_attributes
+        .as_ref()?
+        .items
+        .iter()
+        .find(|attrib| attrib.key == LIFECYCLE_ATTRIBUTE)
+        .map(|attrib| attrib.value.as_str())
+}
+
+/// Returns the stored lifecycle state of a customer – exits if the attributes can't be fetched.
+async fn current_state(provider: &Dracoon<Provisioning>, id: u64) -> Option<String> {
+    match fetch_all_attributes(provider, id).await {
+        Ok(attribs) => attribs
+            .into_iter()
+            .find(|attrib| attrib.key == LIFECYCLE_ATTRIBUTE)
+            .map(|attrib| attrib.value),
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not get customer attributes."));
+            super::exit(1)
+        }
+    }
+}
+
+fn invalid_value(msg: String) -> ! {
+    DcProv::command().error(ErrorKind::InvalidValue, msg).exit()
+}
+
+/// Resolves the arguments of `lifecycle set`: `[URL] ID STATE` – the url defaults to --profile or
+/// the default url of the config. Exits with a usage error on invalid values.
+pub fn lifecycle_set_args(args: Vec<String>) -> (String, u64, LifecycleState) {
+    let (url, id, state) = match args.as_slice() {
+        [url, id, state] => (Some(url.clone()), id, state),
+        [id, state] => (None, id, state),
+        _ => invalid_value("expected [URL] ID STATE".into()),
+    };
+
+    let id = id
+        .parse::<u64>()
+        .unwrap_or_else(|_| invalid_value(format!("invalid customer id '{}'", id)));
+    let state = LifecycleState::from_str(state, true).unwrap_or_else(|_| {
+        let states: Vec<_> = LifecycleState::value_variants()
+            .iter()
+            .map(|state| state.as_str())
+            .collect();
+        invalid_value(format!(
+            "invalid state '{}' [possible values: {}]",
+            state,
+            states.join(", ")
+        ))
+    });
+
+    (target_url(url), id, state)
+}
+
+/// Sets the lifecycle state of a customer – exits if the transition is not allowed (unless `force`).
+pub async fn set_lifecycle(
+    provider: Dracoon<Provisioning>,
+    id: u64,
+    state: LifecycleState,
+    force: bool,
+) {
+    let current = current_state(&provider, id).await;
+
+    let allowed = match current.as_deref().map(LifecycleState::parse) {
+        None => true,
+        Some(Some(current)) => current.can_transition_to(state),
+        // unknown values (set manually) are only replaced with --force
+        Some(None) => false,
+    };
+    if !allowed && !force {
+        println!(
+            "{} Customer {} can't change from {} to {} – pass --force to set it anyway.",
+            "Error".white().on_red(),
+            id,
+            current.unwrap_or_default(),
+            state.as_str()
+        );
+        super::exit(1)
+    }
+
+    let mut attribs = CustomerAttributes::new();
+    attribs.add_attribute(LIFECYCLE_ATTRIBUTE, state.as_str());
+
+    if let Err(ref e) = try_update_customer_attributes(&provider, id, attribs).await {
+        handle_dracoon_errors(e, Some("Could not update customer attributes."));
+        super::exit(1)
+    }
+
+    println!(
+        "{}Lifecycle of customer {} set to {} (was: {})",
+        "Success ".green(),
+        id,
+        state.as_str(),
+        current.as_deref().unwrap_or("-")
+    );
+}
+
+/// Prints the lifecycle state of a customer (- if none is set).
+pub async fn get_lifecycle(provider: Dracoon<Provisioning>, id: u64, print_type: PrintType) {
+    let current = current_state(&provider, id).await;
+
+    match print_type {
+        PrintType::Csv => println!("{},{}", id, current.as_deref().unwrap_or_default()),
+        PrintType::Pretty => println!(
+            "id: {} | lifecycle: {}",
+            id,
+            current.as_deref().unwrap_or("-")
+        ),
+        PrintType::Json => print_json(json!({ "id": id, "lifecycle": current })),
+    }
+}
+
+/// Lists customers with a lifecycle state (optionally only the given state).
+pub async fn list_lifecycle(
+    provider: Dracoon<Provisioning>,
+    state: Option<LifecycleState>,
+    filter: Option<String>,
+    print_type: PrintType,
+) {
+    let lifecycle_filter = match state {
+        Some(state) => state.filter(),
+        None => format!("attributeKey:eq:{}", LIFECYCLE_ATTRIBUTE),
+    };
+    let filter = match filter {
+        Some(filter) => format!("{}|{}", filter, lifecycle_filter),
+        None => lifecycle_filter,
+    };
+
+    let mut customers = fetch_all_customers(&provider, Some(filter), Some("id:asc".into())).await;
+
+    // key and value filters may match different attributes – the state is checked per customer
+    if let Err(e) = include_attributes(&provider, &mut customers, ATTRIBUTES_CONCURRENCY).await {
+        println!(
+            "{} Could not get customer attributes: {}",
+            "Error".white().on_red(),
+            dracoon_error_to_string(e)
+        );
+        super::exit(1)
+    }
+    if is_cancelled() {
+        super::exit(1)
+    }
+
+    let rows: Vec<_> = customers
+        .iter()
+        .filter_map(|customer| lifecycle_of(customer).map(|lifecycle| (customer, lifecycle)))
+        .filter(|(_, lifecycle)| state.is_none_or(|state| state.as_str() == *lifecycle))
+        .collect();
+
+    match print_type {
+        PrintType::Csv => {
+            print_csv_header(LIFECYCLE_CSV_HEADER);
+            for (customer, lifecycle) in &rows {
+                println!("{},{},{}", customer.id, customer.company_name, lifecycle);
+            }
+        }
+        PrintType::Pretty => {
+            for (customer, lifecycle) in &rows {
+                println!(
+                    "company: {} | id: {} | lifecycle: {}",
+                    customer.company_name, customer.id, lifecycle
+                );
+            }
+            println!("customers: {}", rows.len());
+        }
+        PrintType::Json => print_json(json!({
+            "customers": rows.len(),
+            "items": rows
+                .iter()
+                .map(|(customer, lifecycle)| json!({
+                    "id": customer.id,
+                    "companyName": customer.company_name,
+                    "lifecycle": lifecycle,
+                }))
+                .collect::<Vec<_>>(),
+        })),
+    }
+}
@@ -0,0 +1,178 @@
+use super::{
+    dracoon_error_to_string, exclude::apply_exclusions, fetch_all_customers,
+    install_cancel_handler, is_cancelled, print_csv_header, print_json,
+    run_lock::acquire_batch_lock, try_update_customer, BatchReport, PrintType, UpdateCustomerPatch,
+    UpdateType,
+};
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use futures_util::{stream, StreamExt};
+use serde_json::json;
+
+const LOCK_CSV_HEADER: &str = "id,companyName,result,error";
+
+/// Result of locking / unlocking a single customer
+enum LockResult {
+    Changed,
+    Skipped,
+    Failed(String),
+    Cancelled,
+}
+
+impl LockResult {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LockResult::Changed => "changed",
+            LockResult::Skipped => "skipped",
+            LockResult::Failed(_) => "failed",
+            LockResult::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Locks (or unlocks) all customers matching the filter with the given number of parallel
+/// requests – customers already in the requested state are skipped. Asks for confirmation
+/// (unless `yes`) and prints a result per customer and a summary.
+pub async fn lock_customers(
+    provider: Dracoon<Provisioning>,
+    filter: String,
+    unlock: bool,
+    concurrency: usize,
+    yes: bool,
+    print_type: PrintType,
+) -> BatchReport {
+    let action = if unlock { "unlock" } else { "lock" };
+    let customers = fetch_all_customers(&provider, Some(filter), Some("id:asc".into())).await;
+    let (customers, excluded) =
+        apply_exclusions(&provider, customers, |customer| customer.id).await;
+
+    if customers.is_empty() {
+        eprintln!("No customers match the filter.");
+        return BatchReport::default().with_excluded(excluded);
+    }
+
+    if !yes {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!("{} {} customer(s)?", action, customers.len()))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+
+        if !confirmed {
+            println!("{} Aborted.", "Error".white().on_red());
+            super::exit(1)
+        }
+    }
+
+    let _batch_lock = acquire_batch_lock(&provider, "lock-all");
+    install_cancel_handler();
+
+    if let PrintType::Csv = print_type {
+        print_csv_header(LOCK_CSV_HEADER);
+    }
+
+    let provider = &provider;
+    let mut results = stream::iter(&customers)
+        .map(|customer| async move {
+            if customer.is_locked == Some(!unlock) {
+                return (customer, LockResult::Skipped);
+            }
+            if is_cancelled() {
+                return (customer, LockResult::Cancelled);
+            }
+
+            let patch = UpdateCustomerPatch {
+                is_locked: Some(!unlock),
+                ..Default::default()
+            };
+            match try_update_customer(provider, customer.id, UpdateType::Patch(patch)).await {
+                Ok(_) => (customer, LockResult::Changed),
+                Err(e) => (customer, LockResult::Failed(dracoon_error_to_string(e))),
+            }
+        })
+        .buffer_unordered(concurrency.max(1));
+
+    let (mut changed, mut skipped, mut failed, mut cancelled) = (0, 0, 0, 0);
+    let mut json_items = Vec::new();
+
+    while let Some((customer, result)) = results.next().await {
+        match result {
+            LockResult::Changed => changed += 1,
+            LockResult::Skipped => skipped += 1,
+            LockResult::Failed(_) => failed += 1,
+            LockResult::Cancelled => cancelled += 1,
+        }
+        let error = match &result {
+            LockResult::Failed(e) => Some(e.as_str()),
+            _ => None,
+        };
+
+        match print_type {
+            PrintType::Csv => println!(
+                "{},{},{},{}",
+                customer.id,
+                customer.company_name,
+                result.as_str(),
+                error.unwrap_or_default()
+            ),
+            PrintType::Json => json_items.push(json!({
+                "id": customer.id,
+                "companyName": customer.company_name,
+                "result": result.as_str(),
+                "error": error,
+            })),
+            PrintType::Pretty => match &result {
+                LockResult::Changed => println!(
+                    "{}{}ed customer {} (id: {})",
+                    "Success ".green(),
+                    action,
+                    customer.company_name,
+                    customer.id
+                ),
+                LockResult::Failed(e) => println!(
+                    "{} Could not {} customer {} (id: {}): {}",
+                    "Error".white().on_red(),
+                    action,
+                    customer.company_name,
+                    customer.id,
+                    e
+                ),
+                LockResult::Skipped | LockResult::Cancelled => (),
+            },
+        }
+    }
+
+    if cancelled > 0 {
+        eprintln!(
+            "{} Interrupted – {} customer(s) not {}ed.",
+            "Warning".black().on_yellow(),
+            cancelled,
+            action
+        );
+    }
+
+    match print_type {
+        PrintType::Json => print_json(json!({
+            "action": action,
+            "customers": customers.len(),
+            "changed": changed,
+            "skipped": skipped,
+            "failed": failed,
+            "cancelled": cancelled,
+            "items": json_items,
+        })),
+        PrintType::Pretty => println!(
+            "customers: {} | {}ed: {} | skipped (already {}ed): {} | failed: {} | cancelled: {}",
+            customers.len(),
+            action,
+            changed,
+            action,
+            skipped,
+            failed,
+            cancelled
+        ),
+        PrintType::Csv => (),
+    }
+
+    BatchReport::new(changed + skipped, failed, cancelled).with_excluded(excluded)
+}
@@ -0,0 +1,285 @@
+use super::{
+    build_params, fetch_all_customers, handle_dracoon_errors, is_cancelled,
+    sqlite::{run_sqlite, sqlite_available, SqlValue},
+    CreatedCustomer,
+};
+use crate::state;
+use colored::*;
+use dco3::{
+    auth::Provisioning,
+    provisioning::{Customer, UpdateCustomerResponse},
+    CustomerProvisioning, Dracoon,
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    OnceLock,
+};
+
+// external id (providerCustomerId / customerUuid) of the customer to operate on (--external-id)
+static EXTERNAL_ID: OnceLock<String> = OnceLock::new();
+static REFRESH_MAPPING: AtomicBool = AtomicBool::new(false);
+
+/// Sets the external id commands resolve the customer id from (instead of a customer id).
+pub fn set_external_id(external_id: String) {
+    let _ = EXTERNAL_ID.set(external_id);
+}
+
+/// Returns true if an external id was passed (--external-id).
+pub(super) fn external_id_set() -> bool {
+    EXTERNAL_ID.get().is_some()
+}
+
+/// Resyncs the id mapping from DRACOON before resolving an external id.
+pub fn enable_mapping_refresh() {
+    REFRESH_MAPPING.store(true, Ordering::SeqCst);
+}
+
+// mapping entries are stored per DRACOON url
+fn mapping_url(provider: &Dracoon<Provisioning>) -> String {
+    provider
+        .build_api_url("/")
+        .as_str()
+        .trim_end_matches('/')
+        .into()
+}
+
+// SQLite database (state directory) mapping external ids to customer ids per DRACOON url
+const MAPPING_DB: &str = "id-mapping.db";
+
+const MAPPING_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS id_mapping (
+    url TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    customer_id INTEGER NOT NULL,
+    PRIMARY KEY (url, kind, external_id)
+);
+";
+
+// kinds of external ids – only customerUuids can be rebuilt from a customer list (resync)
+const PROVIDER_CUSTOMER_ID: &str = "providerCustomerId";
+const CUSTOMER_UUID: &str = "customerUuid";
+
+/// Runs statements against the mapping database in a transaction and returns the output.
+fn run_mapping_sql(statements: &str) -> Result<String, String> {
+    let path = state::state_dir()
+        .map_err(|e| e.to_string())?
+        .join(MAPPING_DB);
+
+    // parallel runs wait for each other instead of failing on the database lock
+    let script = format!(
+        ".timeout 10000\nBEGIN IMMEDIATE;\n{}{}COMMIT;\n",
+        MAPPING_SCHEMA, statements
+    );
+
+    run_sqlite(&path, &script)
+}
+
+/// Applies changes to the mapping – the mapping is optional, without sqlite3 it is skipped.
+fn update_mapping(statements: String) {
+    if !sqlite_available() {
+        return;
+    }
+
+    if let Err(e) = run_mapping_sql(&statements) {
+        eprintln!(
+            "{} Could not update the id mapping: {}",
+            "Warning".black().on_yellow(),
+            e
+        );
+    }
+}
+
+fn insert_ids(url: &str, kind: &str, ids: impl IntoIterator<Item = (String, u64)>) -> String {
+    ids.into_iter()
+        .map(|(external_id, id)| {
+            format!(
+                "INSERT OR REPLACE INTO id_mapping VALUES ({}, {}, {}, {});\n",
+                url.sql(),
+                kind.sql(),
+                external_id.sql(),
+                id.sql()
+            )
+        })
+        .collect()
+}
+
+/// Returns the customer id mapped to an external id (providerCustomerId takes precedence).
+fn get_mapped_id(url: &str, external_id: &str) -> Option<u64> {
+    if !sqlite_available() {
+        return None;
+    }
+
+    run_mapping_sql(&format!(
+        "SELECT customer_id FROM id_mapping WHERE url = {} AND external_id = {} ORDER BY kind = {} LIMIT 1;\n",
+        url.sql(),
+        external_id.sql(),
+        CUSTOMER_UUID.sql()
+    ))
+    .ok()?
+    .trim()
+    .parse()
+    .ok()
+}
+
+/// Stores the customer UUIDs of listed customers in the id mapping.
+pub(super) fn map_customers(provider: &Dracoon<Provisioning>, customers: &[Customer]) {
+    let ids = customers.iter().filter_map(|customer| {
+        customer
+            .customer_uuid
+            .clone()
+            .map(|uuid| (uuid, customer.id))
+    });
+
+    update_mapping(insert_ids(&mapping_url(provider), CUSTOMER_UUID, ids));
+}
+
+/// Stores the providerCustomerId of a created customer in the id mapping.
+pub(super) fn map_created_customer(provider: &Dracoon<Provisioning>, customer: &CreatedCustomer) {
+    let Some(provider_customer_id) = customer.provider_customer_id.clone() else {
+        return;
+    };
+
+    let ids = [(provider_customer_id, customer.id)];
+    update_mapping(insert_ids(
+        &mapping_url(provider),
+        PROVIDER_CUSTOMER_ID,
+        ids,
+    ));
+}
+
+/// Replaces the mapped external ids of an updated customer (providerCustomerId may have changed).
+pub(super) fn map_updated_customer(
+    provider: &Dracoon<Provisioning>,
+    customer: &UpdateCustomerResponse,
+) {
+    let url = mapping_url(provider);
+    let mut statements = delete_ids_of(&url, customer.id);
+    statements += &insert_ids(
+        &url,
+        CUSTOMER_UUID,
+        [(customer.customer_uuid.clone(), customer.id)],
+    );
+    statements += &insert_ids(
+        &url,
+        PROVIDER_CUSTOMER_ID,
+        customer
+            .provider_customer_id
+            .clone()
+            .map(|provider_customer_id| (provider_customer_id, customer.id)),
+    );
+
+    update_mapping(statements);
+}
+
+fn delete_ids_of(url: &str, id: u64) -> String {
+    format!(
+        "DELETE FROM id_mapping WHERE url = {} AND customer_id = {};\n",
+        url.sql(),
+        id.sql()
+    )
+}
+
+/// Removes a deleted customer from the id mapping (external ids must not resolve to it anymore).
+pub(super) fn unmap_deleted_customer(provider: &Dracoon<Provisioning>, id: u64) {
+    update_mapping(delete_ids_of(&mapping_url(provider), id));
+}
+
+/// Returns the customer id to operate on: the passed id or the id mapped to --external-id.
+/// Unknown external ids are looked up via providerCustomerId filter or a full list (customerUuid)
+/// and added to the mapping – `--refresh-mapping` resyncs the mapping first.
+pub async fn resolve_customer_id(provider: &Dracoon<Provisioning>, id: Option<u64>) -> u64 {
+    if let Some(id) = id {
+        return id;
+    }
+
+    let Some(external_id) = EXTERNAL_ID.get() else {
+        println!(
+            "{} Pass a customer id or --external-id.",
+            "Error".white().on_red()
+        );
+        super::exit(2)
+    };
+
+    let url = mapping_url(provider);
+    let refresh = REFRESH_MAPPING.load(Ordering::SeqCst);
+
+    if refresh {
+        resync_mapping(provider, &url).await;
+    }
+
+    if let Some(id) = get_mapped_id(&url, external_id) {
+        return id;
+    }
+
+    // providerCustomerId is not part of the customer model – resolve via filter
+    let params = build_params(
+        Some(format!("providerCustomerId:eq:{}", external_id)),
+        None,
+        None,
+        Some(2),
+    );
+    let customers = match provider.get_customers(Some(params)).await {
+        Ok(customers) => customers,
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not resolve external id."));
+            super::exit(1)
+        }
+    };
+
+    if let [customer] = customers.items.as_slice() {
+        let ids = [(external_id.clone(), customer.id)];
+        update_mapping(insert_ids(&url, PROVIDER_CUSTOMER_ID, ids));
+        return customer.id;
+    }
+
+    if customers.items.len() > 1 {
+        println!(
+            "{} External id {} is ambiguous (multiple customers with this providerCustomerId).",
+            "Error".white().on_red(),
+            external_id
+        );
+        super::exit(1)
+    }
+
+    // customerUuid can't be filtered – resync from a full list (unless just done)
+    if !refresh {
+        resync_mapping(provider, &url).await;
+        if let Some(id) = get_mapped_id(&url, external_id) {
+            return id;
+        }
+    }
+
+    println!(
+        "{} No customer with external id {} (providerCustomerId or customerUuid).",
+        "Error".white().on_red(),
+        external_id
+    );
+    super::exit(1)
+}
+
+/// Replaces the customerUuids mapped for a DRACOON url with the ones of all customers – mapped
+/// providerCustomerIds are kept (they are not part of the customer list).
+async fn resync_mapping(provider: &Dracoon<Provisioning>, url: &str) {
+    let customers = fetch_all_customers(provider, None, Some("id:asc".into())).await;
+
+    // a partial list would drop valid entries
+    if is_cancelled() {
+        super::exit(1)
+    }
+
+    let ids = customers.iter().filter_map(|customer| {
+        customer
+            .customer_uuid
+            .clone()
+            .map(|uuid| (uuid, customer.id))
+    });
+
+    let mut statements = format!(
+        "DELETE FROM id_mapping WHERE url = {} AND kind = {};\n",
+        url.sql(),
+        CUSTOMER_UUID.sql()
+    );
+    statements += &insert_ids(url, CUSTOMER_UUID, ids);
+
+    update_mapping(statements);
+}
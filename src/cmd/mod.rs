@@ -1,40 +1,277 @@
-use crate::credentials::{get_dracoon_env, set_dracoon_env, SERVICE_NAME};
+use crate::config;
+use crate::credentials::{CredentialBackend, CredentialLock, CredentialStore, SERVICE_NAME};
+use crate::state::{self, PromptDraft};
+use chrono::{DateTime, FixedOffset};
 use colored::*;
 use dco3::{
     auth::{DracoonErrorResponse, Provisioning},
     provisioning::{
-        Customer, CustomerAttributes, FirstAdminUser, NewCustomerRequest as NewCustomerRequestDco3,
-        UpdateCustomerRequest,
+        Customer, CustomerAttributes, CustomerList, FirstAdminUser,
+        NewCustomerRequest as NewCustomerRequestDco3, UpdateCustomerRequest,
+        UpdateCustomerResponse,
     },
-    users::{AuthMethod, UserAuthData, UserItem},
+    users::{AuthMethod, UserAuthData, UserItem, UserList},
     CustomerProvisioning, Dracoon, DracoonClientError, KeyValueEntry, ListAllParams,
 };
-use keyring::Entry;
-use std::fs;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fs,
+    io::Read,
+    sync::atomic::{AtomicBool, Ordering as AtomicOrdering},
+};
 
+mod alerts;
+mod alias;
+mod ansible;
+mod attributes;
+mod audit;
+mod batch;
+mod cancel;
+mod capacity;
+mod changes;
+mod compat;
+mod convert;
+mod create_batch;
+mod default_url;
+mod doctor;
+mod drift;
+mod dry_run;
+mod exclude;
+mod filters;
+mod grpc;
+mod guard;
+mod hooks;
+mod http;
+mod lifecycle;
+mod lock;
+mod mapping;
 mod models;
+mod normalize;
+mod otel;
+mod output;
+mod picker;
+mod playbook;
+mod plugins;
+mod profile_args;
+mod raw;
+mod redact;
+mod report;
+mod results;
+mod retry;
+mod run_lock;
+mod selftest;
+mod serve;
+mod sheets;
+mod shell;
+mod sqlite;
+mod stats;
+mod sync;
+mod telemetry;
+mod terraform;
+mod usage;
+mod users;
 mod utils;
-pub use {models::*, utils::parse_key_val};
+mod whoami;
+mod worker;
+use cancel::{install_cancel_handler, is_cancelled};
+use compat::{compat_new_customer, compat_output, compat_update_patch};
+use dry_run::{dry_run_active, print_dry_run};
+use hooks::{run_hook, HookEvent};
+use http::{http_client, log_payload};
+use lifecycle::lifecycle_of;
+use mapping::{map_created_customer, map_customers, map_updated_customer, unmap_deleted_customer};
+use output::{
+    format_number, format_timestamp, json_query_active, print_created_customer, print_csv_header,
+    print_deleted_customer, print_json, print_update_outcome, print_updated_attributes,
+    TIMESTAMP_FIELDS,
+};
+use raw::{get_json, print_raw, raw_output_active};
+use redact::{redact, redact_customer, redact_fields, redact_user};
+pub use {
+    alerts::usage_alerts,
+    alias::expand_alias,
+    ansible::*,
+    attributes::*,
+    audit::audit_quotas,
+    batch::{finish_batch, BatchReport},
+    cancel::{deadline_exceeded, set_deadline, DEADLINE_EXIT_CODE},
+    capacity::check_capacity,
+    changes::list_changes,
+    compat::print_capabilities,
+    convert::convert_trial,
+    create_batch::{create_customers, is_customer_batch_file, parse_customers_from_file},
+    default_url::{set_profile, target_url, target_url_and_id},
+    doctor::config_doctor,
+    drift::drift_check,
+    dry_run::enable_dry_run,
+    exclude::set_exclusions,
+    filters::print_help,
+    grpc::serve_grpc,
+    guard::{check_expected_host, confirm_mutation, require_expected_host, set_expected_host},
+    http::disable_compression,
+    lifecycle::{get_lifecycle, lifecycle_set_args, list_lifecycle, set_lifecycle, LifecycleState},
+    lock::lock_customers,
+    mapping::{enable_mapping_refresh, resolve_customer_id, set_external_id},
+    models::*,
+    normalize::{normalize_quotas, RoundingMode},
+    output::{set_csv_bom, set_json_query, set_number_locale, set_timestamp_format},
+    picker::pick_customer_id,
+    playbook::{load_playbook, run_playbook},
+    plugins::{list_plugins, run_plugin},
+    profile_args::apply_profile_args,
+    raw::enable_raw_output,
+    redact::enable_pii_redaction,
+    report::*,
+    results::{CreatedCustomer, DeletedCustomer, FieldChange, UpdateOutcome, UpdatedAttributes},
+    run_lock::disable_batch_lock,
+    selftest::selftest,
+    serve::*,
+    sheets::push_to_sheet,
+    shell::run_shell,
+    sqlite::{export_sqlite, ExportFormat},
+    stats::customer_stats,
+    sync::*,
+    telemetry::{command_name, exit, finish_telemetry, start_telemetry},
+    terraform::*,
+    usage::{export_usage, UsagePartition},
+    users::{diff_customer_users, export_all_users},
+    whoami::whoami,
+    worker::*,
+};
+
+// max. customers fetched with list --all unless configured or lifted (--no-limit)
+const DEFAULT_MAX_ITEMS: u64 = 100_000;
 
 // header for CSV output (list customers)
 const CUSTOMER_CSV_HEADER: &str =
     "companyName,contractType,userUsed,userMax,quotaUsed,quotaMax,id,createdAt";
 
-const CUSTOMER_USERS_CSV_HEADER: &str = "id,firstName,lastName,userName,isLocked,lastLoginAt";
+const CUSTOMER_USERS_CSV_HEADER: &str =
+    "id,firstName,lastName,userName,isLocked,lastLoginAt,email,expireAt";
+// additional CSV columns for users with --with-home-rooms
+const HOME_ROOM_CSV_COLUMNS: &str = "homeRoomId,missingHomeRoom";
 const CUSTOMER_ATTRIBUTES_CSV_HEADER: &str = "key,value";
 
+// customer fields (API names) available as columns
+const CUSTOMER_FIELDS: &[&str] = &[
+    "id",
+    "companyName",
+    "customerContractType",
+    "quotaMax",
+    "quotaUsed",
+    "userMax",
+    "userUsed",
+    "createdAt",
+    "updatedAt",
+    "lastLoginAt",
+    "trialDaysLeft",
+    "isLocked",
+    "customerUuid",
+    "cntInternalUser",
+    "cntGuestUser",
+    "status",
+];
+
+// user fields (API names) available as columns
+const USER_FIELDS: &[&str] = &[
+    "id",
+    "firstName",
+    "lastName",
+    "userName",
+    "email",
+    "phone",
+    "isLocked",
+    "expireAt",
+    "lastLoginSuccessAt",
+    "isEncryptionEnabled",
+    "hasManageableRooms",
+    "homeRoomId",
+];
+
+// interval to poll the user list of a new customer (create --verify)
+const VERIFY_POLL_SECS: u64 = 5;
+
+// prefix to address customer attributes instead of customer fields
+const ATTRIBUTE_PREFIX: &str = "attribute:";
+
 // supported update types
 pub enum UpdateType {
     CompanyName(String),
     QuotaMax(u64),
     UserMax(u64),
+    /// quota per user – multiplied by the current user max
+    QuotaPerUser(u64),
+    /// user max and quota per user
+    UserMaxWithQuotaPerUser(u64, u64),
+    ContractType(ContractType),
+    /// partial update from a JSON patch file
+    Patch(UpdateCustomerPatch),
+}
+
+// options for listing customers
+pub struct ListOptions {
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    pub offset: Option<u64>,
+    pub limit: Option<u64>,
+    pub print_type: PrintType,
+    pub all: bool,
+    /// fetch all pages regardless of the max. items of --all
+    pub no_limit: bool,
+    pub changed_since_last_run: bool,
+    pub columns: Option<Vec<String>>,
+    /// only list customers at or over their quota / user limit
+    pub only_over_limit: bool,
+    /// only list the top N customers of the sort (fetches further pages if needed)
+    pub top: Option<u64>,
+    /// fetch customer attributes (one request per customer)
+    pub include_attributes: bool,
+    /// parallel requests to fetch attributes
+    pub concurrency: usize,
+}
+
+// options for listing customer users
+pub struct UserListOptions {
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    pub offset: Option<u64>,
+    pub limit: Option<u64>,
+    pub print_type: PrintType,
+    pub columns: Option<Vec<String>>,
+    pub with_home_rooms: bool,
+    /// fail if users don't match the user model (instead of parsing leniently)
+    pub strict: bool,
 }
 
 // supported customer print output
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PrintType {
     Pretty,
     Csv,
+    Json,
+}
+
+impl PrintType {
+    /// Output format from the --csv and --json flags – JSON if a JMESPath query (--jmespath) is set.
+    pub fn from_flags(csv: bool, json: bool) -> Self {
+        match json {
+            true => PrintType::Json,
+            false => PrintType::from_csv_flag(csv),
+        }
+    }
+
+    /// Output format from the --csv flag – JSON if a JMESPath query (--jmespath) is set.
+    pub fn from_csv_flag(csv: bool) -> Self {
+        match (json_query_active(), csv) {
+            (true, _) => PrintType::Json,
+            (false, true) => PrintType::Csv,
+            (false, false) => PrintType::Pretty,
+        }
+    }
 }
 
 fn print_dracoon_error(err: &DracoonErrorResponse) {
@@ -44,18 +281,26 @@ fn print_dracoon_error(err: &DracoonErrorResponse) {
     };
 }
 
-fn handle_dracoon_errors(err: &DracoonClientError, msg: Option<&str>) -> () {
+fn handle_dracoon_errors(err: &DracoonClientError, msg: Option<&str>) {
     let msg = msg.unwrap_or("Unknown error");
 
     println!("{} {}", "Error".white().on_red(), msg);
 
     match err {
         DracoonClientError::Http(err) => print_dracoon_error(err),
-        _ => println!("{} {}", "Error".white().on_red(), "Uncaught error."),
+        err => print_error(&DcProvError::from_client_error(err)),
+    }
+}
+
+// error message for machine-readable output (no colors, includes API details)
+fn dracoon_error_to_string(err: DracoonClientError) -> String {
+    match err {
+        DracoonClientError::Http(err) => err.to_string(),
+        err => DcProvError::from_client_error(&err).to_string(),
     }
 }
 
-pub fn handle_errors(err: &DcProvError) {
+fn print_error(err: &DcProvError) {
     match err {
         DcProvError::BadRequest(err) => print_dracoon_error(err),
         DcProvError::Unauthorized(err) => print_dracoon_error(err),
@@ -64,26 +309,171 @@ pub fn handle_errors(err: &DcProvError) {
         DcProvError::PaymentRequired(err) => print_dracoon_error(err),
         DcProvError::Conflict(err) => print_dracoon_error(err),
         DcProvError::Unknown(err) => print_dracoon_error(err),
-        DcProvError::Io => println!("{} {}", "Error".white().on_red(), "IO error."),
-        DcProvError::Other => println!("{} {}", "Error".white().on_red(), "Uncaught error."),
-        _ => println!("{} {}", "Error".white().on_red(), "Uncaught error."),
+        DcProvError::Io => println!("{} IO error.", "Error".white().on_red()),
+        DcProvError::InvalidConfig(msg) => {
+            println!("{} Invalid config: {}", "Error".white().on_red(), msg)
+        }
+        DcProvError::Other => println!("{} Uncaught error.", "Error".white().on_red()),
+        err => println!("{} {}", "Error".white().on_red(), err),
     }
+}
 
-    std::process::exit(1)
+pub fn handle_errors(err: &DcProvError) -> ! {
+    print_error(err);
+
+    exit(1)
 }
 
-pub async fn init_provisioning(
-    url: &str,
-    token: Option<String>,
-) -> Result<Dracoon<Provisioning>, DcProvError> {
-    let url = if url.starts_with("https://") {
+/// Options applied to the DRACOON client of a single run
+pub struct ClientOptions {
+    pub verbose: bool,
+    /// keyring service name the tokens are stored under
+    pub keyring_service: String,
+    pub credential_backend: CredentialBackend,
+}
+
+impl ClientOptions {
+    pub fn new(verbose: bool, keyring_service: Option<String>) -> Self {
+        // invalid configs are reported by the commands using them
+        let config = config::load_config().unwrap_or_default();
+
+        // flag takes precedence over config
+        let keyring_service = keyring_service
+            .or(config.keyring_service)
+            .unwrap_or_else(|| SERVICE_NAME.to_string());
+        let credential_backend = config.credentials.backend;
+
+        if verbose {
+            eprintln!(
+                "Credential store: {:?} (service: {})",
+                credential_backend, keyring_service
+            );
+        }
+
+        Self {
+            verbose,
+            keyring_service,
+            credential_backend,
+        }
+    }
+
+    /// Key of the stored token of a (resolved) DRACOON url in the state (e.g. token expiry).
+    fn token_key(&self, url: &str) -> String {
+        format!("{}|{}", self.keyring_service, url)
+    }
+
+    /// Returns the configured token storage for a (resolved) DRACOON url.
+    pub fn credential_store(&self, url: &str) -> Result<CredentialStore, DcProvError> {
+        CredentialStore::new(self.credential_backend, &self.keyring_service, url)
+    }
+}
+
+/// Returns the url with https scheme (http is upgraded, missing scheme is added).
+pub fn normalize_url(url: &str) -> String {
+    if url.starts_with("https://") {
         url.to_string()
     } else if url.starts_with("http://") {
         url.replace("http://", "https://")
     } else {
         format!("https://{}", url)
+    }
+}
+
+// env var holding the X-SDS-Service-Token (used if no token is passed via --token)
+pub const TOKEN_ENV: &str = "DCPROV_TOKEN";
+
+/// Source of the X-SDS-Service-Token used for a DRACOON url
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenSource {
+    Flag,
+    Env,
+    Keyring,
+    File,
+}
+
+impl std::fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenSource::Flag => write!(f, "flag (--token)"),
+            TokenSource::Env => write!(f, "env ({})", TOKEN_ENV),
+            TokenSource::Keyring => write!(f, "keyring"),
+            TokenSource::File => write!(f, "config (credentials file)"),
+        }
+    }
+}
+
+/// Stores (or removes without ttl) the expiry of the stored token of a url – returns the expiry.
+pub fn set_token_ttl(
+    url: &str,
+    ttl: Option<chrono::Duration>,
+    client_opts: &ClientOptions,
+) -> Result<Option<DateTime<FixedOffset>>, DcProvError> {
+    let expiry = ttl.map(|ttl| (chrono::Utc::now() + ttl).fixed_offset());
+    state::set_token_expiry(&client_opts.token_key(url), expiry)?;
+
+    Ok(expiry)
+}
+
+/// Wipes the stored token of a url from the credential store if its expiry (config set --ttl) has
+/// passed – returns true if the token expired.
+pub fn expire_stored_token(url: &str, client_opts: &ClientOptions) -> bool {
+    let key = client_opts.token_key(url);
+    let Ok(Some(expiry)) = state::get_token_expiry(&key) else {
+        return false;
     };
+    if expiry > chrono::Utc::now() {
+        return false;
+    }
+
+    // the token might already be gone (deleted via keyring tools)
+    if let Ok(store) = client_opts.credential_store(url) {
+        let _ = store.delete();
+    }
+    let _ = state::set_token_expiry(&key, None);
 
+    eprintln!(
+        "{} Stored token for {} expired at {} and was deleted.",
+        "Warning".black().on_yellow(),
+        url,
+        expiry.to_rfc3339()
+    );
+
+    true
+}
+
+/// Looks up the token for a resolved url: flag, env var, then the configured credential store.
+pub fn find_token(
+    url: &str,
+    token: Option<String>,
+    client_opts: &ClientOptions,
+) -> Option<(String, TokenSource)> {
+    if let Some(token) = token {
+        return Some((token, TokenSource::Flag));
+    }
+
+    if let Some(token) = std::env::var(TOKEN_ENV).ok().filter(|t| !t.is_empty()) {
+        return Some((token, TokenSource::Env));
+    }
+
+    let source = match client_opts.credential_backend {
+        CredentialBackend::Keyring => TokenSource::Keyring,
+        CredentialBackend::File => TokenSource::File,
+        CredentialBackend::None => return None,
+    };
+    if expire_stored_token(url, client_opts) {
+        return None;
+    }
+    let token = client_opts.credential_store(url).ok()?.get().ok()?;
+
+    Some((token, source))
+}
+
+/// Returns the token for a (resolved) url – prompts for it (and stores it) if none is found.
+pub(super) fn resolve_token(
+    url: &str,
+    token: Option<String>,
+    client_opts: &ClientOptions,
+) -> Result<String, DcProvError> {
     let ask_for_token = || {
         dialoguer::Password::new()
             .with_prompt("Please enter X-SDS-Service-Token: ")
@@ -91,42 +481,74 @@ pub async fn init_provisioning(
             .or(Err(DcProvError::Io))
     };
 
-    let entry = Entry::new(SERVICE_NAME, &url).map_err(|_| DcProvError::CredentialStorageFailed);
+    // expired tokens are never replaced by a prompted (unlimited) token
+    let expired = expire_stored_token(url, client_opts);
 
-    let (token, store) = match token {
-        // Provided token, don't store
-        Some(token) => (token, false),
+    let token = match find_token(url, token, client_opts) {
+        // Provided or stored token, don't store
+        Some((token, _)) => token,
+        None if expired => return Err(DcProvError::TokenExpired),
+        // never prompt (nor touch the keyring) without a credential store
+        None if client_opts.credential_backend == CredentialBackend::None => {
+            return Err(DcProvError::CredentialStoreDisabled)
+        }
         None => {
-            // Entry present and holds a secret
-            if let Ok(entry) = &entry {
-                if let Ok(stored_secret) = get_dracoon_env(entry) {
-                    (stored_secret, false)
-                } else {
-                    // Entry present but no secret, ask and store
-                    (ask_for_token()?, true)
+            // Only one process prompts – parallel invocations wait and use the stored token
+            let _lock = CredentialLock::acquire()?;
+
+            match find_token(url, None, client_opts) {
+                Some((token, _)) => token,
+                None => {
+                    let token = ask_for_token()?;
+
+                    // Entry present but no secret, store (no entry: ask but don't store)
+                    if let Ok(store) = client_opts.credential_store(url) {
+                        store.set(&token)?;
+                    }
+
+                    token
                 }
-            } else {
-                // No entry, ask but don't store
-                (ask_for_token()?, false)
             }
         }
     };
 
-    // If necessary, create a new entry to store the secret
-    if store {
-        let entry =
-            Entry::new(SERVICE_NAME, &url).map_err(|_| DcProvError::CredentialStorageFailed)?;
-        set_dracoon_env(&entry, &token)?;
-    }
+    Ok(token)
+}
 
-    Ok(Dracoon::builder()
+pub async fn init_provisioning(
+    url: &str,
+    token: Option<String>,
+    client_opts: &ClientOptions,
+) -> Result<Dracoon<Provisioning>, DcProvError> {
+    guard::print_production_banner(url);
+
+    let url = config::resolve_url(url)?;
+    let token = resolve_token(&url, token, client_opts)?;
+
+    Dracoon::builder()
         .with_base_url(&url)
         .with_provisioning_token(token)
         .build_provisioning()
-        .map_err(|_| DcProvError::InvalidAccount)?)
+        .map_err(DcProvError::from)
+}
+
+/// Returns the limit status of a customer: over-quota (quota used ≥ quota max), over-users
+/// (users used ≥ user max), over-limits (both) or ok.
+fn customer_status(customer: &Customer) -> &'static str {
+    match (
+        customer.quota_used >= customer.quota_max,
+        customer.user_used >= customer.user_max,
+    ) {
+        (true, true) => "over-limits",
+        (true, false) => "over-quota",
+        (false, true) => "over-users",
+        (false, false) => "ok",
+    }
 }
 
 fn customer_to_string(customer: Customer, print_type: PrintType) -> String {
+    let customer = redact_customer(customer);
+
     match print_type {
         PrintType::Csv => {
             let cus_line = format!(
@@ -138,42 +560,235 @@ fn customer_to_string(customer: Customer, print_type: PrintType) -> String {
                 customer.quota_used,
                 customer.quota_max,
                 customer.id,
-                customer.created_at
+                format_timestamp(&customer.created_at)
             );
             cus_line
         }
         PrintType::Pretty => {
-            let cus_line = format!("company: {} | contract: {} | users used: {} | users max: {} | quota used: {} | quota max: {} | id: {} | created_at: {}", customer.company_name, customer.customer_contract_type, customer.user_used, customer.user_max, customer.quota_used, customer.quota_max, customer.id, customer.created_at);
+            let status = match customer_status(&customer) {
+                "ok" => String::new(),
+                status => format!(" | {}", status.white().on_red()),
+            };
+            let lifecycle = lifecycle_of(&customer)
+                .map(|lifecycle| format!(" | lifecycle: {}", lifecycle))
+                .unwrap_or_default();
+            let cus_line = format!("company: {} | contract: {} | users used: {} | users max: {} | quota used: {} | quota max: {} | id: {} | created_at: {}{}{}", customer.company_name, customer.customer_contract_type, format_number(customer.user_used), format_number(customer.user_max), format_number(customer.quota_used), format_number(customer.quota_max), customer.id, format_timestamp(&customer.created_at), status, lifecycle);
             cus_line
         }
+        PrintType::Json => customer_to_json(&customer).to_string(),
     }
 }
 
+/// Returns the JSON representation of a customer (API field names) – redacted.
+fn customer_to_json(customer: &Customer) -> Value {
+    let attributes: serde_json::Map<String, Value> = customer
+        .customer_attributes
+        .iter()
+        .flat_map(|attribs| attribs.items.iter())
+        .map(|attrib| (attrib.key.clone(), Value::String(attrib.value.clone())))
+        .collect();
+
+    redact_fields(compat_output(json!({
+        "id": customer.id,
+        "companyName": customer.company_name,
+        "customerContractType": customer.customer_contract_type,
+        "quotaMax": customer.quota_max,
+        "quotaUsed": customer.quota_used,
+        "userMax": customer.user_max,
+        "userUsed": customer.user_used,
+        "createdAt": customer.created_at,
+        "updatedAt": customer.updated_at,
+        "lastLoginAt": customer.last_login_at,
+        "trialDaysLeft": customer.trial_days_left,
+        "isLocked": customer.is_locked,
+        "customerUuid": customer.customer_uuid,
+        "cntInternalUser": customer.cnt_internal_user,
+        "cntGuestUser": customer.cnt_guest_user,
+        "status": customer_status(customer),
+        "customerAttributes": attributes,
+    })))
+}
+
+/// Returns the JSON representation of a user (API field names).
+fn user_to_json(user: &UserItem) -> Value {
+    json!({
+        "id": user.id,
+        "firstName": user.first_name,
+        "lastName": user.last_name,
+        "userName": user.user_name,
+        "email": user.email,
+        "phone": user.phone,
+        "isLocked": user.is_locked,
+        "expireAt": user.expire_at,
+        "lastLoginSuccessAt": user.last_login_success_at,
+        "isEncryptionEnabled": user.is_encryption_enabled,
+        "hasManageableRooms": user.has_manageable_rooms,
+        "homeRoomId": user.home_room_id,
+    })
+}
+
+/// Keeps only the given columns of a JSON object – columns not part of the object are looked up.
+fn json_columns(
+    value: Value,
+    columns: Option<&Vec<String>>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Value {
+    let Some(columns) = columns else {
+        return value;
+    };
+
+    Value::Object(
+        columns
+            .iter()
+            .map(|column| {
+                let field = value
+                    .get(column)
+                    .cloned()
+                    .or_else(|| lookup(column).map(Value::String))
+                    .unwrap_or(Value::Null);
+                (column.clone(), field)
+            })
+            .collect(),
+    )
+}
+
 fn user_to_string(user: UserItem, print_type: PrintType) -> String {
     match print_type {
         PrintType::Csv => {
             let user_line = format!(
-                "{},{},{},{},{},{}",
+                "{},{},{},{},{},{},{},{}",
                 user.id,
                 user.first_name,
                 user.last_name,
                 user.user_name,
                 user.is_locked,
-                user.last_login_success_at.unwrap_or("N/A".into())
+                user.last_login_success_at
+                    .as_deref()
+                    .map(format_timestamp)
+                    .unwrap_or("N/A".into()),
+                user.email.unwrap_or_default(),
+                user.expire_at
+                    .as_deref()
+                    .map(format_timestamp)
+                    .unwrap_or_default()
             );
             user_line
         }
         PrintType::Pretty => {
             let user_line = format!(
-                "id: {} | first name: {} | last name: {} | user name: {} | is locked: {} | last login: {}",
-                user.id, user.first_name, user.last_name, user.user_name, user.is_locked, user.last_login_success_at.unwrap_or("N/A".into())
+                "id: {} | first name: {} | last name: {} | user name: {} | is locked: {} | last login: {} | email: {} | expires: {}",
+                user.id, or_dash(&user.first_name), or_dash(&user.last_name), or_dash(&user.user_name), user.is_locked, user.last_login_success_at.as_deref().map(format_timestamp).unwrap_or("-".into()), user.email.as_deref().map_or("-", or_dash), user.expire_at.as_deref().map(format_timestamp).unwrap_or("never".into())
             );
             user_line
         }
+        PrintType::Json => user_to_json(&user).to_string(),
+    }
+}
+
+// placeholder for absent fields in pretty output
+fn or_dash(value: &str) -> &str {
+    match value {
+        "" => "-",
+        value => value,
+    }
+}
+
+// fields required by the user model (dco3) – older DRACOON versions may omit them
+const REQUIRED_USER_FIELDS: [&str; 5] = [
+    "userName",
+    "firstName",
+    "lastName",
+    "isLocked",
+    "avatarUuid",
+];
+
+/// Parses a user list, filling absent (or null) fields required by the user model with defaults.
+fn parse_users_lenient(mut value: Value) -> Result<UserList, String> {
+    if let Some(items) = value.get_mut("items").and_then(Value::as_array_mut) {
+        for item in items.iter_mut().filter_map(Value::as_object_mut) {
+            for field in REQUIRED_USER_FIELDS {
+                if item.get(field).is_none_or(Value::is_null) {
+                    let default = match field {
+                        "isLocked" => Value::Bool(false),
+                        _ => Value::String(String::new()),
+                    };
+                    item.insert(field.to_string(), default);
+                }
+            }
+        }
+    }
+
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Returns a user field by its API (camelCase) name.
+fn user_field(user: &UserItem, field: &str) -> Option<String> {
+    match field {
+        "id" => Some(user.id.to_string()),
+        "firstName" => Some(user.first_name.clone()),
+        "lastName" => Some(user.last_name.clone()),
+        "userName" => Some(user.user_name.clone()),
+        "email" => user.email.clone(),
+        "phone" => user.phone.clone(),
+        "isLocked" => Some(user.is_locked.to_string()),
+        "expireAt" => user.expire_at.clone(),
+        "lastLoginSuccessAt" => user.last_login_success_at.clone(),
+        "isEncryptionEnabled" => user
+            .is_encryption_enabled
+            .map(|enabled| enabled.to_string()),
+        "hasManageableRooms" => user.has_manageable_rooms.map(|rooms| rooms.to_string()),
+        "homeRoomId" => user.home_room_id.map(|id| id.to_string()),
+        _ => None,
+    }
+}
+
+fn user_columns_to_string(user: &UserItem, columns: &[String], print_type: PrintType) -> String {
+    let values = columns
+        .iter()
+        .map(|column| display_field(column, user_field(user, column)));
+
+    match print_type {
+        PrintType::Json => {
+            json_columns(user_to_json(user), Some(&columns.to_vec()), |_| None).to_string()
+        }
+        PrintType::Csv => values.collect::<Vec<_>>().join(","),
+        PrintType::Pretty => columns
+            .iter()
+            .zip(values)
+            .map(|(column, value)| format!("{}: {}", column, value))
+            .collect::<Vec<_>>()
+            .join(" | "),
     }
 }
 
-fn customer_attribute_to_string(attrib: KeyValueEntry, print_type: PrintType) -> String {
+/// Returns a field value for pretty / CSV output (timestamps rendered via --tz / --date-format).
+fn display_field(field: &str, value: Option<String>) -> String {
+    match value {
+        Some(value) if TIMESTAMP_FIELDS.contains(&field) => format_timestamp(&value),
+        value => value.unwrap_or_default(),
+    }
+}
+
+/// Exits if any of the columns is not a known field.
+fn validate_columns(columns: Option<&Vec<String>>, fields: &[&str]) {
+    if let Some(unknown) = columns
+        .into_iter()
+        .flatten()
+        .find(|column| !fields.contains(&column.as_str()))
+    {
+        println!(
+            "{} Unknown column {} (available: {})",
+            "Error".white().on_red(),
+            unknown,
+            fields.join(", ")
+        );
+        exit(1)
+    }
+}
+
+fn customer_attribute_to_string(mut attrib: KeyValueEntry, print_type: PrintType) -> String {
+    attrib.value = redact(&attrib.key, &attrib.value);
+
     match print_type {
         PrintType::Csv => {
             let attrib_line = format!("{},{}", attrib.key, attrib.value);
@@ -183,19 +798,105 @@ fn customer_attribute_to_string(attrib: KeyValueEntry, print_type: PrintType) ->
             let cus_line = format!("key: {} | value: {}", attrib.key, attrib.value);
             cus_line
         }
+        PrintType::Json => json!({ "key": attrib.key, "value": attrib.value }).to_string(),
     }
 }
 
-pub async fn list_customers(
-    provider: Dracoon<Provisioning>,
-    filter: Option<String>,
-    sort: Option<String>,
-    offset: Option<u64>,
-    limit: Option<u64>,
-    print_type: Option<PrintType>,
-    all: bool,
-) {
-    let print_type = print_type.unwrap_or(PrintType::Pretty);
+/// Returns a customer field by its API (camelCase) name.
+fn customer_field(customer: &Customer, field: &str) -> Option<String> {
+    match field {
+        "id" => Some(customer.id.to_string()),
+        "companyName" => Some(customer.company_name.clone()),
+        "customerContractType" => Some(customer.customer_contract_type.clone()),
+        "quotaMax" => Some(customer.quota_max.to_string()),
+        "quotaUsed" => Some(customer.quota_used.to_string()),
+        "userMax" => Some(customer.user_max.to_string()),
+        "userUsed" => Some(customer.user_used.to_string()),
+        "createdAt" => Some(customer.created_at.clone()),
+        "updatedAt" => customer.updated_at.clone(),
+        "lastLoginAt" => customer.last_login_at.clone(),
+        "trialDaysLeft" => customer.trial_days_left.map(|days| days.to_string()),
+        "isLocked" => customer.is_locked.map(|locked| locked.to_string()),
+        "customerUuid" => customer.customer_uuid.clone(),
+        "cntInternalUser" => customer.cnt_internal_user.map(|cnt| cnt.to_string()),
+        "cntGuestUser" => customer.cnt_guest_user.map(|cnt| cnt.to_string()),
+        "status" => Some(customer_status(customer).to_string()),
+        attrib => attrib.strip_prefix(ATTRIBUTE_PREFIX).and_then(|key| {
+            customer
+                .customer_attributes
+                .as_ref()?
+                .items
+                .iter()
+                .find(|attrib| attrib.key == key)
+                .map(|attrib| attrib.value.clone())
+        }),
+    }
+}
+
+/// Returns a customer field for output – redacted (attributes by their key).
+fn redacted_customer_field(customer: &Customer, field: &str) -> Option<String> {
+    let value = customer_field(customer, field)?;
+
+    Some(redact(
+        field.strip_prefix(ATTRIBUTE_PREFIX).unwrap_or(field),
+        &value,
+    ))
+}
+
+fn customer_columns_to_string(
+    customer: &Customer,
+    columns: &[String],
+    print_type: PrintType,
+) -> String {
+    let values = columns
+        .iter()
+        .map(|column| display_field(column, redacted_customer_field(customer, column)));
+
+    match print_type {
+        PrintType::Json => json_columns(
+            customer_to_json(customer),
+            Some(&columns.to_vec()),
+            |column| redacted_customer_field(customer, column),
+        )
+        .to_string(),
+        PrintType::Csv => values.collect::<Vec<_>>().join(","),
+        PrintType::Pretty => columns
+            .iter()
+            .zip(values)
+            .map(|(column, value)| format!("{}: {}", column, value))
+            .collect::<Vec<_>>()
+            .join(" | "),
+    }
+}
+
+pub async fn list_customers(provider: Dracoon<Provisioning>, opts: ListOptions) {
+    let ListOptions {
+        filter,
+        sort,
+        offset,
+        limit,
+        print_type,
+        all,
+        no_limit,
+        changed_since_last_run,
+        columns,
+        only_over_limit,
+        top,
+        include_attributes: with_attributes,
+        concurrency,
+    } = opts;
+
+    validate_columns(columns.as_ref(), CUSTOMER_FIELDS);
+
+    // delta listing needs to see every customer to compute the next watermark
+    let all = all || changed_since_last_run;
+    let sort = paged_sort(sort, offset.is_some() || all, "id:asc");
+
+    if raw_output_active() {
+        let params = raw_params(&filter, &sort, offset, limit);
+        print_raw(&provider, "", &params).await;
+        return;
+    }
 
     let params = build_params(filter.clone(), sort.clone(), offset, limit);
 
@@ -203,46 +904,405 @@ pub async fn list_customers(
 
     if let Err(ref e) = customers {
         handle_dracoon_errors(e, Some("Could not list customers."));
-        std::process::exit(1)
+        exit(1)
     };
 
     let mut customers = customers.unwrap();
 
     match print_type {
-        PrintType::Csv => {
-            println!("{}", CUSTOMER_CSV_HEADER);
-        }
+        PrintType::Csv => match &columns {
+            Some(columns) => print_csv_header(&columns.join(",")),
+            None => print_csv_header(CUSTOMER_CSV_HEADER),
+        },
         PrintType::Pretty => {
             println!(
                 "total customers: {} | offset: {} | limit: {}",
                 customers.range.total, customers.range.offset, customers.range.limit
             );
         }
+        PrintType::Json => (),
     };
 
+    let mut complete = true;
+
+    let page_size = effective_page_size(&customers, limit);
+
+    if let (Some(limit), Some(page_size)) = (limit, page_size) {
+        // further pages are fetched for the top
+        if page_size < limit && top.is_none() {
+            eprintln!(
+                "{} Server returns max. {} items per page – limit {} is capped.",
+                "Warning".black().on_yellow(),
+                page_size,
+                limit
+            );
+        }
+    }
+
+    // top N beyond one page or among the over-limit customers requires all customers
+    let fetched = customers.items.len() as u64;
+    let all = all
+        || top.is_some_and(|top| {
+            (top > fetched || only_over_limit) && customers.range.total > fetched
+        });
+
+    if all && !no_limit {
+        let max_items = config::load_config()
+            .unwrap_or_default()
+            .list
+            .max_items
+            .unwrap_or(DEFAULT_MAX_ITEMS);
+
+        if customers.range.total > max_items {
+            println!(
+                "{} {} customers match – more than the max. {} fetched with --all. Narrow the filter, raise max_items in the [list] config or pass --no-limit.",
+                "Error".white().on_red(),
+                customers.range.total,
+                max_items
+            );
+            exit(1)
+        }
+    }
+
     if all {
-        for offset in 500..=customers.range.total {
-            let params = build_params(filter.clone(), sort.clone(), Some(offset), limit);
+        install_cancel_handler();
+        complete =
+            fetch_remaining_customers(&provider, &mut customers, filter, sort.clone(), page_size)
+                .await;
+        customers.items = dedupe_and_sort_customers(customers.items, sort.as_deref());
+
+        if !complete {
+            eprintln!(
+                "{} Interrupted – partial results ({} of {} customers).",
+                "Warning".black().on_yellow(),
+                customers.items.len(),
+                customers.range.total
+            );
+        }
+    }
 
-            let next_customers = provider.get_customers(Some(params)).await;
+    map_customers(&provider, &customers.items);
 
-            if let Err(ref e) = next_customers {
-                handle_dracoon_errors(e, Some("Could not list customers."));
-                std::process::exit(1)
-            };
+    // client-side filter – only applies to the fetched page(s)
+    if only_over_limit {
+        customers
+            .items
+            .retain(|customer| customer_status(customer) != "ok");
+    }
+
+    if let Some(top) = top {
+        customers.items.truncate(top as usize);
+    }
+
+    if changed_since_last_run {
+        customers.items = filter_changed_since_last_run(&provider, customers.items, complete);
+
+        if let PrintType::Pretty = print_type {
+            println!(
+                "changed customers since last run: {}",
+                customers.items.len()
+            );
+        }
+    }
 
-            let next_customers = next_customers.unwrap();
+    let with_attributes = with_attributes
+        || columns
+            .iter()
+            .flatten()
+            .any(|column| column.starts_with(ATTRIBUTE_PREFIX));
 
-            customers.items.extend(next_customers.items);
+    if with_attributes {
+        if !all {
+            install_cancel_handler();
+        }
+        match include_attributes(&provider, &mut customers.items, concurrency).await {
+            Ok(true) => (),
+            Ok(false) => eprintln!(
+                "{} Interrupted – attributes missing for some customers.",
+                "Warning".black().on_yellow()
+            ),
+            Err(ref e) => {
+                handle_dracoon_errors(e, Some("Could not get customer attributes."));
+                exit(1)
+            }
         }
     }
 
+    if let PrintType::Json = print_type {
+        let items: Vec<_> = customers
+            .items
+            .iter()
+            .map(|customer| {
+                json_columns(customer_to_json(customer), columns.as_ref(), |column| {
+                    redacted_customer_field(customer, column)
+                })
+            })
+            .collect();
+        print_json(json!({ "range": range_to_json(&customers.range), "items": items }));
+        return;
+    }
+
     for customer in customers.items {
-        let cus_line = customer_to_string(customer, print_type);
+        let cus_line = match &columns {
+            Some(columns) => customer_columns_to_string(&customer, columns, print_type),
+            None => customer_to_string(customer, print_type),
+        };
         println!("{}", cus_line);
     }
 }
 
+fn range_to_json(range: &dco3::Range) -> Value {
+    json!({ "offset": range.offset, "limit": range.limit, "total": range.total })
+}
+
+/// Returns the page size to request for further pages: some deployments cap the page size
+/// below the requested limit (or default of 500) – a full page with less items reveals the cap.
+fn effective_page_size(page: &CustomerList, requested: Option<u64>) -> Option<u64> {
+    let received = page.items.len() as u64;
+    let is_last_page = page.range.offset + received >= page.range.total;
+
+    match requested {
+        _ if received == 0 || is_last_page => requested,
+        Some(requested) => Some(requested.min(received)),
+        None => Some(received),
+    }
+}
+
+/// Fetches all pages following the already fetched (first) page of customers.
+/// Returns false if fetching was interrupted (partial results).
+async fn fetch_remaining_customers(
+    provider: &Dracoon<Provisioning>,
+    customers: &mut CustomerList,
+    filter: Option<String>,
+    sort: Option<String>,
+    limit: Option<u64>,
+) -> bool {
+    let mut next_offset = customers.range.offset + customers.items.len() as u64;
+
+    while next_offset < customers.range.total {
+        if is_cancelled() {
+            return false;
+        }
+
+        let params = build_params(filter.clone(), sort.clone(), Some(next_offset), limit);
+
+        let next_customers = provider.get_customers(Some(params)).await;
+
+        if let Err(ref e) = next_customers {
+            handle_dracoon_errors(e, Some("Could not list customers."));
+            exit(1)
+        };
+
+        let next_customers = next_customers.unwrap();
+
+        // an empty page means the total shrunk mid-run – stop instead of looping forever
+        if next_customers.items.is_empty() {
+            break;
+        }
+
+        next_offset += next_customers.items.len() as u64;
+        customers.items.extend(next_customers.items);
+    }
+
+    true
+}
+
+/// Fetches all customers (all pages), deduplicated and sorted.
+async fn fetch_all_customers(
+    provider: &Dracoon<Provisioning>,
+    filter: Option<String>,
+    sort: Option<String>,
+) -> Vec<Customer> {
+    let sort = paged_sort(sort, true, "id:asc");
+    let params = build_params(filter.clone(), sort.clone(), None, None);
+
+    let customers = provider.get_customers(Some(params)).await;
+
+    if let Err(ref e) = customers {
+        handle_dracoon_errors(e, Some("Could not list customers."));
+        exit(1)
+    };
+
+    let mut customers = customers.unwrap();
+    let page_size = effective_page_size(&customers, None);
+
+    fetch_remaining_customers(provider, &mut customers, filter, sort.clone(), page_size).await;
+
+    // callers check is_cancelled() before acting on the (partial) list
+
+    dedupe_and_sort_customers(customers.items, sort.as_deref())
+}
+
+/// Returns the customer with the exact company name (if any).
+async fn find_customer_by_name(
+    provider: &Dracoon<Provisioning>,
+    company_name: &str,
+) -> Result<Option<Customer>, DracoonClientError> {
+    let params = build_params(
+        Some(format!("companyName:cn:{}", company_name)),
+        None,
+        None,
+        None,
+    );
+
+    let customers = provider.get_customers(Some(params)).await?;
+
+    Ok(customers
+        .items
+        .into_iter()
+        .find(|customer| customer.company_name == company_name))
+}
+
+/// Warns if customers other than the given one already use the company name (case-insensitive).
+pub async fn warn_duplicate_company_name(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+    company_name: &str,
+) {
+    let params = build_params(
+        Some(format!("companyName:cn:{}", company_name)),
+        None,
+        None,
+        None,
+    );
+
+    let customers = match provider.get_customers(Some(params)).await {
+        Ok(customers) => customers,
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not check company name."));
+            exit(1)
+        }
+    };
+
+    let duplicates: Vec<_> = customers
+        .items
+        .iter()
+        .filter(|customer| customer.id != id)
+        .filter(|customer| {
+            customer
+                .company_name
+                .trim()
+                .eq_ignore_ascii_case(company_name.trim())
+        })
+        .map(|customer| customer.id.to_string())
+        .collect();
+
+    if !duplicates.is_empty() {
+        eprintln!(
+            "{} Company name {} is already used by customer(s) {}",
+            "Warning".black().on_yellow(),
+            company_name,
+            duplicates.join(", ")
+        );
+    }
+}
+
+fn customer_changed_at(customer: &Customer) -> Option<DateTime<FixedOffset>> {
+    let changed_at = customer
+        .updated_at
+        .as_deref()
+        .unwrap_or(&customer.created_at);
+
+    DateTime::parse_from_rfc3339(changed_at).ok()
+}
+
+/// Keeps only customers created or updated after the stored watermark for this DRACOON url
+/// and advances the watermark to the most recent change seen.
+fn filter_changed_since_last_run(
+    provider: &Dracoon<Provisioning>,
+    customers: Vec<Customer>,
+    complete: bool,
+) -> Vec<Customer> {
+    let url = provider.build_api_url("/").to_string();
+
+    let watermark = match state::get_watermark(&url) {
+        Ok(watermark) => watermark,
+        Err(ref e) => handle_errors(e),
+    };
+
+    let latest = customers.iter().filter_map(customer_changed_at).max();
+
+    let customers = customers
+        .into_iter()
+        .filter(
+            |customer| match (watermark, customer_changed_at(customer)) {
+                (Some(watermark), Some(changed_at)) => changed_at > watermark,
+                _ => true,
+            },
+        )
+        .collect();
+
+    // partial results must not advance the watermark – the next run would miss changes
+    if let Some(latest) = latest.filter(|_| complete) {
+        if let Err(ref e) = state::set_watermark(&url, latest.max(watermark.unwrap_or(latest))) {
+            handle_errors(e)
+        }
+    }
+
+    customers
+}
+
+/// Removes duplicate customers (by id) from aggregated pages and applies a stable client-side sort.
+/// The sort string follows the API syntax (e.g. `companyName:asc|createdAt:desc`), unknown fields are ignored.
+fn dedupe_and_sort_customers(customers: Vec<Customer>, sort: Option<&str>) -> Vec<Customer> {
+    let mut seen = HashSet::new();
+    let mut customers: Vec<Customer> = customers
+        .into_iter()
+        .filter(|customer| seen.insert(customer.id))
+        .collect();
+
+    let Some(sort) = sort else {
+        return customers;
+    };
+
+    let sort_keys: Vec<(&str, bool)> = sort
+        .split('|')
+        .filter_map(|part| {
+            let mut split = part.split(':');
+            let field = split.next()?.trim();
+            let desc = split
+                .next()
+                .map(|order| order.trim().eq_ignore_ascii_case("desc"))
+                .unwrap_or(false);
+            Some((field, desc))
+        })
+        .collect();
+
+    customers.sort_by(|a, b| {
+        sort_keys
+            .iter()
+            .map(|(field, desc)| {
+                let ordering = compare_customers_by_field(a, b, field);
+                if *desc {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    customers
+}
+
+fn compare_customers_by_field(a: &Customer, b: &Customer, field: &str) -> Ordering {
+    match field {
+        "id" => a.id.cmp(&b.id),
+        "companyName" => a.company_name.cmp(&b.company_name),
+        "customerContractType" => a.customer_contract_type.cmp(&b.customer_contract_type),
+        "quotaMax" => a.quota_max.cmp(&b.quota_max),
+        "quotaUsed" => a.quota_used.cmp(&b.quota_used),
+        "userMax" => a.user_max.cmp(&b.user_max),
+        "userUsed" => a.user_used.cmp(&b.user_used),
+        "createdAt" => a.created_at.cmp(&b.created_at),
+        "updatedAt" => a.updated_at.cmp(&b.updated_at),
+        "lastLoginAt" => a.last_login_at.cmp(&b.last_login_at),
+        "trialDaysLeft" => a.trial_days_left.cmp(&b.trial_days_left),
+        _ => Ordering::Equal,
+    }
+}
+
 pub async fn get_customer(
     provider: Dracoon<Provisioning>,
     id: u64,
@@ -250,77 +1310,241 @@ pub async fn get_customer(
 ) -> () {
     let print_type = print_type.unwrap_or(PrintType::Pretty);
 
-    let customer = provider.get_customer(id, None).await;
+    if raw_output_active() {
+        print_raw(&provider, &id.to_string(), &[]).await;
+        return;
+    }
+
+    // attributes carry the lifecycle state
+    let customer = provider.get_customer(id, Some(true)).await;
+
+    if let Err(ref e) = customer {
+        handle_dracoon_errors(e, Some("Could not get customer info."));
+        exit(1)
+    };
+
+    let customer = customer.unwrap();
+
+    if let PrintType::Json = print_type {
+        print_json(customer_to_json(&customer));
+        return;
+    }
+
+    let cus_line = customer_to_string(customer, print_type);
+    match print_type {
+        // single line without header
+        PrintType::Csv => print_csv_header(&cus_line),
+        _ => println!("{}", cus_line),
+    }
+}
+
+async fn create_update_request(
+    provider: &Dracoon<Provisioning>,
+    update_type: UpdateType,
+) -> UpdateCustomerRequest {
+    match update_type {
+        UpdateType::CompanyName(name) => UpdateCustomerRequest::builder()
+            .with_company_name(name)
+            .build(),
+        UpdateType::QuotaMax(quota_max) => UpdateCustomerRequest::builder()
+            .with_quota_max(quota_max)
+            .build(),
+        UpdateType::UserMax(user_max) => UpdateCustomerRequest::builder()
+            .with_user_max(user_max)
+            .build(),
+        UpdateType::UserMaxWithQuotaPerUser(user_max, quota_per_user) => {
+            UpdateCustomerRequest::builder()
+                .with_user_max(user_max)
+                .with_quota_max(quota_for_users(quota_per_user, user_max))
+                .build()
+        }
+        UpdateType::ContractType(contract_type) => UpdateCustomerRequest::builder()
+            .with_customer_contract_type(contract_type.as_str())
+            .build(),
+        UpdateType::Patch(patch) => compat_update_patch(provider, patch).await.into(),
+        UpdateType::QuotaPerUser(_) => unreachable!("resolved with the current user max"),
+    }
+}
+
+/// Returns the quota max for a quota per user (quota per user × user max).
+pub fn quota_for_users(quota_per_user: u64, user_max: u64) -> u64 {
+    let Some(quota_max) = quota_per_user.checked_mul(user_max) else {
+        println!(
+            "{} Quota per user {} × {} users exceeds the max. quota.",
+            "Error".white().on_red(),
+            quota_per_user,
+            user_max
+        );
+        exit(1)
+    };
+
+    println!(
+        "quota max: {} bytes ({} bytes per user × {} users)",
+        quota_max, quota_per_user, user_max
+    );
+
+    quota_max
+}
+
+/// Returns the changed fields (API names) of an update as field, value before, value after.
+fn update_changes(before: &Customer, after: &UpdateCustomerResponse) -> Vec<FieldChange> {
+    [
+        (
+            "companyName",
+            json!(before.company_name),
+            json!(after.company_name),
+        ),
+        (
+            "customerContractType",
+            json!(before.customer_contract_type),
+            json!(after.customer_contract_type),
+        ),
+        ("quotaMax", json!(before.quota_max), json!(after.quota_max)),
+        ("userMax", json!(before.user_max), json!(after.user_max)),
+        ("isLocked", json!(before.is_locked), json!(after.is_locked)),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| before != after)
+    .map(|(field, before, after)| FieldChange {
+        field,
+        before,
+        after,
+    })
+    .collect()
+}
 
-    if let Err(ref e) = customer {
-        handle_dracoon_errors(e, Some("Could not get customer info."));
-        std::process::exit(1)
+/// Updates a customer and returns the updated customer with the changed fields (without printing).
+/// Quota per user is resolved from the current user max – fails if the customer can't be fetched.
+pub async fn try_update_customer(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+    update_type: UpdateType,
+) -> Result<UpdateOutcome, DracoonClientError> {
+    // the current customer is needed for the diff (and quota per user)
+    let before = match provider.get_customer(id, None).await {
+        Ok(customer) => Ok(customer),
+        Err(e) if matches!(update_type, UpdateType::QuotaPerUser(_)) => return Err(e),
+        Err(e) => Err(dracoon_error_to_string(e)),
     };
 
-    let customer = customer.unwrap();
+    // quota per user depends on the current user max
+    let update_type = match (update_type, &before) {
+        (UpdateType::QuotaPerUser(quota_per_user), Ok(customer)) => {
+            UpdateType::QuotaMax(quota_for_users(quota_per_user, customer.user_max))
+        }
+        (update_type, _) => update_type,
+    };
 
-    let cus_line = customer_to_string(customer, print_type);
-    println!("{}", cus_line);
+    let customer = provider
+        .update_customer(id, create_update_request(provider, update_type).await)
+        .await?;
+    map_updated_customer(provider, &customer);
+    let changes = before.map(|before| update_changes(&before, &customer));
+    let outcome = UpdateOutcome::new(customer, changes);
+    run_hook(HookEvent::Update, outcome.hook_values()).await;
+
+    Ok(outcome)
 }
 
-fn create_update_request(update_type: UpdateType) -> UpdateCustomerRequest {
-    match update_type {
-        UpdateType::CompanyName(name) => UpdateCustomerRequest::builder()
-            .with_company_name(name)
-            .build(),
-        UpdateType::QuotaMax(quota_max) => UpdateCustomerRequest::builder()
-            .with_quota_max(quota_max)
-            .build(),
-        UpdateType::UserMax(user_max) => UpdateCustomerRequest::builder()
-            .with_user_max(user_max)
-            .build(),
+pub async fn update_customer(
+    provider: Dracoon<Provisioning>,
+    id: u64,
+    update_type: UpdateType,
+    json: bool,
+) {
+    if dry_run_active() {
+        // quota per user depends on the current user max
+        let update_type = match update_type {
+            UpdateType::QuotaPerUser(quota_per_user) => match provider.get_customer(id, None).await
+            {
+                Ok(customer) => {
+                    UpdateType::QuotaMax(quota_for_users(quota_per_user, customer.user_max))
+                }
+                Err(ref e) => {
+                    handle_dracoon_errors(e, Some("Could not get customer."));
+                    exit(1)
+                }
+            },
+            update_type => update_type,
+        };
+        let payload = json!(create_update_request(&provider, update_type).await);
+        print_dry_run(&provider, "PUT", &id.to_string(), Some(payload));
+        return;
     }
+
+    let outcome = match try_update_customer(&provider, id, update_type).await {
+        Ok(outcome) => outcome,
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not update customer."));
+            exit(1)
+        }
+    };
+
+    print_update_outcome(&outcome, json || json_query_active());
 }
 
-pub async fn update_customer(provider: Dracoon<Provisioning>, id: u64, update_type: UpdateType) {
-    let update_customer = create_update_request(update_type);
+/// Deletes a customer and removes it from the id mapping (without printing).
+pub async fn try_delete_customer(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+) -> Result<DeletedCustomer, DracoonClientError> {
+    provider.delete_customer(id).await?;
+    unmap_deleted_customer(provider, id);
+    let deleted = DeletedCustomer { id };
+    run_hook(HookEvent::Delete, deleted.hook_values()).await;
 
-    let customer = provider.update_customer(id.into(), update_customer).await;
+    Ok(deleted)
+}
 
-    if let Err(ref e) = customer {
-        handle_dracoon_errors(e, Some("Could not update customer."));
-        std::process::exit(1)
+/// Asks to type the company name of the customer (fetched live) to confirm the deletion.
+async fn confirm_delete(provider: &Dracoon<Provisioning>, id: u64) {
+    let customer = match provider.get_customer(id, None).await {
+        Ok(customer) => customer,
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not get customer."));
+            exit(1)
+        }
     };
 
-    let customer = customer.unwrap();
-
     println!(
-        "{}{}{}",
-        "Success ".green(),
-        "Updated customer with id ",
-        id
-    );
-
-    let cus_line = format!(
-        "company: {} | contract: {} | users max: {} | quota max: {} | id: {}",
-        customer.company_name,
-        customer.customer_contract_type,
-        customer.user_max,
-        customer.quota_max,
-        customer.id
+        "{} This deletes customer {} (id: {}) with {} user(s) and all data.",
+        "Warning".black().on_yellow(),
+        customer.company_name.bold(),
+        customer.id,
+        customer.user_used
     );
-    println!("{}", cus_line);
+    let confirmation = dialoguer::Input::<String>::new()
+        .with_prompt("Type the company name to confirm")
+        .allow_empty(true)
+        .interact_text();
+
+    if confirmation.ok().as_deref().map(str::trim) != Some(customer.company_name.trim()) {
+        println!(
+            "{} Company name does not match – aborted.",
+            "Error".white().on_red()
+        );
+        exit(1)
+    }
 }
 
-pub async fn delete_customer(provider: Dracoon<Provisioning>, id: u64) {
-    match provider.delete_customer(id.into()).await {
-        Ok(_) => {
-            println!(
-                "{}{}{}",
-                "Success ".green(),
-                "Deleted customer with id ",
-                id
-            );
-            std::process::exit(0)
+pub async fn delete_customer(provider: Dracoon<Provisioning>, id: u64, yes: bool) {
+    if dry_run_active() {
+        print_dry_run(&provider, "DELETE", &id.to_string(), None);
+        return;
+    }
+
+    if !yes {
+        confirm_delete(&provider, id).await;
+    }
+
+    match try_delete_customer(&provider, id).await {
+        Ok(deleted) => {
+            print_deleted_customer(&deleted);
+            exit(0)
         }
         Err(ref e) => {
             handle_dracoon_errors(e, Some("Could not delete customer."));
-            std::process::exit(1);
+            exit(1);
         }
     };
 }
@@ -333,121 +1557,235 @@ pub fn parse_customer_json_from_file(path: &str) -> Result<NewCustomerRequestDco
         Ok(res) => res,
         Err(e) => {
             println!(
-                "{} {}{}",
+                "{} Could not open file from path {}",
                 "Error".white().on_red(),
-                "Could not open file from path ",
                 path
             );
             println!("{:?}", e);
-            std::process::exit(1)
+            exit(1)
         }
     };
 
-    let new_customer = match serde_json::from_str::<NewCustomerRequest>(&raw_json) {
-        Ok(customer) => customer,
+    parse_customer_json(&raw_json, &format!("file {}", path))
+}
+
+/// Parses a partial customer update (JSON patch file) – exits if the file is invalid or empty.
+pub fn parse_update_json_from_file(path: &str) -> UpdateCustomerPatch {
+    let raw_json = match fs::read_to_string(path) {
+        Ok(res) => res,
         Err(e) => {
             println!(
-                "{} {}{}",
+                "{} Could not open file from path {}",
                 "Error".white().on_red(),
-                "Could not parse customer from file ",
                 path
             );
             println!("{:?}", e);
-            std::process::exit(1)
+            exit(1)
         }
     };
 
-    Ok(new_customer.into())
+    let patch = match serde_json::from_str::<UpdateCustomerPatch>(&raw_json) {
+        Ok(patch) => patch,
+        Err(e) => {
+            println!(
+                "{} Could not parse update from file {}",
+                "Error".white().on_red(),
+                path
+            );
+            println!("{:?}", e);
+            exit(1)
+        }
+    };
+
+    if patch.is_empty() {
+        println!(
+            "{} No fields to update in file {}",
+            "Error".white().on_red(),
+            path
+        );
+        exit(1)
+    }
+
+    patch
 }
 
-/// This function prompts for required fields via stdout and returns a request struct to create a new customer.
-pub fn prompt_new_customer() -> Result<NewCustomerRequestDco3, DcProvError> {
-    // first admin user
-    println!("{}", "Step 1: Enter first admin user".white().on_blue());
+/// Parses a customer from an inline JSON payload (`-` reads the payload from stdin, e.g. a heredoc).
+pub fn parse_customer_json_from_arg(payload: &str) -> Result<NewCustomerRequestDco3, DcProvError> {
+    if payload != "-" {
+        return parse_customer_json(payload, "--json");
+    }
 
-    let first_name: String = dialoguer::Input::new()
-        .with_prompt("Please enter first name: ")
-        .interact()
+    let mut raw_json = String::new();
+    std::io::stdin()
+        .read_to_string(&mut raw_json)
         .or(Err(DcProvError::Io))?;
 
-    let last_name: String = dialoguer::Input::new()
-        .with_prompt("Please enter last name: ")
-        .interact()
-        .or(Err(DcProvError::Io))?;
+    parse_customer_json(&raw_json, "stdin")
+}
 
-    let email: String = dialoguer::Input::new()
-        .with_prompt("Please enter email address: ")
-        .interact()
-        .or(Err(DcProvError::Io))?;
+fn parse_customer_json(
+    raw_json: &str,
+    source: &str,
+) -> Result<NewCustomerRequestDco3, DcProvError> {
+    let new_customer = match serde_json::from_str::<NewCustomerRequest>(raw_json) {
+        Ok(customer) => customer,
+        Err(e) => {
+            println!(
+                "{} Could not parse customer from {}",
+                "Error".white().on_red(),
+                source
+            );
+            println!("{:?}", e);
+            exit(1)
+        }
+    };
 
-    let user_name: Option<String> = if dialoguer::Confirm::new()
-        .with_prompt("Provide username?")
-        .interact()
-        .or(Err(DcProvError::Io))?
-    {
-        Some(
-            dialoguer::Input::new()
-                .with_prompt("Please enter username: ")
-                .interact()
-                .or(Err(DcProvError::Io))?,
-        )
-    } else {
-        None
+    Ok(new_customer.into())
+}
+
+// set while the answers of the create prompt are persisted (removed once the customer is created)
+static PROMPT_DRAFT_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Returns the answers of an interrupted create prompt if the user wants to resume them.
+fn resume_prompt_draft() -> Result<PromptDraft, DcProvError> {
+    let Ok(Some(draft)) = state::get_prompt_draft() else {
+        return Ok(PromptDraft::default());
     };
 
-    // customer
-    println!("{}", "Step 2: Configure customer".white().on_blue());
+    println!(
+        "Unfinished answers from an interrupted prompt: {} {} <{}> | company: {}",
+        or_dash(draft.first_name.as_deref().unwrap_or_default()),
+        or_dash(draft.last_name.as_deref().unwrap_or_default()),
+        or_dash(draft.email.as_deref().unwrap_or_default()),
+        or_dash(draft.company_name.as_deref().unwrap_or_default())
+    );
 
-    let company_name: String = dialoguer::Input::new()
-        .with_prompt("Please enter company name: ")
+    let resume = dialoguer::Confirm::new()
+        .with_prompt("Resume with these answers?")
+        .default(true)
         .interact()
         .or(Err(DcProvError::Io))?;
 
-    let quota_max = loop {
-        let quota_max: String = dialoguer::Input::new()
-            .with_prompt("Please enter maxium quota (in bytes): ")
+    if resume {
+        return Ok(draft);
+    }
+
+    let _ = state::remove_prompt_draft();
+    Ok(PromptDraft::default())
+}
+
+/// Returns the resumed answer or prompts for it – new answers are persisted right away.
+fn draft_answer<T: Clone>(
+    draft: &mut PromptDraft,
+    answer: fn(&mut PromptDraft) -> &mut Option<T>,
+    prompt: impl FnOnce() -> Result<T, DcProvError>,
+) -> Result<T, DcProvError> {
+    if let Some(value) = answer(draft).clone() {
+        return Ok(value);
+    }
+
+    let value = prompt()?;
+    *answer(draft) = Some(value.clone());
+
+    // losing the draft only costs the resume
+    let _ = state::set_prompt_draft(draft);
+
+    Ok(value)
+}
+
+/// Prompts for a positive number until a valid one is entered.
+fn prompt_positive_number(prompt: &str) -> Result<u64, DcProvError> {
+    loop {
+        let input: String = dialoguer::Input::new()
+            .with_prompt(prompt)
             .interact()
             .or(Err(DcProvError::Io))?;
 
-        match quota_max.trim().parse::<u64>() {
-            Ok(num) => {
-                if num > 0 {
-                    break num;
-                }
-            }
-            Err(_) => {
-                println!(
-                    "{} {}",
-                    "Error".white().on_red(),
-                    "Please enter a valid positive number."
-                );
-            }
-        };
+        match input.trim().parse::<u64>() {
+            Ok(num) if num > 0 => return Ok(num),
+            _ => println!(
+                "{} Please enter a valid positive number.",
+                "Error".white().on_red()
+            ),
+        }
+    }
+}
+
+/// This function prompts for required fields via stdout and returns a request struct to create a new customer.
+/// Answers are persisted after each step and offered for resume if the prompt is interrupted (e.g. Ctrl-C).
+pub fn prompt_new_customer() -> Result<NewCustomerRequestDco3, DcProvError> {
+    let mut draft = resume_prompt_draft()?;
+    PROMPT_DRAFT_ACTIVE.store(true, AtomicOrdering::SeqCst);
+
+    let input = |prompt: &'static str| {
+        move || {
+            dialoguer::Input::<String>::new()
+                .with_prompt(prompt)
+                .interact()
+                .or(Err(DcProvError::Io))
+        }
     };
 
-    let user_max = loop {
-        let user_max: String = dialoguer::Input::new()
-            .with_prompt("Please enter maxium users: ")
-            .interact()
-            .or(Err(DcProvError::Io))?;
+    // first admin user
+    println!("{}", "Step 1: Enter first admin user".white().on_blue());
 
-        match user_max.trim().parse::<u64>() {
-            Ok(num) => {
-                if num > 0 {
-                    break num;
-                }
-            }
-            Err(_) => {
-                println!(
-                    "{} {}",
-                    "Error".white().on_red(),
-                    "Please enter a valid positive number."
-                );
-            }
-        };
+    let first_name = draft_answer(
+        &mut draft,
+        |draft| &mut draft.first_name,
+        input("Please enter first name: "),
+    )?;
+    let last_name = draft_answer(
+        &mut draft,
+        |draft| &mut draft.last_name,
+        input("Please enter last name: "),
+    )?;
+    let email = draft_answer(
+        &mut draft,
+        |draft| &mut draft.email,
+        input("Please enter email address: "),
+    )?;
+
+    // optional answers – empty skips them
+    let optional_input = |prompt: &'static str| {
+        move || {
+            dialoguer::Input::<String>::new()
+                .with_prompt(prompt)
+                .allow_empty(true)
+                .interact()
+                .map(|answer| answer.trim().to_string())
+                .or(Err(DcProvError::Io))
+        }
     };
 
-    let user_name = user_name.unwrap_or(email.clone());
+    let user_name = draft_answer(
+        &mut draft,
+        |draft| &mut draft.user_name,
+        optional_input("Please enter username (empty: email address): "),
+    )?;
+    let phone = draft_answer(
+        &mut draft,
+        |draft| &mut draft.phone,
+        optional_input("Please enter phone number (optional): "),
+    )?;
+
+    // customer
+    println!("{}", "Step 2: Configure customer".white().on_blue());
+
+    let company_name = draft_answer(
+        &mut draft,
+        |draft| &mut draft.company_name,
+        input("Please enter company name: "),
+    )?;
+    let quota_max = draft_answer(
+        &mut draft,
+        |draft| &mut draft.quota_max,
+        || prompt_positive_number("Please enter maxium quota (in bytes): "),
+    )?;
+    let user_max = draft_answer(
+        &mut draft,
+        |draft| &mut draft.user_max,
+        || prompt_positive_number("Please enter maxium users: "),
+    )?;
 
     // TODO: remove manual build once dco3 fixes bug with must_change_password
     let auth_data = UserAuthData::builder(AuthMethod::Basic)
@@ -457,39 +1795,130 @@ pub fn prompt_new_customer() -> Result<NewCustomerRequestDco3, DcProvError> {
     let first_admin_user = FirstAdminUser {
         first_name,
         last_name,
-        user_name: Some(user_name),
+        user_name: Some(user_name).filter(|user_name| !user_name.is_empty()),
         email: Some(email),
         auth_data: Some(auth_data),
         notify_user: Some(true),
         receiver_language: None,
-        phone: None,
+        phone: Some(phone).filter(|phone| !phone.is_empty()),
     };
 
-    Ok(
-        NewCustomerRequestDco3::builder("pay", quota_max, user_max, first_admin_user)
-            .with_company_name(company_name)
-            .build(),
+    Ok(NewCustomerRequestDco3::builder(
+        ContractType::default().as_str(),
+        quota_max,
+        user_max,
+        first_admin_user,
     )
+    .with_company_name(company_name)
+    .build())
+}
+
+/// Creates a customer (and maps its providerCustomerId) – returns the created customer without printing.
+pub async fn try_create_customer(
+    provider: &Dracoon<Provisioning>,
+    new_customer: NewCustomerRequestDco3,
+) -> Result<CreatedCustomer, DracoonClientError> {
+    let customer: CreatedCustomer = provider
+        .create_customer(compat_new_customer(provider, new_customer).await)
+        .await?
+        .into();
+    map_created_customer(provider, &customer);
+    run_hook(HookEvent::Create, customer.hook_values()).await;
+
+    Ok(customer)
 }
 
 pub async fn create_customer(
     provider: Dracoon<Provisioning>,
     new_customer: NewCustomerRequestDco3,
+    verify_timeout: Option<std::time::Duration>,
 ) -> () {
-    let customer = provider.create_customer(new_customer).await;
+    if dry_run_active() {
+        let payload = json!(compat_new_customer(&provider, new_customer).await);
+        print_dry_run(&provider, "POST", "", Some(payload));
+        return;
+    }
 
-    if let Err(ref e) = customer {
-        handle_dracoon_errors(e, Some(" customer info."));
-        std::process::exit(1)
+    let customer = match try_create_customer(&provider, new_customer).await {
+        Ok(customer) => customer,
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some(" customer info."));
+            exit(1)
+        }
     };
 
-    let customer = customer.unwrap();
+    print_created_customer(&customer);
 
-    println!("{}{}", "Success ".green(), "Customer creeated.");
-    println!(
-        "Company name: {} | user max: {} | quota max: {} | id: {}",
-        customer.company_name, customer.user_max, customer.quota_max, customer.id
-    );
+    if PROMPT_DRAFT_ACTIVE.load(AtomicOrdering::SeqCst) {
+        let _ = state::remove_prompt_draft();
+    }
+
+    if let Some(timeout) = verify_timeout {
+        verify_first_admin(&provider, customer.id, &customer.first_admin_user, timeout).await;
+    }
+}
+
+fn is_first_admin(user: &UserItem, first_admin: &FirstAdminUser) -> bool {
+    let eq = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+
+    match (&first_admin.user_name, &first_admin.email) {
+        (Some(user_name), _) => eq(&user.user_name, user_name),
+        (None, Some(email)) => {
+            eq(&user.user_name, email) || user.email.as_deref().is_some_and(|e| eq(e, email))
+        }
+        (None, None) => {
+            eq(&user.first_name, &first_admin.first_name)
+                && eq(&user.last_name, &first_admin.last_name)
+        }
+    }
+}
+
+/// Polls the user list of a new customer until the first admin user appears – exits with 1 on timeout.
+async fn verify_first_admin(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+    first_admin: &FirstAdminUser,
+    timeout: std::time::Duration,
+) {
+    let started = std::time::Instant::now();
+    let mut last_error = None;
+
+    loop {
+        match provider.get_customer_users(id, None).await {
+            Ok(users) => {
+                if let Some(user) = users
+                    .items
+                    .iter()
+                    .find(|user| is_first_admin(user, first_admin))
+                {
+                    println!(
+                        "{}First admin user {} created with id {}",
+                        "Success ".green(),
+                        user.user_name,
+                        user.id
+                    );
+                    return;
+                }
+            }
+            // the customer might not be fully provisioned yet – keep polling until the timeout
+            Err(e) => last_error = Some(dracoon_error_to_string(e)),
+        }
+
+        if started.elapsed() >= timeout {
+            println!(
+                "{} First admin user of customer {} did not appear within {}s – check notification / AD settings.",
+                "Error".white().on_red(),
+                id,
+                timeout.as_secs()
+            );
+            if let Some(e) = last_error {
+                println!("Last error: {}", e);
+            }
+            exit(1)
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(VERIFY_POLL_SECS)).await;
+    }
 }
 
 pub async fn get_customer_attributes(
@@ -502,30 +1931,44 @@ pub async fn get_customer_attributes(
     print_type: Option<PrintType>,
 ) {
     let print_type = print_type.unwrap_or(PrintType::Pretty);
+    let sort = paged_sort(sort, offset.is_some(), "key:asc");
+
+    if raw_output_active() {
+        let params = raw_params(&filter, &sort, offset, limit);
+        print_raw(&provider, &format!("{}/customerAttributes", id), &params).await;
+        return;
+    }
 
     let params = build_params(filter, sort, offset, limit);
 
-    let attribs = provider
-        .get_customer_attributes(id.into(), Some(params))
-        .await;
+    let attribs = provider.get_customer_attributes(id, Some(params)).await;
 
     if let Err(ref e) = attribs {
         handle_dracoon_errors(e, Some("Could not get customer attributes."));
-        std::process::exit(1)
+        exit(1)
     };
 
     let attribs = attribs.unwrap();
 
     match print_type {
+        PrintType::Json => {
+            let items: Vec<_> = attribs
+                .items
+                .iter()
+                .map(|attrib| json!({ "key": attrib.key, "value": attrib.value }))
+                .collect();
+            print_json(json!({ "range": range_to_json(&attribs.range), "items": items }));
+            return;
+        }
         PrintType::Csv => {
-            println!("{}", CUSTOMER_ATTRIBUTES_CSV_HEADER);
+            print_csv_header(CUSTOMER_ATTRIBUTES_CSV_HEADER);
         }
         PrintType::Pretty => {
             println!("Customer attributes for customer with id: {}", id);
         }
     };
 
-    if attribs.items.len() == 0 {
+    if attribs.items.is_empty() {
         println!("Customer has no customer attributes.")
     }
 
@@ -535,6 +1978,25 @@ pub async fn get_customer_attributes(
     }
 }
 
+/// Sets customer attributes (without printing) – the changed attributes are passed to the
+/// on_update hook.
+pub async fn try_update_customer_attributes(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+    attribs: CustomerAttributes,
+) -> Result<UpdatedAttributes, DracoonClientError> {
+    let attributes = attribs
+        .items
+        .iter()
+        .map(|attrib| (attrib.key.clone(), attrib.value.clone()))
+        .collect();
+    let customer = provider.update_customer_attributes(id, attribs).await?;
+    let updated = UpdatedAttributes::new(customer, attributes);
+    run_hook(HookEvent::Update, updated.hook_values()).await;
+
+    Ok(updated)
+}
+
 pub async fn update_customer_attributes(
     provider: Dracoon<Provisioning>,
     id: u64,
@@ -545,50 +2007,108 @@ pub async fn update_customer_attributes(
         customer_attribs.add_attribute(key, value);
     });
 
-    let customer = provider
-        .update_customer_attributes(id.into(), customer_attribs)
-        .await;
-
-    if let Err(ref e) = customer {
-        handle_dracoon_errors(e, Some("Could not update customer attributes."));
-        std::process::exit(1)
-    };
-
-    let customer = customer.unwrap();
+    if dry_run_active() {
+        let path = format!("{}/customerAttributes", id);
+        print_dry_run(&provider, "PUT", &path, Some(json!(customer_attribs)));
+        return;
+    }
 
-    println!(
-        "{}{}{}",
-        "Success ".green(),
-        "Updated customer attributes of customer with id ",
-        customer.id
-    );
+    match try_update_customer_attributes(&provider, id, customer_attribs).await {
+        Ok(updated) => print_updated_attributes(&updated),
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not update customer attributes."));
+            exit(1)
+        }
+    }
 }
 
-pub async fn get_customer_users(
-    provider: Dracoon<Provisioning>,
-    id: u64,
-    filter: Option<String>,
-    sort: Option<String>,
-    offset: Option<u64>,
-    limit: Option<u64>,
-    print_type: Option<PrintType>,
-) -> () {
-    let print_type = print_type.unwrap_or(PrintType::Pretty);
-
-    let params = build_params(filter, sort, offset, limit);
+pub async fn get_customer_users(provider: Dracoon<Provisioning>, id: u64, opts: UserListOptions) {
+    let UserListOptions {
+        filter,
+        sort,
+        offset,
+        limit,
+        print_type,
+        columns,
+        with_home_rooms,
+        strict,
+    } = opts;
+
+    validate_columns(columns.as_ref(), USER_FIELDS);
+    let sort = paged_sort(sort, offset.is_some(), "userName:asc");
+
+    if raw_output_active() {
+        let params = raw_params(&filter, &sort, offset, limit);
+        print_raw(&provider, &format!("{}/users", id), &params).await;
+        return;
+    }
 
-    let user_list = provider.get_customer_users(id.into(), Some(params)).await;
+    let params = build_params(filter.clone(), sort.clone(), offset, limit);
 
-    if let Err(ref e) = user_list {
-        handle_dracoon_errors(e, Some("Could not get customer users."));
-        std::process::exit(1)
+    let user_list = match provider.get_customer_users(id, Some(params)).await {
+        Ok(user_list) => user_list,
+        // users not matching the model (e.g. older DRACOON versions omitting fields)
+        Err(ref e)
+            if !strict
+                && matches!(
+                    DcProvError::from_client_error(e),
+                    DcProvError::InvalidResponse
+                ) =>
+        {
+            eprintln!(
+                "{} Users don't match the user model (older DRACOON version?) – absent fields are shown as -",
+                "Warning".black().on_yellow()
+            );
+            let params = raw_params(&filter, &sort, offset, limit);
+            let users = get_json(&provider, &format!("{}/users", id), &params)
+                .await
+                .and_then(parse_users_lenient);
+            match users {
+                Ok(users) => users,
+                Err(e) => {
+                    println!(
+                        "{} Could not get customer users: {}",
+                        "Error".white().on_red(),
+                        e
+                    );
+                    exit(1)
+                }
+            }
+        }
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not get customer users."));
+            exit(1)
+        }
     };
 
-    let user_list = user_list.unwrap();
+    let mut user_list = user_list;
+    user_list.items = user_list.items.into_iter().map(redact_user).collect();
 
     match print_type {
+        PrintType::Json => {
+            let items: Vec<_> = user_list
+                .items
+                .iter()
+                .map(|user| {
+                    let mut item = json_columns(user_to_json(user), columns.as_ref(), |_| None);
+                    if let (true, Value::Object(fields)) = (with_home_rooms, &mut item) {
+                        fields.insert("missingHomeRoom".into(), user.home_room_id.is_none().into());
+                    }
+                    item
+                })
+                .collect();
+            print_json(json!({ "range": range_to_json(&user_list.range), "items": items }));
+            return;
+        }
         PrintType::Csv => {
-            println!("{}", CUSTOMER_USERS_CSV_HEADER);
+            let header = match &columns {
+                Some(columns) => columns.join(","),
+                None => CUSTOMER_USERS_CSV_HEADER.to_string(),
+            };
+            match with_home_rooms {
+                true => print_csv_header(&format!("{},{}", header, HOME_ROOM_CSV_COLUMNS)),
+                false => print_csv_header(&header),
+            }
         }
         PrintType::Pretty => {
             println!(
@@ -598,13 +2118,74 @@ pub async fn get_customer_users(
         }
     };
 
+    let mut without_home_room = 0;
+
     for user in user_list.items {
-        let user_line = user_to_string(user, print_type);
-        println!("{}", user_line);
+        let home_room_id = user.home_room_id;
+        let user_line = match &columns {
+            Some(columns) => user_columns_to_string(&user, columns, print_type),
+            None => user_to_string(user, print_type),
+        };
+
+        if !with_home_rooms {
+            println!("{}", user_line);
+            continue;
+        }
+
+        if home_room_id.is_none() {
+            without_home_room += 1;
+        }
+
+        match (print_type, home_room_id) {
+            (PrintType::Csv, Some(room_id)) => println!("{},{},false", user_line, room_id),
+            (PrintType::Csv, None) => println!("{},,true", user_line),
+            (PrintType::Pretty, Some(room_id)) => {
+                println!("{} | home room: {}", user_line, room_id)
+            }
+            (PrintType::Pretty, None) => {
+                println!(
+                    "{} | home room: {}",
+                    user_line,
+                    "MISSING".black().on_yellow()
+                )
+            }
+            (PrintType::Json, _) => unreachable!("printed as JSON above"),
+        }
+    }
+
+    if let (true, PrintType::Pretty) = (with_home_rooms, print_type) {
+        println!("users without home room: {}", without_home_room);
     }
 }
 
-pub fn print_version() {
+/// Prints the version – plain semver with `short`, build info as JSON with `json` (or --jmespath),
+/// the logo otherwise.
+pub fn print_version(short: bool, json: bool) {
+    if short {
+        println!("{}", env!("CARGO_PKG_VERSION"));
+        return;
+    }
+
+    if json || json_query_active() {
+        let non_empty = |value: &'static str| (!value.is_empty()).then_some(value);
+        let build_date = env!("DCPROV_BUILD_EPOCH")
+            .parse::<i64>()
+            .ok()
+            .and_then(|epoch| chrono::DateTime::from_timestamp(epoch, 0))
+            .map(|date| date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+
+        print_json(json!({
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "commit": non_empty(env!("DCPROV_BUILD_COMMIT")),
+            "buildDate": build_date,
+            "dco3Version": non_empty(env!("DCPROV_DCO3_VERSION")),
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+        }));
+        return;
+    }
+
     println!("@@@@@@@@@@@@@   @@@@@@@@@@@@@   @@@@@@@@@@@@@  @@@@@@@@@@@@@%   @@@@@@@@@@@@  @@@@@@   @@@@@  ");
     println!("@@@@@@@@@@@@@@  @@@@@@@@@@@@@@ @@@@@@@@@@@@@@  @@@@@@@@@@@@@@  @@@@@@@@@@@@@@ @@@@@@   @@@@@  ");
     println!("@@@@@   @@@@@@  @@@@@   @@@@@@ @@@@@@   @@@@@  @@@@@    @@@@@  @@@@@   @@@@@@ @@@@@@   @@@@@  ");
@@ -620,7 +2201,7 @@ pub fn print_version() {
     println!("@@@@@@          @@@@@                                   @@@@@    @@@@@@@@@@            @@@@@   ");
     println!("@@@             @@                                       @@@@       @@@@@                 @@     ");
     println!("@               @                                          @@        @@                    @");
-    println!("");
+    println!();
     println!(
         "                               {} version {}",
         env!("CARGO_PKG_NAME"),
@@ -633,6 +2214,49 @@ pub fn print_version() {
     println!("                     https://github.com/unbekanntes-pferd/dcprov         ");
 }
 
+// add a default sort to paged requests without explicit sort (--auto-sort)
+static AUTO_SORT: AtomicBool = AtomicBool::new(false);
+
+pub fn enable_auto_sort() {
+    AUTO_SORT.store(true, AtomicOrdering::SeqCst);
+}
+
+/// Returns the sort for a (possibly) paged request: without explicit sort, the order of items is not
+/// stable across pages (items are returned twice or skipped) – adds the default sort with --auto-sort,
+/// warns otherwise.
+fn paged_sort(sort: Option<String>, paged: bool, default_sort: &str) -> Option<String> {
+    if sort.is_some() || !paged {
+        return sort;
+    }
+
+    if AUTO_SORT.load(AtomicOrdering::SeqCst) {
+        return Some(default_sort.to_string());
+    }
+
+    eprintln!(
+        "{} Paging without --sort – items may be duplicated or missing across pages (pass --sort {} or --auto-sort).",
+        "Warning".black().on_yellow(),
+        default_sort
+    );
+
+    None
+}
+
+/// Query parameters of a list request (as sent by dco3) for --raw.
+fn raw_params(
+    filter: &Option<String>,
+    sort: &Option<String>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> [(&'static str, Option<String>); 4] {
+    [
+        ("filter", filter.clone()),
+        ("sort", sort.clone()),
+        ("offset", offset.map(|offset| offset.to_string())),
+        ("limit", limit.map(|limit| limit.to_string())),
+    ]
+}
+
 fn build_params(
     filter: Option<String>,
     sort: Option<String>,
@@ -667,3 +2291,73 @@ fn build_params(
 
     params.build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn customer(id: u64, company_name: &str, user_max: u64) -> Customer {
+        serde_json::from_value(json!({
+            "id": id,
+            "companyName": company_name,
+            "customerContractType": "pay",
+            "quotaMax": 1000,
+            "quotaUsed": 0,
+            "userMax": user_max,
+            "userUsed": 0,
+            "createdAt": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn ids(customers: &[Customer]) -> Vec<u64> {
+        customers.iter().map(|customer| customer.id).collect()
+    }
+
+    #[test]
+    fn dedupe_keeps_first_occurrence_and_order() {
+        // pages may overlap if customers are created while paging
+        let customers = vec![
+            customer(3, "Acme", 10),
+            customer(1, "Beta", 20),
+            customer(3, "Acme (moved)", 10),
+            customer(2, "Gamma", 30),
+            customer(1, "Beta", 20),
+        ];
+
+        let customers = dedupe_and_sort_customers(customers, None);
+
+        assert_eq!(ids(&customers), vec![3, 1, 2]);
+        assert_eq!(customers[0].company_name, "Acme");
+    }
+
+    #[test]
+    fn sort_by_multiple_fields() {
+        let customers = vec![
+            customer(1, "Beta", 20),
+            customer(2, "Acme", 10),
+            customer(3, "Acme", 30),
+            customer(4, "Gamma", 20),
+            customer(2, "Acme", 10),
+        ];
+
+        let sorted = dedupe_and_sort_customers(customers, Some("companyName:asc|userMax:desc"));
+        assert_eq!(ids(&sorted), vec![3, 2, 1, 4]);
+
+        // order defaults to ascending, ties keep the original order (stable sort)
+        let sorted = dedupe_and_sort_customers(sorted, Some("userMax"));
+        assert_eq!(ids(&sorted), vec![2, 1, 4, 3]);
+
+        let sorted = dedupe_and_sort_customers(sorted, Some("id:DESC"));
+        assert_eq!(ids(&sorted), vec![4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn sort_by_unknown_field_keeps_order() {
+        let customers = vec![customer(2, "Beta", 20), customer(1, "Acme", 10)];
+
+        let sorted = dedupe_and_sort_customers(customers, Some("unknown:asc"));
+
+        assert_eq!(ids(&sorted), vec![2, 1]);
+    }
+}
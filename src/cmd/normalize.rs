@@ -0,0 +1,231 @@
+use super::{
+    dracoon_error_to_string, exclude::apply_exclusions, fetch_all_customers, format_number,
+    install_cancel_handler, is_cancelled, print_csv_header, print_json,
+    run_lock::acquire_batch_lock, try_update_customer, BatchReport, PrintType, UpdateType,
+};
+use crate::config;
+use clap::ValueEnum;
+use colored::*;
+use dco3::{auth::Provisioning, provisioning::Customer, Dracoon};
+use serde_json::json;
+
+const NORMALIZE_CSV_HEADER: &str = "id,companyName,quotaUsed,quotaMax,newQuotaMax,note";
+
+/// Rounding of quota max values to the granularity
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum RoundingMode {
+    /// next multiple of the granularity
+    #[default]
+    RoundUp,
+    /// previous multiple of the granularity (never below quota used)
+    RoundDown,
+    /// nearest multiple of the granularity (never below quota used)
+    Nearest,
+}
+
+/// Planned quota change of a customer
+struct QuotaChange<'a> {
+    customer: &'a Customer,
+    quota_max: u64,
+    note: Option<&'static str>,
+}
+
+/// Returns the quota max rounded to the granularity – at least one granularity and never below
+/// quota used (raised to the next multiple covering it).
+fn normalized_quota(
+    customer: &Customer,
+    granularity: u64,
+    mode: RoundingMode,
+) -> (u64, Option<&'static str>) {
+    let quota_max = customer.quota_max;
+    let down = quota_max - quota_max % granularity;
+    let up = if down == quota_max {
+        down
+    } else {
+        down.saturating_add(granularity)
+    };
+
+    let target = match mode {
+        RoundingMode::RoundUp => up,
+        RoundingMode::RoundDown => down,
+        RoundingMode::Nearest if quota_max - down < up - quota_max => down,
+        RoundingMode::Nearest => up,
+    };
+
+    if target == 0 {
+        (granularity, Some("raised to min. granularity"))
+    } else if target < customer.quota_used {
+        let min_quota = customer
+            .quota_used
+            .div_ceil(granularity)
+            .saturating_mul(granularity);
+        (min_quota, Some("raised to cover quota used"))
+    } else {
+        (target, None)
+    }
+}
+
+fn print_changes(changes: &[QuotaChange], print_type: PrintType) {
+    match print_type {
+        PrintType::Csv => {
+            print_csv_header(NORMALIZE_CSV_HEADER);
+            for change in changes {
+                println!(
+                    "{},{},{},{},{},{}",
+                    change.customer.id,
+                    change.customer.company_name,
+                    change.customer.quota_used,
+                    change.customer.quota_max,
+                    change.quota_max,
+                    change.note.unwrap_or_default()
+                );
+            }
+        }
+        PrintType::Json => {
+            let items: Vec<_> = changes
+                .iter()
+                .map(|change| {
+                    json!({
+                        "id": change.customer.id,
+                        "companyName": change.customer.company_name,
+                        "quotaUsed": change.customer.quota_used,
+                        "quotaMax": change.customer.quota_max,
+                        "newQuotaMax": change.quota_max,
+                        "note": change.note,
+                    })
+                })
+                .collect();
+            print_json(json!({ "items": items }));
+        }
+        PrintType::Pretty => {
+            for change in changes {
+                println!(
+                    "{} (id: {}) | quota used: {} | quota max: {} -> {}{}",
+                    change.customer.company_name.bold(),
+                    change.customer.id,
+                    format_number(change.customer.quota_used),
+                    format_number(change.customer.quota_max),
+                    format_number(change.quota_max),
+                    change
+                        .note
+                        .map(|note| format!(" ({})", note))
+                        .unwrap_or_default()
+                );
+            }
+            println!("customers to update: {}", changes.len());
+        }
+    }
+}
+
+/// Rounds the quota max of all (filtered) customers to a granularity. Prints the planned changes
+/// for review and applies them after confirmation (unless `dry_run`).
+pub async fn normalize_quotas(
+    provider: Dracoon<Provisioning>,
+    granularity: Option<u64>,
+    mode: RoundingMode,
+    filter: Option<String>,
+    dry_run: bool,
+    yes: bool,
+    print_type: PrintType,
+) -> BatchReport {
+    // flag takes precedence over the config file (same setting as audit quotas)
+    let granularity = granularity
+        .or(config::load_config()
+            .unwrap_or_default()
+            .audit
+            .quota_granularity)
+        .filter(|granularity| *granularity > 0);
+    let Some(granularity) = granularity else {
+        println!(
+            "{} Pass --granularity (or set quota_granularity in the [audit] config).",
+            "Error".white().on_red()
+        );
+        super::exit(2)
+    };
+
+    let customers = fetch_all_customers(&provider, filter, Some("id:asc".into())).await;
+    if is_cancelled() {
+        super::exit(1)
+    }
+    let (customers, excluded) =
+        apply_exclusions(&provider, customers, |customer| customer.id).await;
+
+    let changes: Vec<_> = customers
+        .iter()
+        .map(|customer| {
+            let (quota_max, note) = normalized_quota(customer, granularity, mode);
+            QuotaChange {
+                customer,
+                quota_max,
+                note,
+            }
+        })
+        .filter(|change| change.quota_max != change.customer.quota_max)
+        .collect();
+
+    print_changes(&changes, print_type);
+
+    if dry_run {
+        return BatchReport::default();
+    }
+    if changes.is_empty() {
+        return BatchReport::default().with_excluded(excluded);
+    }
+
+    if !yes {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Update quota max of {} customer(s)?",
+                changes.len()
+            ))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+
+        if !confirmed {
+            println!("{} Aborted.", "Error".white().on_red());
+            super::exit(1)
+        }
+    }
+
+    let _batch_lock = acquire_batch_lock(&provider, "normalize-quotas");
+    install_cancel_handler();
+
+    let (mut updated, mut failed) = (0, 0);
+
+    for change in &changes {
+        if is_cancelled() {
+            eprintln!(
+                "{} Interrupted – remaining customers not updated.",
+                "Warning".black().on_yellow()
+            );
+            break;
+        }
+
+        let update_type = UpdateType::QuotaMax(change.quota_max);
+        match try_update_customer(&provider, change.customer.id, update_type).await {
+            Ok(_) => {
+                updated += 1;
+                eprintln!(
+                    "{}Updated quota max of customer {} (id: {}) to {}",
+                    "Success ".green(),
+                    change.customer.company_name,
+                    change.customer.id,
+                    change.quota_max
+                );
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!(
+                    "{} Could not update customer {} (id: {}): {}",
+                    "Error".white().on_red(),
+                    change.customer.company_name,
+                    change.customer.id,
+                    dracoon_error_to_string(e)
+                );
+            }
+        }
+    }
+
+    BatchReport::new(updated, failed, changes.len() - updated - failed).with_excluded(excluded)
+}
@@ -0,0 +1,169 @@
+use super::http_client;
+use serde_json::{json, Value};
+use std::{
+    sync::OnceLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// exporting must never noticeably delay a command
+const OTLP_TIMEOUT_SECS: u64 = 2;
+
+// OTLP span kind and status codes
+const SPAN_KIND_CLIENT: u8 = 3;
+const STATUS_OK: u8 = 1;
+const STATUS_ERROR: u8 = 2;
+
+/// Returns the OTLP/HTTP traces endpoint from the standard OpenTelemetry env vars –
+/// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is used as is, `OTEL_EXPORTER_OTLP_ENDPOINT` gets
+/// `/v1/traces` appended. Spans are only exported if one of them is set.
+pub(super) fn otlp_endpoint() -> Option<String> {
+    let non_empty = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+    non_empty("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT").or_else(|| {
+        non_empty("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .map(|endpoint| format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+    })
+}
+
+/// Parses `OTEL_EXPORTER_OTLP_HEADERS` (`key=value,key2=value2`), e.g. for an API key.
+fn otlp_headers() -> Vec<(String, String)> {
+    std::env::var("OTEL_EXPORTER_OTLP_HEADERS")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+// traceparent of the command span – continued by hooks, plugins and requests sent by dcprov itself
+static TRACEPARENT: OnceLock<String> = OnceLock::new();
+
+/// Sets the traceparent of the command span (see `traceparent`).
+pub(super) fn set_traceparent(traceparent: String) {
+    let _ = TRACEPARENT.set(traceparent);
+}
+
+/// Returns the W3C traceparent passed on to hooks, plugins, shell commands and requests sent by
+/// dcprov itself – the command span's or, without a span, the caller's `TRACEPARENT` env var.
+pub(super) fn traceparent() -> Option<String> {
+    TRACEPARENT.get().cloned().or_else(|| {
+        std::env::var("TRACEPARENT")
+            .ok()
+            .filter(|value| !value.is_empty())
+    })
+}
+
+fn random_hex(len: usize) -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..len].to_string()
+}
+
+/// Trace context of a command span – continues the trace of a W3C `TRACEPARENT` env var (e.g. set
+/// by the provisioning portal) so dcprov spans show up in the caller's trace.
+pub(super) struct SpanContext {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+}
+
+impl SpanContext {
+    pub(super) fn new() -> Self {
+        let parent = std::env::var("TRACEPARENT").ok().and_then(|traceparent| {
+            match traceparent.trim().split('-').collect::<Vec<_>>()[..] {
+                [_, trace_id, span_id, _] if trace_id.len() == 32 && span_id.len() == 16 => {
+                    Some((trace_id.to_lowercase(), span_id.to_lowercase()))
+                }
+                _ => None,
+            }
+        });
+
+        match parent {
+            Some((trace_id, parent_span_id)) => Self {
+                trace_id,
+                span_id: random_hex(16),
+                parent_span_id: Some(parent_span_id),
+            },
+            None => Self {
+                trace_id: random_hex(32),
+                span_id: random_hex(16),
+                parent_span_id: None,
+            },
+        }
+    }
+
+    /// W3C traceparent of this span – passed on via `set_traceparent`.
+    pub(super) fn traceparent(&self) -> String {
+        format!("00-{}-{}-01", self.trace_id, self.span_id)
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+fn attribute(key: &str, value: Value) -> Value {
+    let value = match value {
+        Value::Number(number) => json!({ "intValue": number.to_string() }),
+        Value::Bool(flag) => json!({ "boolValue": flag }),
+        other => json!({ "stringValue": other.as_str().unwrap_or_default() }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+/// Exports the span of a command (OTLP/HTTP JSON) – the only span of a run, DRACOON API calls are
+/// not observable (dco3 has no request hook). Failing to export is never an error of the command.
+pub(super) async fn export_command_span(
+    endpoint: &str,
+    context: &SpanContext,
+    command: &str,
+    started: SystemTime,
+    exit_code: i32,
+) {
+    let mut span = json!({
+        "traceId": context.trace_id,
+        "spanId": context.span_id,
+        "name": format!("dcprov {}", command).trim_end(),
+        "kind": SPAN_KIND_CLIENT,
+        "startTimeUnixNano": unix_nanos(started),
+        "endTimeUnixNano": unix_nanos(SystemTime::now()),
+        "attributes": [
+            attribute("dcprov.command", json!(command)),
+            attribute("process.exit.code", json!(exit_code)),
+        ],
+        "status": {
+            "code": if exit_code == 0 { STATUS_OK } else { STATUS_ERROR },
+        },
+    });
+    if let Some(parent_span_id) = &context.parent_span_id {
+        span["parentSpanId"] = json!(parent_span_id);
+    }
+
+    let service_name = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "dcprov".into());
+    let payload = json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [
+                    attribute("service.name", json!(service_name)),
+                    attribute("service.version", json!(env!("CARGO_PKG_VERSION"))),
+                ],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "dcprov", "version": env!("CARGO_PKG_VERSION") },
+                "spans": [span],
+            }],
+        }],
+    });
+
+    let mut request = http_client()
+        .post(endpoint)
+        .timeout(Duration::from_secs(OTLP_TIMEOUT_SECS))
+        .json(&payload);
+    for (key, value) in otlp_headers() {
+        request = request.header(key, value);
+    }
+
+    let _ = request.send().await;
+}
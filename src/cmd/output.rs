@@ -0,0 +1,282 @@
+use super::{redact_fields, CreatedCustomer, DeletedCustomer, UpdateOutcome, UpdatedAttributes};
+use crate::config;
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime,
+};
+use chrono_tz::Tz;
+use colored::*;
+use serde_json::{json, Value};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    OnceLock,
+};
+
+// timestamp fields (API names) rendered via --tz / --date-format
+pub(super) const TIMESTAMP_FIELDS: &[&str] = &[
+    "createdAt",
+    "updatedAt",
+    "lastLoginAt",
+    "expireAt",
+    "lastLoginSuccessAt",
+];
+
+// JMESPath expression applied to JSON output (--jmespath)
+static JSON_QUERY: OnceLock<String> = OnceLock::new();
+
+/// Sets the JMESPath expression applied to all JSON output – exits if the expression is invalid.
+pub fn set_json_query(expr: String) {
+    if let Err(e) = jmespath::compile(&expr) {
+        println!(
+            "{} Invalid JMESPath expression: {}",
+            "Error".white().on_red(),
+            e
+        );
+        super::exit(1)
+    }
+
+    let _ = JSON_QUERY.set(expr);
+}
+
+#[derive(Default)]
+struct TimestampFormat {
+    tz: Option<Tz>,
+    format: Option<String>,
+}
+
+// timezone and format of timestamps in pretty / CSV output (--tz, --date-format)
+static TIMESTAMP_FORMAT: OnceLock<TimestampFormat> = OnceLock::new();
+
+/// Sets timezone and format of timestamps (flags take precedence over the config file) – exits if invalid.
+pub fn set_timestamp_format(tz: Option<String>, format: Option<String>) {
+    let output = config::load_config().unwrap_or_default().output;
+    let (tz, format) = (tz.or(output.tz), format.or(output.date_format));
+
+    let tz = tz.map(|tz| match tz.parse::<Tz>() {
+        Ok(tz) => tz,
+        Err(_) => {
+            println!(
+                "{} Unknown timezone {} (e.g. Europe/Berlin, UTC)",
+                "Error".white().on_red(),
+                tz
+            );
+            super::exit(1)
+        }
+    });
+
+    if let Some(format) = &format {
+        if StrftimeItems::new(format).any(|item| item == Item::Error) {
+            println!(
+                "{} Invalid date format {} (e.g. %Y-%m-%d %H:%M)",
+                "Error".white().on_red(),
+                format
+            );
+            super::exit(1)
+        }
+    }
+
+    let _ = TIMESTAMP_FORMAT.set(TimestampFormat { tz, format });
+}
+
+/// Renders an RFC 3339 timestamp in the configured timezone and format (unchanged if not set or not parseable).
+pub(super) fn format_timestamp(raw: &str) -> String {
+    let Some(TimestampFormat { tz, format }) = TIMESTAMP_FORMAT.get() else {
+        return raw.to_string();
+    };
+    let Ok(timestamp) = DateTime::parse_from_rfc3339(raw) else {
+        return raw.to_string();
+    };
+
+    match (tz, format) {
+        (Some(tz), Some(format)) => timestamp.with_timezone(tz).format(format).to_string(),
+        (Some(tz), None) => timestamp.with_timezone(tz).to_rfc3339(),
+        (None, Some(format)) => timestamp.format(format).to_string(),
+        (None, None) => raw.to_string(),
+    }
+}
+
+// UTF-8 byte order mark – Excel on Windows reads CSV without it in the ANSI codepage
+const UTF8_BOM: &str = "\u{feff}";
+
+static CSV_BOM: AtomicBool = AtomicBool::new(false);
+
+/// Starts CSV output with a UTF-8 byte order mark (--bom or `bom` in the [output] config).
+pub fn set_csv_bom(bom: bool) {
+    let bom = bom || config::load_config().unwrap_or_default().output.bom;
+    CSV_BOM.store(bom, Ordering::SeqCst);
+}
+
+/// Prints the first line of CSV output (header) – prefixed with the BOM if enabled.
+pub(super) fn print_csv_header(header: &str) {
+    match CSV_BOM.load(Ordering::SeqCst) {
+        true => println!("{}{}", UTF8_BOM, header),
+        false => println!("{}", header),
+    }
+}
+
+pub(super) fn json_query_active() -> bool {
+    JSON_QUERY.get().is_some()
+}
+
+// thousands separator of numbers in pretty output (--locale) – none for the C / POSIX locale
+static THOUSANDS_SEPARATOR: OnceLock<Option<&'static str>> = OnceLock::new();
+
+/// Sets the locale of numbers in pretty output (flag takes precedence over the config file, then
+/// LC_ALL / LC_NUMERIC / LANG).
+pub fn set_number_locale(locale: Option<String>) {
+    let locale = locale
+        .or(config::load_config().unwrap_or_default().output.locale)
+        .or_else(|| {
+            ["LC_ALL", "LC_NUMERIC", "LANG"]
+                .iter()
+                .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        });
+
+    let _ = THOUSANDS_SEPARATOR.set(locale.as_deref().and_then(thousands_separator));
+}
+
+/// Returns the thousands separator of a locale, e.g. de_DE.UTF-8, de-CH or en_US.
+fn thousands_separator(locale: &str) -> Option<&'static str> {
+    let locale = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('-', "_")
+        .to_lowercase();
+    let (language, region) = locale.split_once('_').unwrap_or((&locale, ""));
+
+    match (language, region) {
+        ("" | "c" | "posix", _) => None,
+        ("de" | "fr" | "it" | "rm", "ch" | "li") => Some("'"),
+        ("de" | "nl" | "it" | "es" | "pt" | "da" | "tr" | "el" | "id", _) => Some("."),
+        ("fr" | "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "nb" | "no" | "fi" | "hu", _) => {
+            Some(" ")
+        }
+        _ => Some(","),
+    }
+}
+
+/// Renders a number (quota bytes, user counts) with the thousands separator of the locale.
+pub(super) fn format_number(number: u64) -> String {
+    let digits = number.to_string();
+    let Some(separator) = THOUSANDS_SEPARATOR.get().copied().flatten() else {
+        return digits;
+    };
+
+    let mut formatted = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push_str(separator);
+        }
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+/// Prints a JSON document (pretty) – with the configured fields redacted and filtered by the
+/// JMESPath expression if set.
+pub(super) fn print_json(value: Value) {
+    let value = redact_fields(value);
+
+    let Some(expr) = JSON_QUERY.get() else {
+        println!("{:#}", value);
+        return;
+    };
+
+    // compiled per call: expressions are not Sync (and only evaluated once per run)
+    let result = jmespath::compile(expr).and_then(|expr| expr.search(value));
+
+    match result {
+        Ok(result) => match serde_json::to_string_pretty(&*result) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                println!(
+                    "{} Could not serialize query result: {}",
+                    "Error".white().on_red(),
+                    e
+                );
+                super::exit(1)
+            }
+        },
+        Err(e) => {
+            println!("{} JMESPath query failed: {}", "Error".white().on_red(), e);
+            super::exit(1)
+        }
+    }
+}
+
+/// Prints a created customer.
+pub(super) fn print_created_customer(customer: &CreatedCustomer) {
+    println!("{}Customer creeated.", "Success ".green());
+    println!(
+        "Company name: {} | user max: {} | quota max: {} | id: {}",
+        customer.company_name,
+        format_number(customer.user_max),
+        format_number(customer.quota_max),
+        customer.id
+    );
+}
+
+/// Prints a deleted customer.
+pub(super) fn print_deleted_customer(customer: &DeletedCustomer) {
+    println!(
+        "{}Deleted customer with id {}",
+        "Success ".green(),
+        customer.id
+    );
+}
+
+/// Prints the attributes set on a customer.
+pub(super) fn print_updated_attributes(updated: &UpdatedAttributes) {
+    println!(
+        "{}Updated customer attributes of customer with id {}",
+        "Success ".green(),
+        updated.id
+    );
+}
+
+/// Prints an updated customer with the changed fields (before / after) – as JSON if `json`.
+pub(super) fn print_update_outcome(outcome: &UpdateOutcome, json: bool) {
+    if let Err(e) = &outcome.changes {
+        eprintln!(
+            "{} Could not get customer – changes can't be shown: {}",
+            "Warning".black().on_yellow(),
+            e
+        );
+    }
+
+    if json {
+        print_json(json!({ "id": outcome.id, "changes": outcome.changes_json() }));
+        return;
+    }
+
+    println!(
+        "{}Updated customer with id {}",
+        "Success ".green(),
+        outcome.id
+    );
+    println!(
+        "company: {} | contract: {} | users max: {} | quota max: {} | id: {}",
+        outcome.company_name,
+        outcome.customer_contract_type,
+        format_number(outcome.user_max),
+        format_number(outcome.quota_max),
+        outcome.id
+    );
+
+    match &outcome.changes {
+        Ok(changes) if changes.is_empty() => println!("no changes"),
+        Ok(changes) => {
+            for change in changes {
+                println!(
+                    "{}: {} -> {}",
+                    change.field,
+                    change.before.to_string().red(),
+                    change.after.to_string().green()
+                );
+            }
+        }
+        Err(_) => (),
+    }
+}
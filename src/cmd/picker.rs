@@ -0,0 +1,94 @@
+use super::{fetch_all_customers, is_cancelled, mapping::external_id_set, resolve_customer_id};
+use colored::*;
+use dco3::{auth::Provisioning, provisioning::Customer, Dracoon};
+use std::io::IsTerminal;
+
+// customers shown at once in the picker (scrollable)
+const PICKER_PAGE_SIZE: usize = 15;
+
+/// Returns the match score of a search term in a label (lower is better): substring matches rank
+/// before subsequence (fuzzy) matches, e.g. `acm` and `amc` both match `ACME Corp`.
+fn match_score(label: &str, term: &str) -> Option<usize> {
+    let label = label.to_lowercase();
+    let term = term.to_lowercase();
+
+    if let Some(position) = label.find(&term) {
+        return Some(position);
+    }
+
+    let mut chars = label.chars();
+    term.chars()
+        .all(|c| chars.any(|label_char| label_char == c))
+        .then_some(label.len())
+}
+
+fn customer_label(customer: &Customer) -> String {
+    format!("{} (id: {})", customer.company_name, customer.id)
+}
+
+/// Asks for a search term and lets the user pick a customer from the matches – repeats the search
+/// if nothing matches. Exits if the prompt is aborted.
+fn pick_customer(customers: &[Customer]) -> u64 {
+    loop {
+        let term: String = dialoguer::Input::new()
+            .with_prompt("Search customer (company name or id, empty for all)")
+            .allow_empty(true)
+            .interact_text()
+            .unwrap_or_else(|_| super::exit(1));
+
+        let mut matches: Vec<_> = customers
+            .iter()
+            .filter_map(|customer| {
+                match_score(&customer_label(customer), term.trim()).map(|score| (score, customer))
+            })
+            .collect();
+        matches.sort_by_key(|(score, _)| *score);
+
+        if matches.is_empty() {
+            eprintln!("No customer matches {} – try again.", term.trim());
+            continue;
+        }
+
+        let labels: Vec<_> = matches
+            .iter()
+            .map(|(_, customer)| customer_label(customer))
+            .collect();
+
+        let selection = dialoguer::Select::new()
+            .with_prompt(format!("Select customer ({} matches)", matches.len()))
+            .items(&labels)
+            .default(0)
+            .max_length(PICKER_PAGE_SIZE)
+            .interact_opt()
+            .unwrap_or_else(|_| super::exit(1));
+
+        // Esc returns to the search
+        if let Some(index) = selection {
+            return matches[index].1.id;
+        }
+    }
+}
+
+/// Returns the customer id to operate on – like `resolve_customer_id`, but opens a searchable
+/// picker over all customers if neither an id nor --external-id is passed in a terminal.
+pub async fn pick_customer_id(provider: &Dracoon<Provisioning>, id: Option<u64>) -> u64 {
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+
+    if id.is_some() || external_id_set() || !interactive {
+        return resolve_customer_id(provider, id).await;
+    }
+
+    eprintln!("No customer id passed – listing customers…");
+    let customers = fetch_all_customers(provider, None, Some("companyName:asc".into())).await;
+
+    if is_cancelled() {
+        super::exit(1)
+    }
+
+    if customers.is_empty() {
+        println!("{} No customers found.", "Error".white().on_red());
+        super::exit(1)
+    }
+
+    pick_customer(&customers)
+}
@@ -0,0 +1,415 @@
+use super::{
+    customer_field, dracoon_error_to_string, exclude::is_excluded, http_client,
+    install_cancel_handler, is_cancelled, run_lock::acquire_batch_lock, try_create_customer,
+    try_update_customer, try_update_customer_attributes, BatchReport, NewCustomerRequest,
+    UpdateCustomerPatch, UpdateType, CUSTOMER_FIELDS,
+};
+use colored::*;
+use dco3::{
+    auth::Provisioning,
+    provisioning::{Customer, CustomerAttributes},
+    CustomerProvisioning, Dracoon,
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+};
+
+type Vars = Map<String, Value>;
+
+/// Declarative sequence of provisioning steps
+#[derive(Deserialize)]
+pub struct Playbook {
+    /// DRACOON url (or alias) – can be overridden via --url
+    pub url: Option<String>,
+    #[serde(default)]
+    vars: Vars,
+    steps: Vec<Step>,
+}
+
+#[derive(Deserialize)]
+struct Step {
+    name: Option<String>,
+    /// condition (rendered): skipped if empty, false, 0 or null – supports `a == b` and `a != b`
+    when: Option<String>,
+    /// variable to store the resulting customer in (e.g. `{{ customer.id }}`) – create, update and verify only
+    register: Option<String>,
+    /// action (single key), rendered before parsing
+    #[serde(flatten)]
+    action: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum Action {
+    /// same format as create from-file
+    Create(Box<NewCustomerRequest>),
+    Update {
+        id: u64,
+        company_name: Option<String>,
+        quota_max: Option<u64>,
+        user_max: Option<u64>,
+    },
+    SetAttributes {
+        id: u64,
+        attributes: BTreeMap<String, Value>,
+    },
+    /// compares customer fields (API names, attributes via attribute:<key>)
+    Verify {
+        id: u64,
+        expect: BTreeMap<String, Value>,
+    },
+    Webhook {
+        url: String,
+        #[serde(default)]
+        body: Value,
+    },
+}
+
+impl Action {
+    /// Returns the customer changed by the action – skipped if excluded (--exclude-attribute, --exclude-ids).
+    fn changed_customer(&self) -> Option<u64> {
+        match self {
+            Action::Update { id, .. } | Action::SetAttributes { id, .. } => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+/// Result of a step that did not fail
+enum StepOutcome {
+    Done(String, Option<Value>),
+    ConditionNotMet,
+    Excluded(u64),
+}
+
+/// Reads a playbook (YAML) – exits if the file cannot be read or parsed.
+pub fn load_playbook(path: &str) -> Playbook {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) => {
+            println!(
+                "{} Could not open file from path {}",
+                "Error".white().on_red(),
+                path
+            );
+            println!("{:?}", e);
+            super::exit(1)
+        }
+    };
+
+    match serde_yaml::from_str(&raw) {
+        Ok(playbook) => playbook,
+        Err(e) => {
+            println!(
+                "{} Could not parse playbook {}: {}",
+                "Error".white().on_red(),
+                path,
+                e
+            );
+            super::exit(1)
+        }
+    }
+}
+
+fn lookup<'a>(vars: &'a Vars, path: &str) -> Option<&'a Value> {
+    let mut parts = path.split('.');
+    let mut value = vars.get(parts.next()?)?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+/// Replaces `{{ var }}` placeholders – a string consisting of a single placeholder keeps the type of the variable.
+fn render_str(template: &str, vars: &Vars) -> Result<Value, String> {
+    let undefined = |name: &str| format!("undefined variable: {}", name);
+
+    if let Some(name) = template
+        .trim()
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .filter(|name| !name.contains("{{") && !name.contains("}}"))
+    {
+        let name = name.trim();
+        return lookup(vars, name).cloned().ok_or_else(|| undefined(name));
+    }
+
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .map(|end| start + end)
+            .ok_or_else(|| format!("unclosed placeholder in `{}`", template))?;
+        let name = rest[start + 2..end].trim();
+        let value = lookup(vars, name).ok_or_else(|| undefined(name))?;
+
+        result.push_str(&rest[..start]);
+        result.push_str(&value_to_string(value));
+        rest = &rest[end + 2..];
+    }
+    result.push_str(rest);
+
+    Ok(Value::String(result))
+}
+
+fn render(value: Value, vars: &Vars) -> Result<Value, String> {
+    match value {
+        Value::String(s) => render_str(&s, vars),
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| render(item, vars))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .into_iter()
+            .map(|(key, value)| Ok((key, render(value, vars)?)))
+            .collect::<Result<_, String>>()
+            .map(Value::Object),
+        value => Ok(value),
+    }
+}
+
+fn is_true(condition: &str) -> bool {
+    if let Some((a, b)) = condition.split_once("!=") {
+        return a.trim() != b.trim();
+    }
+    if let Some((a, b)) = condition.split_once("==") {
+        return a.trim() == b.trim();
+    }
+    !matches!(condition.trim(), "" | "false" | "0" | "null" | "no")
+}
+
+/// Customer fields as variables (numbers and booleans keep their type).
+fn customer_to_vars(customer: &Customer) -> Value {
+    let fields = CUSTOMER_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let value = customer_field(customer, field)?;
+            let value = serde_json::from_str::<Value>(&value)
+                .ok()
+                .filter(|v| v.is_number() || v.is_boolean())
+                .unwrap_or(Value::String(value));
+            Some((field.to_string(), value))
+        })
+        .collect();
+
+    Value::Object(fields)
+}
+
+async fn run_action(
+    provider: &Dracoon<Provisioning>,
+    action: Action,
+) -> Result<(String, Option<Value>), String> {
+    match action {
+        Action::Create(new_customer) => {
+            let customer = try_create_customer(provider, (*new_customer).into())
+                .await
+                .map_err(dracoon_error_to_string)?;
+            let vars = json!({
+                "id": customer.id,
+                "companyName": customer.company_name,
+                "customerContractType": customer.customer_contract_type,
+                "quotaMax": customer.quota_max,
+                "userMax": customer.user_max,
+            });
+            Ok((format!("created customer {}", customer.id), Some(vars)))
+        }
+        Action::Update {
+            id,
+            company_name,
+            quota_max,
+            user_max,
+        } => {
+            if company_name.is_none() && quota_max.is_none() && user_max.is_none() {
+                return Err("nothing to update (company_name, quota_max or user_max)".into());
+            }
+
+            let patch = UpdateCustomerPatch {
+                company_name,
+                quota_max,
+                user_max,
+                ..Default::default()
+            };
+
+            let customer = try_update_customer(provider, id, UpdateType::Patch(patch))
+                .await
+                .map_err(dracoon_error_to_string)?;
+            let vars = json!({
+                "id": customer.id,
+                "companyName": customer.company_name,
+                "customerContractType": customer.customer_contract_type,
+                "quotaMax": customer.quota_max,
+                "userMax": customer.user_max,
+                "customerUuid": customer.customer_uuid,
+            });
+            Ok((format!("updated customer {}", id), Some(vars)))
+        }
+        Action::SetAttributes { id, attributes } => {
+            let mut customer_attribs = CustomerAttributes::new();
+            attributes.iter().for_each(|(key, value)| {
+                customer_attribs.add_attribute(key, value_to_string(value));
+            });
+
+            try_update_customer_attributes(provider, id, customer_attribs)
+                .await
+                .map_err(dracoon_error_to_string)?;
+            Ok((
+                format!("set {} attribute(s) on customer {}", attributes.len(), id),
+                None,
+            ))
+        }
+        Action::Verify { id, expect } => {
+            let customer = provider
+                .get_customer(id, Some(true))
+                .await
+                .map_err(dracoon_error_to_string)?;
+
+            let mismatches: Vec<_> = expect
+                .iter()
+                .filter_map(|(field, expected)| {
+                    let expected = value_to_string(expected);
+                    let actual = customer_field(&customer, field).unwrap_or_default();
+                    (actual != expected)
+                        .then(|| format!("{}: expected {}, got {}", field, expected, actual))
+                })
+                .collect();
+
+            if !mismatches.is_empty() {
+                return Err(format!("verification failed – {}", mismatches.join(" | ")));
+            }
+            Ok((
+                format!("verified customer {}", id),
+                Some(customer_to_vars(&customer)),
+            ))
+        }
+        Action::Webhook { url, body } => {
+            let res = http_client()
+                .post(&url)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| format!("webhook failed: {}", e))?;
+
+            if !res.status().is_success() {
+                return Err(format!("webhook returned {}", res.status()));
+            }
+            Ok((format!("notified {}", url), None))
+        }
+    }
+}
+
+/// Runs all steps of a playbook in order and stops on the first error (exit code 1).
+pub async fn run_playbook(
+    provider: Dracoon<Provisioning>,
+    playbook: Playbook,
+    overrides: Vec<(String, String)>,
+) -> BatchReport {
+    let _batch_lock = acquire_batch_lock(&provider, "run");
+    install_cancel_handler();
+
+    let mut vars = playbook.vars;
+    // values passed via --var are parsed as YAML scalars (numbers, booleans)
+    for (key, value) in overrides {
+        let value = serde_yaml::from_str::<Value>(&value).unwrap_or(Value::String(value));
+        vars.insert(key, value);
+    }
+
+    let total = playbook.steps.len();
+    let mut skipped = 0;
+    let mut excluded = BTreeSet::new();
+
+    for (idx, step) in playbook.steps.into_iter().enumerate() {
+        if is_cancelled() {
+            println!(
+                "{} Cancelled before step {} of {}.",
+                "Warning".black().on_yellow(),
+                idx + 1,
+                total
+            );
+            return BatchReport::new(idx - excluded.len(), 0, total - idx)
+                .with_excluded(excluded.into_iter().collect());
+        }
+
+        let label = step
+            .name
+            .clone()
+            .or_else(|| step.action.keys().next().cloned())
+            .unwrap_or_default();
+        let prefix = format!("[{}/{}] {}", idx + 1, total, label);
+
+        let result = async {
+            if let Some(when) = step.when.as_deref() {
+                let condition = render_str(when, &vars)?;
+                if !is_true(&value_to_string(&condition)) {
+                    return Ok(StepOutcome::ConditionNotMet);
+                }
+            }
+
+            if step.action.len() != 1 {
+                return Err("step requires exactly one action (create, update, set_attributes, verify, webhook)".to_string());
+            }
+            let action = render(Value::Object(step.action), &vars)?;
+            let action = serde_json::from_value::<Action>(action)
+                .map_err(|e| format!("invalid step: {}", e))?;
+
+            if let Some(id) = action.changed_customer() {
+                if is_excluded(&provider, id).await {
+                    return Ok(StepOutcome::Excluded(id));
+                }
+            }
+
+            run_action(&provider, action)
+                .await
+                .map(|(msg, result)| StepOutcome::Done(msg, result))
+        }
+        .await;
+
+        match result {
+            Ok(StepOutcome::Done(msg, result)) => {
+                println!("{}{} – {}", "Success ".green(), prefix, msg);
+                if let (Some(register), Some(result)) = (step.register, result) {
+                    vars.insert(register, result);
+                }
+            }
+            Ok(StepOutcome::ConditionNotMet) => {
+                skipped += 1;
+                println!("{}{} – condition not met", "Skipped ".yellow(), prefix);
+            }
+            Ok(StepOutcome::Excluded(id)) => {
+                skipped += 1;
+                excluded.insert(id);
+                println!(
+                    "{}{} – customer {} excluded",
+                    "Skipped ".yellow(),
+                    prefix,
+                    id
+                );
+            }
+            Err(msg) => {
+                println!("{} {} – {}", "Error".white().on_red(), prefix, msg);
+                println!("Stopped at step {} of {}.", idx + 1, total);
+                return BatchReport::new(idx - excluded.len(), 1, total - idx - 1)
+                    .with_excluded(excluded.into_iter().collect());
+            }
+        }
+    }
+
+    println!(
+        "Playbook finished: {} step(s) run, {} skipped.",
+        total - skipped,
+        skipped
+    );
+    BatchReport::new(total - excluded.len(), 0, 0).with_excluded(excluded.into_iter().collect())
+}
@@ -0,0 +1,127 @@
+use super::{find_token, guard, otel::traceparent, ClientOptions};
+use crate::config;
+use colored::*;
+use serde_json::json;
+use std::{
+    collections::BTreeSet,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+// plugins are executables named dcprov-<name> on PATH
+const PLUGIN_PREFIX: &str = "dcprov-";
+
+fn plugin_file_name(name: &str) -> String {
+    match cfg!(windows) {
+        true => format!("{}{}.exe", PLUGIN_PREFIX, name),
+        false => format!("{}{}", PLUGIN_PREFIX, name),
+    }
+}
+
+/// Returns the path of a plugin executable on PATH.
+fn find_plugin(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(plugin_file_name(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Prints all plugins found on PATH.
+pub fn list_plugins() {
+    let names: BTreeSet<_> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let name = name.strip_prefix(PLUGIN_PREFIX)?;
+            Some(name.trim_end_matches(".exe").to_string())
+        })
+        .collect();
+
+    if names.is_empty() {
+        println!(
+            "No plugins found (executables named {}<name> on PATH).",
+            PLUGIN_PREFIX
+        );
+    }
+    for name in names {
+        println!("{}", name);
+    }
+}
+
+/// Runs a plugin (`dcprov <name> [url] [args...]`) – the first argument is resolved as DRACOON url or alias
+/// like for built-in commands. The context (url, token, request id, ...) is passed as JSON on stdin.
+/// Exits with the exit code of the plugin.
+pub fn run_plugin(args: Vec<String>, token: Option<String>, client_opts: &ClientOptions) -> ! {
+    let (name, args) = args.split_first().expect("clap passes the subcommand name");
+
+    let Some(path) = find_plugin(name) else {
+        println!(
+            "{} Unknown command {} (no plugin {} on PATH) – see dcprov --help",
+            "Error".white().on_red(),
+            name,
+            plugin_file_name(name)
+        );
+        super::exit(1)
+    };
+
+    let url = args
+        .first()
+        .filter(|arg| !arg.starts_with('-'))
+        .and_then(|url_or_alias| {
+            guard::print_production_banner(url_or_alias);
+            config::resolve_url(url_or_alias).ok()
+        });
+    let token = url
+        .as_deref()
+        .and_then(|url| find_token(url, token, client_opts))
+        .map(|(token, _)| token);
+
+    let context = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "url": url,
+        "token": token,
+        "verbose": client_opts.verbose,
+        "args": args,
+    });
+
+    if client_opts.verbose {
+        eprintln!("Plugin: {}", path.display());
+    }
+
+    let mut cmd = Command::new(&path);
+    if let Some(traceparent) = traceparent() {
+        cmd.env("TRACEPARENT", traceparent);
+    }
+
+    let result = cmd
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                // plugins not reading the context close the pipe – not an error
+                let _ = stdin.write_all(context.to_string().as_bytes());
+            }
+            child.wait()
+        });
+
+    match result {
+        Ok(status) => super::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            println!(
+                "{} Could not run plugin {}: {}",
+                "Error".white().on_red(),
+                path.display(),
+                e
+            );
+            super::exit(1)
+        }
+    }
+}
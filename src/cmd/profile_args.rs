@@ -0,0 +1,210 @@
+use super::{
+    default_url::{default_url, is_customer_id},
+    DcProv,
+};
+use crate::config;
+use clap::{Arg, ArgMatches, Command, CommandFactory};
+use std::ffi::OsString;
+
+/// Returns the path of the subcommand owning the url argument with the url passed (if any), e.g.
+/// `update` and `prod` for `update prod 999 quota-max 10`.
+fn url_command(matches: &ArgMatches) -> Option<(Vec<String>, Option<String>)> {
+    let mut path = Vec::new();
+    let mut current = matches;
+
+    while let Some((name, sub)) = current.subcommand() {
+        path.push(name.to_string());
+        if let Ok(url) = sub.try_get_one::<String>("url") {
+            return Some((path, url.cloned()));
+        }
+        current = sub;
+    }
+
+    None
+}
+
+fn find_command(path: &[String]) -> Option<Command> {
+    let mut command = DcProv::command();
+    for name in path {
+        command = command.find_subcommand(name)?.clone();
+    }
+    Some(command)
+}
+
+/// Splits default args into flags with their values, e.g. `--format json` or `--csv`.
+fn flag_groups(args: &[String]) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+
+    for arg in args {
+        match groups.last_mut() {
+            Some(group) if !arg.starts_with('-') => group.push(arg.clone()),
+            _ => groups.push(vec![arg.clone()]),
+        }
+    }
+
+    groups
+}
+
+/// Returns the long name and values of a flag group, e.g. `format` and `json` for `--format=json`.
+fn flag_values(group: &[String]) -> (&str, Vec<String>) {
+    let flag = group[0].trim_start_matches('-');
+    let (name, inline) = match flag.split_once('=') {
+        Some((name, value)) => (name, Some(value.to_string())),
+        None => (flag, None),
+    };
+
+    (
+        name,
+        inline.into_iter().chain(group[1..].to_vec()).collect(),
+    )
+}
+
+/// Sets the profile values as default of an argument – flags without value are switched on.
+fn with_default(arg: Arg, values: &[String]) -> Arg {
+    match (arg.get_action().takes_values(), values) {
+        (false, _) => arg.default_value("true"),
+        (true, []) => arg,
+        (true, values) => arg.default_values(values),
+    }
+}
+
+/// Sets the default of an argument of the (nested) subcommand at path.
+fn set_default(command: Command, path: &[String], id: &str, values: &[String]) -> Command {
+    match path.split_first() {
+        Some((name, rest)) => {
+            command.mut_subcommand(name, |sub| set_default(sub, rest, id, values))
+        }
+        None => command.mut_arg(id, |arg| with_default(arg, values)),
+    }
+}
+
+/// Applies profile default args as defaults of the arguments of the command at path and parses the
+/// args again: flags passed explicitly take precedence, flags the command does not accept are
+/// skipped. Returns the matches unchanged if no default arg applies.
+fn with_profile_args(
+    args: Vec<OsString>,
+    matches: ArgMatches,
+    path: &[String],
+    profile_args: &[String],
+) -> ArgMatches {
+    let Some(command) = find_command(path) else {
+        return matches;
+    };
+
+    let root = DcProv::command();
+    let mut cli = DcProv::command();
+    let mut applied = false;
+
+    for group in flag_groups(profile_args) {
+        // defaults apply to all commands – skip flags the targeted command does not know
+        if !group[0].starts_with("--") {
+            continue;
+        }
+        let (name, values) = flag_values(&group);
+
+        if let Some(arg) = command
+            .get_arguments()
+            .find(|arg| !arg.is_global_set() && arg.get_long() == Some(name))
+        {
+            cli = set_default(cli, path, arg.get_id().as_str(), &values);
+            applied = true;
+        } else if let Some(arg) = root
+            .get_arguments()
+            .find(|arg| arg.is_global_set() && arg.get_long() == Some(name))
+        {
+            cli = set_default(cli, &[], arg.get_id().as_str(), &values);
+            applied = true;
+        }
+    }
+
+    if !applied {
+        return matches;
+    }
+
+    cli.get_matches_from(args)
+}
+
+/// Applies the default args of the profile targeted by the command (alias, url or – if omitted –
+/// --profile / default url) – see `with_profile_args`. Returns the matches unchanged if no profile
+/// with default args is targeted.
+pub fn apply_profile_args(args: Vec<OsString>, matches: ArgMatches) -> ArgMatches {
+    let Some((path, url)) = url_command(&matches) else {
+        return matches;
+    };
+    let url = url
+        .filter(|url| !is_customer_id(url))
+        .or_else(|| default_url(matches.get_one::<String>("profile").map(String::as_str)));
+    let Some(Ok(Some((_, profile)))) = url.map(|url| config::find_profile(&url)) else {
+        return matches;
+    };
+
+    with_profile_args(args, matches, &path, &profile.args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(args: &str, profile_args: &[&str]) -> ArgMatches {
+        let args: Vec<OsString> = std::iter::once("dcprov")
+            .chain(args.split_whitespace())
+            .map(OsString::from)
+            .collect();
+        let matches = DcProv::command().get_matches_from(&args);
+        let (path, _) = url_command(&matches).unwrap();
+        let profile_args: Vec<String> = profile_args.iter().map(|arg| arg.to_string()).collect();
+
+        with_profile_args(args, matches, &path, &profile_args)
+    }
+
+    fn list(matches: &ArgMatches) -> &ArgMatches {
+        matches.subcommand_matches("list").unwrap()
+    }
+
+    #[test]
+    fn profile_args_are_defaults() {
+        let matches = apply(
+            "list prod",
+            &["--all", "--filter", "isLocked:eq:true", "--verbose"],
+        );
+
+        assert!(list(&matches).get_flag("all"));
+        assert!(!list(&matches).get_flag("csv"));
+        assert_eq!(
+            list(&matches).get_one::<String>("filter").unwrap(),
+            "isLocked:eq:true"
+        );
+        // global flags apply as well
+        assert!(matches.get_flag("verbose"));
+    }
+
+    #[test]
+    fn explicit_args_override_profile_args() {
+        let matches = apply(
+            "list prod --filter companyName:cn:acme --sort=id:desc",
+            &[
+                "--filter=isLocked:eq:true",
+                "--sort",
+                "companyName:asc",
+                "--csv",
+            ],
+        );
+
+        assert_eq!(
+            list(&matches).get_one::<String>("filter").unwrap(),
+            "companyName:cn:acme"
+        );
+        assert_eq!(list(&matches).get_one::<String>("sort").unwrap(), "id:desc");
+        assert!(list(&matches).get_flag("csv"));
+    }
+
+    #[test]
+    fn unknown_profile_args_are_skipped() {
+        // --state is only known to `lifecycle list` (parsing would fail), values without flag are
+        // ignored
+        let matches = apply("list prod --csv", &["all", "--state", "suspended", "--all"]);
+
+        assert!(list(&matches).get_flag("csv"));
+        assert!(list(&matches).get_flag("all"));
+    }
+}
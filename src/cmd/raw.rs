@@ -0,0 +1,89 @@
+use super::http_client;
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// base path of the provisioning API (relative to the DRACOON url)
+pub(super) const PROVISIONING_CUSTOMERS_PATH: &str = "api/v4/provisioning/customers";
+
+// header carrying the X-SDS-Service-Token
+const SERVICE_TOKEN_HEADER: &str = "X-Sds-Service-Token";
+
+static RAW_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Prints the unmodified response bodies of the API instead of the formatted output (--raw).
+pub fn enable_raw_output() {
+    RAW_OUTPUT.store(true, Ordering::SeqCst);
+}
+
+pub(super) fn raw_output_active() -> bool {
+    RAW_OUTPUT.load(Ordering::SeqCst)
+}
+
+/// Sends the GET request of a list / get command (path relative to the customers endpoint, e.g.
+/// `999/users`) – returns the status and the unmodified response body.
+async fn get_raw(
+    provider: &Dracoon<Provisioning>,
+    path: &str,
+    params: &[(&str, Option<String>)],
+) -> Result<(StatusCode, String), reqwest::Error> {
+    let mut url = provider.build_api_url(&match path {
+        "" => PROVISIONING_CUSTOMERS_PATH.to_string(),
+        path => format!("{}/{}", PROVISIONING_CUSTOMERS_PATH, path),
+    });
+    for (name, value) in params {
+        if let Some(value) = value {
+            url.query_pairs_mut().append_pair(name, value);
+        }
+    }
+
+    let res = http_client()
+        .get(url)
+        .header(SERVICE_TOKEN_HEADER, provider.get_service_token())
+        .send()
+        .await?;
+    let status = res.status();
+
+    Ok((status, res.text().await?))
+}
+
+/// Prints the unmodified response body of a list / get request – exits with 1 on error responses.
+pub(super) async fn print_raw(
+    provider: &Dracoon<Provisioning>,
+    path: &str,
+    params: &[(&str, Option<String>)],
+) {
+    let (status, body) = match get_raw(provider, path, params).await {
+        Ok(res) => res,
+        Err(e) => {
+            println!("{} Request failed: {}", "Error".white().on_red(), e);
+            super::exit(1)
+        }
+    };
+
+    println!("{}", body);
+
+    if !status.is_success() {
+        eprintln!("{} HTTP {}", "Error".white().on_red(), status);
+        super::exit(1)
+    }
+}
+
+/// Returns the JSON response of a list / get request (without mapping it to a dco3 model).
+pub(super) async fn get_json(
+    provider: &Dracoon<Provisioning>,
+    path: &str,
+    params: &[(&str, Option<String>)],
+) -> Result<Value, String> {
+    let (status, body) = get_raw(provider, path, params)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !status.is_success() {
+        return Err(format!("HTTP {}", status));
+    }
+
+    serde_json::from_str(&body).map_err(|e| e.to_string())
+}
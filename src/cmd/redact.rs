@@ -0,0 +1,226 @@
+use crate::config;
+use dco3::{provisioning::Customer, users::UserItem};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+// env var holding an optional salt for hashed values (prevents dictionary lookups of known emails)
+const REDACT_SALT_ENV: &str = "DCPROV_REDACT_SALT";
+// length of the hex digest used for pseudonyms
+const HASH_LEN: usize = 12;
+
+// placeholder of masked fields
+const MASKED: &str = "[redacted]";
+
+static REDACT_PII: AtomicBool = AtomicBool::new(false);
+
+// fields redacted in all output (lowercase keys) – loaded from the [redaction] config once
+static REDACTED_FIELDS: OnceLock<(Vec<String>, Vec<String>)> = OnceLock::new();
+
+// values produced by redaction – returned unchanged if redacted again (e.g. redacted users printed
+// as JSON)
+static REDACTED_VALUES: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Enables redaction of personal data (names, emails, phone numbers) in all output.
+pub fn enable_pii_redaction() {
+    REDACT_PII.store(true, Ordering::SeqCst);
+}
+
+/// Returns a stable pseudonym for a value – equal values map to equal hashes across runs.
+fn hash(value: &str) -> String {
+    let salt = std::env::var(REDACT_SALT_ENV).unwrap_or_default();
+    let digest = Sha256::digest(format!("{}{}", salt, value).as_bytes());
+
+    digest
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>()[..HASH_LEN]
+        .to_string()
+}
+
+/// Masks all but the last two digits of a phone number.
+fn mask_phone(phone: &str) -> String {
+    let digits: Vec<_> = phone.chars().filter(char::is_ascii_digit).collect();
+    let visible: String = digits[digits.len().saturating_sub(2)..].iter().collect();
+
+    format!("***{}", visible)
+}
+
+fn redacted_fields() -> &'static (Vec<String>, Vec<String>) {
+    REDACTED_FIELDS.get_or_init(|| {
+        let redaction = config::load_config().unwrap_or_default().redaction;
+        let lowercase = |fields: Vec<String>| fields.iter().map(|f| f.to_lowercase()).collect();
+
+        (lowercase(redaction.hash), lowercase(redaction.mask))
+    })
+}
+
+/// Redaction of a field – configured in the [redaction] config or personal data (--redact-pii)
+#[derive(Clone, Copy)]
+enum Redaction {
+    Hash,
+    Mask,
+    Name,
+    UserName,
+    Email,
+    Phone,
+}
+
+impl Redaction {
+    fn apply(self, value: &str) -> String {
+        let Ok(mut redacted_values) = REDACTED_VALUES.lock() else {
+            return MASKED.into();
+        };
+        if redacted_values.contains(value) {
+            return value.into();
+        }
+
+        let redacted = match self {
+            Redaction::Hash => hash(&value.to_lowercase()),
+            Redaction::Mask => MASKED.into(),
+            Redaction::Name => hash(value),
+            Redaction::UserName => format!("user-{}", hash(&value.to_lowercase())),
+            Redaction::Email => format!("{}@redacted.invalid", hash(&value.to_lowercase())),
+            Redaction::Phone => mask_phone(value),
+        };
+        redacted_values.insert(redacted.clone());
+
+        redacted
+    }
+}
+
+fn redaction_active() -> bool {
+    let (hashed, masked) = redacted_fields();
+
+    REDACT_PII.load(Ordering::SeqCst) || !hashed.is_empty() || !masked.is_empty()
+}
+
+/// Returns the redaction of a field (API name, case-insensitive): the [redaction] config takes
+/// precedence over --redact-pii (user names, first / last names, emails and phone numbers).
+fn redaction_of(field: &str) -> Option<Redaction> {
+    let field = field.to_lowercase();
+    let (hashed, masked) = redacted_fields();
+
+    if masked.contains(&field) {
+        return Some(Redaction::Mask);
+    }
+    if hashed.contains(&field) {
+        return Some(Redaction::Hash);
+    }
+    if !REDACT_PII.load(Ordering::SeqCst) {
+        return None;
+    }
+
+    match field.as_str() {
+        "username" => Some(Redaction::UserName),
+        "firstname" | "lastname" => Some(Redaction::Name),
+        "email" => Some(Redaction::Email),
+        "phone" => Some(Redaction::Phone),
+        _ => None,
+    }
+}
+
+/// Returns the redacted value of a field (API name) – unchanged if the field is not redacted.
+/// Used by CSV and pretty output, JSON output is redacted by `redact_fields`.
+pub(super) fn redact(field: &str, value: &str) -> String {
+    match redaction_of(field) {
+        Some(redaction) => redaction.apply(value),
+        None => value.to_string(),
+    }
+}
+
+/// Returns the user with its redacted fields (see `redact`). IDs are kept to allow joining
+/// exports.
+pub(super) fn redact_user(mut user: UserItem) -> UserItem {
+    if !redaction_active() {
+        return user;
+    }
+
+    user.user_name = redact("userName", &user.user_name);
+    user.first_name = redact("firstName", &user.first_name);
+    user.last_name = redact("lastName", &user.last_name);
+    user.email = user.email.map(|email| redact("email", &email));
+    user.phone = user.phone.map(|phone| redact("phone", &phone));
+
+    user
+}
+
+/// Returns the customer with its redacted fields (see `redact`) – attribute values are redacted by
+/// their key.
+pub(super) fn redact_customer(mut customer: Customer) -> Customer {
+    if !redaction_active() {
+        return customer;
+    }
+
+    customer.company_name = redact("companyName", &customer.company_name);
+    customer.customer_contract_type =
+        redact("customerContractType", &customer.customer_contract_type);
+    customer.customer_uuid = customer
+        .customer_uuid
+        .map(|uuid| redact("customerUuid", &uuid));
+    if let Some(attribs) = customer.customer_attributes.as_mut() {
+        for attrib in attribs.items.iter_mut() {
+            attrib.value = redact(&attrib.key, &attrib.value);
+        }
+    }
+
+    customer
+}
+
+/// Redacts the fields configured in the [redaction] config and personal data (--redact-pii) at
+/// any depth: `hash` fields get a stable pseudonym, `mask` fields a placeholder. Applied to all
+/// JSON output so reports, audits and run results can be shipped to a log system.
+pub(super) fn redact_fields(value: Value) -> Value {
+    if !redaction_active() {
+        return value;
+    }
+
+    redact_value(value)
+}
+
+fn redact_value(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let value = match (redaction_of(&key), value) {
+                        (_, Value::Null) => Value::Null,
+                        (Some(Redaction::Mask), _) => Value::String(MASKED.into()),
+                        (Some(redaction), Value::String(raw)) => {
+                            Value::String(redaction.apply(&raw))
+                        }
+                        (Some(redaction), value @ (Value::Number(_) | Value::Bool(_))) => {
+                            Value::String(redaction.apply(&value.to_string()))
+                        }
+                        (_, value) => redact_value(value),
+                    };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact_value).collect()),
+        scalar => scalar,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacting_twice_keeps_the_pseudonym() {
+        let email = Redaction::Email.apply("Jane.Doe@example.com");
+
+        assert!(email.ends_with("@redacted.invalid"));
+        assert_eq!(email, Redaction::Email.apply("jane.doe@example.com"));
+        // output passing several redacting layers (e.g. redacted users printed as JSON)
+        assert_eq!(Redaction::Email.apply(&email), email);
+        assert_eq!(Redaction::Hash.apply(&email), email);
+    }
+}
@@ -0,0 +1,566 @@
+use super::{
+    dracoon_error_to_string, fetch_all_customers, format_number, format_timestamp, get_json,
+    install_cancel_handler, is_cancelled, print_csv_header, print_json, raw_params, redact_user,
+    PrintType,
+};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use colored::*;
+use dco3::{
+    auth::Provisioning, users::UserItem, CustomerProvisioning, Dracoon, DracoonClientError,
+    ListAllParams,
+};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+// max. page size for customer users
+const USERS_PAGE_SIZE: u64 = 500;
+
+const EXPIRING_USERS_CSV_HEADER: &str = "customerId,companyName,userId,userName,email,expireAt";
+const ENCRYPTION_CSV_HEADER: &str = "customerId,companyName,users,encryptionEnabled,percent";
+const DUPLICATES_CSV_HEADER: &str = "key,customerId,companyName,providerCustomerId,createdAt";
+const GROWTH_CSV_HEADER: &str = "month,contractType,newCustomers,customers,newQuotaMax,quotaMax";
+
+/// Fetches all users of a customer (all pages).
+pub(super) async fn fetch_all_users(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+) -> Result<Vec<UserItem>, DracoonClientError> {
+    let mut users = Vec::new();
+
+    loop {
+        let params = ListAllParams::builder()
+            .with_offset(users.len() as u64)
+            .with_limit(USERS_PAGE_SIZE)
+            .build();
+        let page = provider.get_customer_users(id, Some(params)).await?;
+        let total = page.range.total;
+
+        if page.items.is_empty() {
+            break;
+        }
+        users.extend(page.items);
+
+        if users.len() as u64 >= total {
+            break;
+        }
+    }
+
+    Ok(users)
+}
+
+/// Lists users expiring within the given window, grouped by customer.
+pub async fn report_expiring_users(
+    provider: Dracoon<Provisioning>,
+    within: Duration,
+    filter: Option<String>,
+    print_type: PrintType,
+) {
+    let now = Utc::now();
+    let until = now + within;
+
+    let customers = fetch_all_customers(&provider, filter, Some("companyName:asc".into())).await;
+
+    install_cancel_handler();
+
+    if let PrintType::Csv = print_type {
+        print_csv_header(EXPIRING_USERS_CSV_HEADER);
+    }
+
+    let (mut expiring, mut failed) = (0, 0);
+    let mut json_items = Vec::new();
+
+    for customer in &customers {
+        if is_cancelled() {
+            eprintln!(
+                "{} Interrupted – remaining customers not scanned.",
+                "Warning".black().on_yellow()
+            );
+            break;
+        }
+
+        let users = match fetch_all_users(&provider, customer.id).await {
+            Ok(users) => users,
+            Err(e) => {
+                failed += 1;
+                eprintln!(
+                    "{} Could not get users of customer {}: {}",
+                    "Error".white().on_red(),
+                    customer.id,
+                    dracoon_error_to_string(e)
+                );
+                continue;
+            }
+        };
+
+        let mut users: Vec<_> = users
+            .into_iter()
+            .map(redact_user)
+            .filter_map(|user| {
+                let expire_at = DateTime::parse_from_rfc3339(user.expire_at.as_deref()?).ok()?;
+                (expire_at >= now && expire_at <= until).then_some((expire_at, user))
+            })
+            .collect();
+
+        if users.is_empty() {
+            continue;
+        }
+
+        users.sort_by_key(|(expire_at, _)| *expire_at);
+        expiring += users.len();
+
+        match print_type {
+            PrintType::Csv => {
+                for (expire_at, user) in users {
+                    println!(
+                        "{},{},{},{},{},{}",
+                        customer.id,
+                        customer.company_name,
+                        user.id,
+                        user.user_name,
+                        user.email.unwrap_or_default(),
+                        format_timestamp(&expire_at.to_rfc3339())
+                    );
+                }
+            }
+            PrintType::Json => {
+                json_items.extend(users.into_iter().map(|(expire_at, user)| {
+                    json!({
+                        "customerId": customer.id,
+                        "companyName": customer.company_name,
+                        "userId": user.id,
+                        "userName": user.user_name,
+                        "email": user.email,
+                        "expireAt": expire_at.to_rfc3339(),
+                    })
+                }));
+            }
+            PrintType::Pretty => {
+                println!(
+                    "{} (id: {}) – {} expiring user(s)",
+                    customer.company_name.bold(),
+                    customer.id,
+                    users.len()
+                );
+                for (expire_at, user) in users {
+                    println!(
+                        "  user name: {} | email: {} | expires: {} ({} days)",
+                        user.user_name,
+                        user.email.as_deref().unwrap_or("N/A"),
+                        format_timestamp(&expire_at.to_rfc3339()),
+                        (expire_at.with_timezone(&Utc) - now).num_days()
+                    );
+                }
+            }
+        }
+    }
+
+    if let PrintType::Json = print_type {
+        print_json(json!({
+            "customers": customers.len(),
+            "expiringUsers": expiring,
+            "failed": failed,
+            "items": json_items,
+        }));
+    }
+
+    if let PrintType::Pretty = print_type {
+        println!(
+            "customers: {} | expiring users: {} | failed: {}",
+            customers.len(),
+            expiring,
+            failed
+        );
+    }
+
+    if failed > 0 || is_cancelled() {
+        super::exit(1)
+    }
+}
+
+/// Lists per customer how many users have client-side encryption enabled.
+pub async fn report_encryption(
+    provider: Dracoon<Provisioning>,
+    filter: Option<String>,
+    only_enabled: bool,
+    print_type: PrintType,
+) {
+    let customers = fetch_all_customers(&provider, filter, Some("companyName:asc".into())).await;
+
+    install_cancel_handler();
+
+    let mut rows = Vec::new();
+    let mut failed = 0;
+
+    for customer in &customers {
+        if is_cancelled() {
+            eprintln!(
+                "{} Interrupted – remaining customers not scanned.",
+                "Warning".black().on_yellow()
+            );
+            break;
+        }
+
+        match fetch_all_users(&provider, customer.id).await {
+            Ok(users) => {
+                let enabled = users
+                    .iter()
+                    .filter(|user| user.is_encryption_enabled.unwrap_or_default())
+                    .count();
+                rows.push((customer, users.len(), enabled));
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!(
+                    "{} Could not get users of customer {}: {}",
+                    "Error".white().on_red(),
+                    customer.id,
+                    dracoon_error_to_string(e)
+                );
+            }
+        }
+    }
+
+    if only_enabled {
+        rows.retain(|(_, _, enabled)| *enabled > 0);
+    }
+
+    // tenants using encryption the most first
+    rows.sort_by(|(_, _, a), (_, _, b)| b.cmp(a));
+
+    if let PrintType::Csv = print_type {
+        print_csv_header(ENCRYPTION_CSV_HEADER);
+    }
+
+    let mut json_items = Vec::new();
+
+    for (customer, users, enabled) in &rows {
+        let share = match users {
+            0 => 0.0,
+            users => *enabled as f64 / *users as f64 * 100.0,
+        };
+
+        match print_type {
+            PrintType::Csv => println!(
+                "{},{},{},{},{:.1}",
+                customer.id, customer.company_name, users, enabled, share
+            ),
+            PrintType::Pretty => println!(
+                "company: {} | id: {} | users: {} | encryption enabled: {} ({:.1}%)",
+                customer.company_name, customer.id, users, enabled, share
+            ),
+            PrintType::Json => json_items.push(json!({
+                "customerId": customer.id,
+                "companyName": customer.company_name,
+                "users": users,
+                "encryptionEnabled": enabled,
+                "percent": (share * 10.0).round() / 10.0,
+            })),
+        }
+    }
+
+    if let PrintType::Json = print_type {
+        print_json(json!({
+            "customers": customers.len(),
+            "failed": failed,
+            "items": json_items,
+        }));
+    }
+
+    if let PrintType::Pretty = print_type {
+        println!(
+            "customers: {} | using encryption: {} | failed: {}",
+            customers.len(),
+            rows.iter().filter(|(_, _, enabled)| *enabled > 0).count(),
+            failed
+        );
+    }
+
+    if failed > 0 || is_cancelled() {
+        super::exit(1)
+    }
+}
+
+/// Customers and quota max of a contract type (new within a month or cumulative)
+#[derive(Clone, Copy, Default)]
+struct Growth {
+    customers: u64,
+    quota_max: u64,
+}
+
+impl Growth {
+    fn add(&mut self, quota_max: u64) {
+        self.customers += 1;
+        self.quota_max += quota_max;
+    }
+}
+
+/// Lists customer count and quota max growth per month and contract type since a date.
+/// Derived from the creation date of the current customers: deleted customers and later
+/// changes (quota, contract type) are not reflected.
+pub async fn report_growth(
+    provider: Dracoon<Provisioning>,
+    since: NaiveDate,
+    filter: Option<String>,
+    print_type: PrintType,
+) {
+    let customers = fetch_all_customers(&provider, filter, Some("id:asc".into())).await;
+
+    let since = since.with_day(1).unwrap_or(since);
+    let mut totals: BTreeMap<&str, Growth> = BTreeMap::new();
+    let mut new_per_month: BTreeMap<(NaiveDate, &str), Growth> = BTreeMap::new();
+
+    for customer in &customers {
+        let Ok(created_at) = DateTime::parse_from_rfc3339(&customer.created_at) else {
+            continue;
+        };
+        let created = created_at.date_naive();
+        let contract_type = customer.customer_contract_type.as_str();
+
+        // contract types with new customers only start with a total of 0
+        let total = totals.entry(contract_type).or_default();
+        if created < since {
+            total.add(customer.quota_max);
+        } else {
+            let month = created.with_day(1).unwrap_or(created);
+            new_per_month
+                .entry((month, contract_type))
+                .or_default()
+                .add(customer.quota_max);
+        }
+    }
+
+    if let PrintType::Csv = print_type {
+        print_csv_header(GROWTH_CSV_HEADER);
+    }
+
+    let today = Utc::now().date_naive();
+    let mut json_items = Vec::new();
+    let mut month = since;
+
+    while month <= today {
+        let mut month_total = Growth::default();
+
+        for (contract_type, total) in totals.iter_mut() {
+            let new = new_per_month
+                .get(&(month, *contract_type))
+                .copied()
+                .unwrap_or_default();
+            total.customers += new.customers;
+            total.quota_max += new.quota_max;
+            month_total.customers += new.customers;
+
+            let label = month.format("%Y-%m");
+            match print_type {
+                PrintType::Csv => println!(
+                    "{},{},{},{},{},{}",
+                    label,
+                    contract_type,
+                    new.customers,
+                    total.customers,
+                    new.quota_max,
+                    total.quota_max
+                ),
+                PrintType::Json => json_items.push(json!({
+                    "month": label.to_string(),
+                    "contractType": contract_type,
+                    "newCustomers": new.customers,
+                    "customers": total.customers,
+                    "newQuotaMax": new.quota_max,
+                    "quotaMax": total.quota_max,
+                })),
+                PrintType::Pretty => (),
+            }
+        }
+
+        if let PrintType::Pretty = print_type {
+            println!(
+                "{} | new customers: {} | {}",
+                month.format("%Y-%m").to_string().bold(),
+                month_total.customers,
+                totals
+                    .iter()
+                    .map(|(contract_type, total)| format!(
+                        "{}: {} (quota max: {})",
+                        contract_type,
+                        total.customers,
+                        format_number(total.quota_max)
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            );
+        }
+
+        let Some(next) = month.checked_add_months(Months::new(1)) else {
+            break;
+        };
+        month = next;
+    }
+
+    if let PrintType::Json = print_type {
+        print_json(json!({
+            "since": since.to_string(),
+            "customers": customers.len(),
+            "items": json_items,
+        }));
+    }
+}
+
+/// Fetches all customers as unmodified JSON (all pages) – dco3 doesn't model all customer fields
+/// (e.g. providerCustomerId).
+async fn fetch_all_customers_json(
+    provider: &Dracoon<Provisioning>,
+    filter: Option<String>,
+) -> Result<Vec<Value>, String> {
+    let sort = Some("id:asc".to_string());
+    let mut customers = Vec::new();
+
+    loop {
+        let params = raw_params(&filter, &sort, Some(customers.len() as u64), None);
+        let mut page = get_json(provider, "", &params).await?;
+        let total = page["range"]["total"].as_u64().unwrap_or_default();
+
+        let items = match page["items"].take() {
+            Value::Array(items) => items,
+            _ => Vec::new(),
+        };
+        if items.is_empty() {
+            break;
+        }
+        customers.extend(items);
+
+        if customers.len() as u64 >= total || is_cancelled() {
+            break;
+        }
+    }
+
+    Ok(customers)
+}
+
+/// Lists groups of customers sharing the same company name (case-insensitive, trimmed) or
+/// provider customer id – exits with 1 if any are found. Customers without a value are ignored.
+pub async fn report_duplicates(
+    provider: Dracoon<Provisioning>,
+    key: String,
+    filter: Option<String>,
+    print_type: PrintType,
+) {
+    install_cancel_handler();
+
+    let customers = match fetch_all_customers_json(&provider, filter).await {
+        Ok(customers) => customers,
+        Err(e) => {
+            println!(
+                "{} Could not list customers: {}",
+                "Error".white().on_red(),
+                e
+            );
+            super::exit(1)
+        }
+    };
+
+    if is_cancelled() {
+        eprintln!(
+            "{} Interrupted – duplicates not reported.",
+            "Warning".black().on_yellow()
+        );
+        super::exit(1)
+    }
+
+    let mut groups: BTreeMap<String, Vec<&Value>> = BTreeMap::new();
+    for customer in &customers {
+        let value = customer[key.as_str()].as_str().unwrap_or_default().trim();
+        let value = match key.as_str() {
+            "companyName" => value.to_lowercase(),
+            _ => value.to_string(),
+        };
+        if !value.is_empty() {
+            groups.entry(value).or_default().push(customer);
+        }
+    }
+
+    let mut duplicates: Vec<_> = groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .collect();
+
+    // largest groups first
+    duplicates.sort_by_key(|(_, members)| std::cmp::Reverse(members.len()));
+
+    if let PrintType::Csv = print_type {
+        print_csv_header(DUPLICATES_CSV_HEADER);
+    }
+
+    let mut json_items = Vec::new();
+
+    for (value, members) in &duplicates {
+        if let PrintType::Pretty = print_type {
+            println!(
+                "{} | customers: {}",
+                format!("{}: {}", key, value).bold(),
+                members.len()
+            );
+        }
+
+        for customer in members {
+            let id = customer["id"].as_u64().unwrap_or_default();
+            let company_name = customer["companyName"].as_str().unwrap_or_default();
+            let provider_customer_id = customer["providerCustomerId"].as_str().unwrap_or_default();
+            let created_at = customer["createdAt"].as_str().unwrap_or_default();
+
+            match print_type {
+                PrintType::Csv => println!(
+                    "{},{},{},{},{}",
+                    value,
+                    id,
+                    company_name,
+                    provider_customer_id,
+                    format_timestamp(created_at)
+                ),
+                PrintType::Pretty => println!(
+                    "  id: {} | company: {} | provider customer id: {} | created: {}",
+                    id,
+                    company_name,
+                    match provider_customer_id {
+                        "" => "-",
+                        id => id,
+                    },
+                    format_timestamp(created_at)
+                ),
+                PrintType::Json => {}
+            }
+        }
+
+        if let PrintType::Json = print_type {
+            json_items.push(json!({
+                "key": value,
+                "customers": members
+                    .iter()
+                    .map(|customer| json!({
+                        "id": customer["id"],
+                        "companyName": customer["companyName"],
+                        "providerCustomerId": customer["providerCustomerId"],
+                        "createdAt": customer["createdAt"],
+                    }))
+                    .collect::<Vec<_>>(),
+            }));
+        }
+    }
+
+    match print_type {
+        PrintType::Json => print_json(json!({
+            "customers": customers.len(),
+            "key": key,
+            "items": json_items,
+        })),
+        PrintType::Pretty => println!(
+            "customers: {} | duplicate groups: {}",
+            customers.len(),
+            duplicates.len()
+        ),
+        PrintType::Csv => {}
+    }
+
+    if !duplicates.is_empty() {
+        super::exit(1)
+    }
+}
@@ -0,0 +1,161 @@
+//! Typed results of the customer mutations (create, update, delete, set attributes) – returned by
+//! the `try_*` functions and rendered separately (see `output`). Batch commands return a
+//! `BatchReport`. Read-only commands (list, reports, ...) render their output directly.
+
+use super::ATTRIBUTE_PREFIX;
+use dco3::provisioning::{Customer, FirstAdminUser, NewCustomerResponse, UpdateCustomerResponse};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Customer created by `try_create_customer` – rendered by `print_created_customer`.
+pub struct CreatedCustomer {
+    pub id: u64,
+    pub company_name: String,
+    pub customer_contract_type: String,
+    pub quota_max: u64,
+    pub user_max: u64,
+    pub provider_customer_id: Option<String>,
+    pub first_admin_user: FirstAdminUser,
+}
+
+impl From<NewCustomerResponse> for CreatedCustomer {
+    fn from(customer: NewCustomerResponse) -> Self {
+        Self {
+            id: customer.id,
+            company_name: customer.company_name,
+            customer_contract_type: customer.customer_contract_type,
+            quota_max: customer.quota_max,
+            user_max: customer.user_max,
+            provider_customer_id: customer.provider_customer_id,
+            first_admin_user: customer.first_admin_user,
+        }
+    }
+}
+
+impl CreatedCustomer {
+    /// Values passed to the on_create hook.
+    pub(super) fn hook_values(&self) -> BTreeMap<&'static str, String> {
+        BTreeMap::from([
+            ("id", self.id.to_string()),
+            ("company_name", self.company_name.clone()),
+            ("contract_type", self.customer_contract_type.clone()),
+            ("quota_max", self.quota_max.to_string()),
+            ("user_max", self.user_max.to_string()),
+        ])
+    }
+}
+
+/// Changed field of a customer (API name) with the value before and after an update
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: Value,
+    pub after: Value,
+}
+
+/// Customer updated by `try_update_customer` – rendered by `print_update_outcome`.
+pub struct UpdateOutcome {
+    pub id: u64,
+    pub company_name: String,
+    pub customer_contract_type: String,
+    pub quota_max: u64,
+    pub user_max: u64,
+    pub customer_uuid: String,
+    /// changed fields – error if the customer could not be fetched before the update
+    pub changes: Result<Vec<FieldChange>, String>,
+}
+
+impl UpdateOutcome {
+    pub(super) fn new(
+        customer: UpdateCustomerResponse,
+        changes: Result<Vec<FieldChange>, String>,
+    ) -> Self {
+        Self {
+            id: customer.id,
+            company_name: customer.company_name,
+            customer_contract_type: customer.customer_contract_type,
+            quota_max: customer.quota_max,
+            user_max: customer.user_max,
+            customer_uuid: customer.customer_uuid,
+            changes,
+        }
+    }
+
+    /// Values passed to the on_update hook.
+    pub(super) fn hook_values(&self) -> BTreeMap<&'static str, String> {
+        BTreeMap::from([
+            ("id", self.id.to_string()),
+            ("company_name", self.company_name.clone()),
+            ("contract_type", self.customer_contract_type.clone()),
+            ("quota_max", self.quota_max.to_string()),
+            ("user_max", self.user_max.to_string()),
+            ("changes", self.changes_json().to_string()),
+        ])
+    }
+
+    /// Changed fields as JSON (`null` if unknown).
+    pub fn changes_json(&self) -> Value {
+        match &self.changes {
+            Ok(changes) => changes
+                .iter()
+                .map(|change| {
+                    json!({ "field": change.field, "before": change.before, "after": change.after })
+                })
+                .collect(),
+            Err(_) => Value::Null,
+        }
+    }
+}
+
+/// Customer deleted by `try_delete_customer` – rendered by `print_deleted_customer`.
+pub struct DeletedCustomer {
+    pub id: u64,
+}
+
+impl DeletedCustomer {
+    /// Values passed to the on_delete hook.
+    pub(super) fn hook_values(&self) -> BTreeMap<&'static str, String> {
+        BTreeMap::from([("id", self.id.to_string())])
+    }
+}
+
+/// Attributes set by `try_update_customer_attributes` – rendered by `print_updated_attributes`.
+pub struct UpdatedAttributes {
+    pub id: u64,
+    pub company_name: String,
+    pub customer_contract_type: String,
+    pub quota_max: u64,
+    pub user_max: u64,
+    /// set attributes (key, value)
+    pub attributes: Vec<(String, String)>,
+}
+
+impl UpdatedAttributes {
+    pub(super) fn new(customer: Customer, attributes: Vec<(String, String)>) -> Self {
+        Self {
+            id: customer.id,
+            company_name: customer.company_name,
+            customer_contract_type: customer.customer_contract_type,
+            quota_max: customer.quota_max,
+            user_max: customer.user_max,
+            attributes,
+        }
+    }
+
+    /// Values passed to the on_update hook – the set attributes as changes (`attribute:<key>`).
+    pub(super) fn hook_values(&self) -> BTreeMap<&'static str, String> {
+        let changes: Value = self
+            .attributes
+            .iter()
+            .map(|(key, value)| json!({ "field": format!("{}{}", ATTRIBUTE_PREFIX, key), "after": value }))
+            .collect();
+
+        BTreeMap::from([
+            ("id", self.id.to_string()),
+            ("company_name", self.company_name.clone()),
+            ("contract_type", self.customer_contract_type.clone()),
+            ("quota_max", self.quota_max.to_string()),
+            ("user_max", self.user_max.to_string()),
+            ("changes", changes.to_string()),
+        ])
+    }
+}
@@ -0,0 +1,10 @@
+use std::time::Duration;
+
+// max. delay between retries of a single request / job
+const MAX_BACKOFF_SECS: u64 = 60;
+
+/// Returns the delay before the next attempt: exponential backoff (1s, 2s, 4s, ...) capped at 60s.
+pub(super) fn backoff_delay(attempt: u32) -> Duration {
+    Duration::from_secs(2u64.saturating_pow(attempt.saturating_sub(1)))
+        .min(Duration::from_secs(MAX_BACKOFF_SECS))
+}
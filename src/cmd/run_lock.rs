@@ -0,0 +1,93 @@
+use crate::state;
+use chrono::Utc;
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use serde_json::json;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// directory (in the state dir) holding the per-host batch lock files
+const LOCKS_DIR: &str = "locks";
+
+static NO_BATCH_LOCK: AtomicBool = AtomicBool::new(false);
+
+/// Disables the batch lock (--no-lock) – mutating batch runs no longer check for concurrent runs.
+pub fn disable_batch_lock() {
+    NO_BATCH_LOCK.store(true, Ordering::SeqCst);
+}
+
+/// Advisory OS-level lock on a DRACOON host held for a mutating batch run – a second batch run
+/// against the same host aborts instead of applying conflicting changes. Released on drop (and on
+/// exit, even if the process is killed).
+pub struct BatchLock {
+    _file: Option<fs::File>,
+}
+
+/// Acquires the batch lock of the provider's host or exits with the owner of the running batch.
+/// Returns a no-op lock with --no-lock or if the lock file can't be created.
+pub(super) fn acquire_batch_lock(provider: &Dracoon<Provisioning>, operation: &str) -> BatchLock {
+    if NO_BATCH_LOCK.load(Ordering::SeqCst) {
+        return BatchLock { _file: None };
+    }
+
+    let host = provider
+        .build_api_url("/")
+        .host_str()
+        .unwrap_or_default()
+        .to_string();
+
+    let Ok(dir) = state::state_dir().map(|dir| dir.join(LOCKS_DIR)) else {
+        return BatchLock { _file: None };
+    };
+    let path = dir.join(format!("{}.lock", host));
+
+    let file = fs::create_dir_all(&dir).and_then(|_| {
+        OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&path)
+    });
+    let Ok(mut file) = file else {
+        eprintln!(
+            "{} Could not create lock file {} – running without batch lock.",
+            "Warning".black().on_yellow(),
+            path.display()
+        );
+        return BatchLock { _file: None };
+    };
+
+    if file.try_lock().is_err() {
+        // the owner may not be readable while locked (Windows)
+        let owner = fs::read_to_string(&path)
+            .ok()
+            .filter(|owner| !owner.trim().is_empty())
+            .unwrap_or_else(|| "unknown".into());
+        println!(
+            "{} Another batch run against {} is in progress (owner: {}) – pass --no-lock to run anyway.",
+            "Error".white().on_red(),
+            host,
+            owner.trim()
+        );
+        super::exit(1)
+    }
+
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    let owner = json!({
+        "pid": std::process::id(),
+        "user": user,
+        "operation": operation,
+        "startedAt": Utc::now().to_rfc3339(),
+    });
+
+    // the owner is informational only – the OS lock is what counts
+    let _ = file.set_len(0).and_then(|_| writeln!(file, "{}", owner));
+
+    BatchLock { _file: Some(file) }
+}
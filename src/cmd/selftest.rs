@@ -0,0 +1,236 @@
+use super::{
+    confirm_mutation, dracoon_error_to_string, handle_errors, init_provisioning, json_query_active,
+    print_json, ClientOptions, ContractType, NewCustomerRequestDco3,
+};
+use colored::*;
+use dco3::{
+    auth::Provisioning,
+    provisioning::{CustomerAttributes, FirstAdminUser, UpdateCustomerRequest},
+    users::{AuthMethod, UserAuthData},
+    CustomerProvisioning, Dracoon, DracoonClientError, ListAllParams,
+};
+use serde_json::json;
+
+// throwaway customer (smallest possible demo customer)
+const SELFTEST_QUOTA_MAX: u64 = 1_000_000_000;
+const SELFTEST_ATTRIBUTE: (&str, &str) = ("dcprov_selftest", "ok");
+
+/// Result of a single selftest operation
+enum Check {
+    Allowed,
+    Denied(String),
+    Failed(String),
+    Skipped,
+}
+
+impl Check {
+    fn from_result<T>(result: Result<T, DracoonClientError>) -> Self {
+        match result {
+            Ok(_) => Check::Allowed,
+            Err(err) if err.is_unauthorized() || err.is_forbidden() => {
+                Check::Denied(dracoon_error_to_string(err))
+            }
+            Err(err) => Check::Failed(dracoon_error_to_string(err)),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Check::Allowed => "allowed",
+            Check::Denied(_) => "denied",
+            Check::Failed(_) => "failed",
+            Check::Skipped => "skipped",
+        }
+    }
+
+    fn detail(&self) -> Option<&str> {
+        match self {
+            Check::Denied(detail) | Check::Failed(detail) => Some(detail),
+            _ => None,
+        }
+    }
+}
+
+fn selftest_customer(name: &str) -> NewCustomerRequestDco3 {
+    let auth_data = UserAuthData::builder(AuthMethod::Basic)
+        .with_must_change_password(true)
+        .build();
+
+    let first_admin_user = FirstAdminUser {
+        first_name: "dcprov".into(),
+        last_name: "selftest".into(),
+        user_name: Some(name.into()),
+        email: Some(format!("{}@example.com", name)),
+        auth_data: Some(auth_data),
+        notify_user: Some(false),
+        receiver_language: None,
+        phone: None,
+    };
+
+    NewCustomerRequestDco3::builder(
+        ContractType::Demo.as_str(),
+        SELFTEST_QUOTA_MAX,
+        1,
+        first_admin_user,
+    )
+    .with_company_name(name)
+    .build()
+}
+
+/// Runs all operations against a throwaway customer – returns the checks in order.
+/// Calls the API directly (not via the try_* helpers): hooks don't fire for the throwaway customer.
+async fn run_checks(provider: &Dracoon<Provisioning>, name: &str) -> Vec<(&'static str, Check)> {
+    let mut checks = Vec::new();
+
+    let params = ListAllParams::builder().with_limit(1).build();
+    checks.push((
+        "list",
+        Check::from_result(provider.get_customers(Some(params)).await),
+    ));
+
+    let customer_id = match provider.create_customer(selftest_customer(name)).await {
+        Ok(customer) => {
+            checks.push(("create", Check::Allowed));
+            customer.id
+        }
+        Err(err) => {
+            checks.push(("create", Check::from_result::<()>(Err(err))));
+            for operation in [
+                "get",
+                "update",
+                "set-attributes",
+                "get-attributes",
+                "get-users",
+                "delete",
+            ] {
+                checks.push((operation, Check::Skipped));
+            }
+            return checks;
+        }
+    };
+
+    checks.push((
+        "get",
+        Check::from_result(provider.get_customer(customer_id, None).await),
+    ));
+
+    let update = UpdateCustomerRequest::builder().with_user_max(2).build();
+    checks.push((
+        "update",
+        Check::from_result(provider.update_customer(customer_id, update).await),
+    ));
+
+    let mut attribs = CustomerAttributes::new();
+    attribs.add_attribute(SELFTEST_ATTRIBUTE.0, SELFTEST_ATTRIBUTE.1);
+    checks.push((
+        "set-attributes",
+        Check::from_result(
+            provider
+                .update_customer_attributes(customer_id, attribs)
+                .await,
+        ),
+    ));
+
+    checks.push((
+        "get-attributes",
+        Check::from_result(provider.get_customer_attributes(customer_id, None).await),
+    ));
+
+    checks.push((
+        "get-users",
+        Check::from_result(provider.get_customer_users(customer_id, None).await),
+    ));
+
+    let delete = Check::from_result(provider.delete_customer(customer_id).await);
+    if !matches!(delete, Check::Allowed) {
+        eprintln!(
+            "{} Could not delete the selftest customer {} (id: {}) – please delete it manually.",
+            "Warning".black().on_yellow(),
+            name,
+            customer_id
+        );
+    }
+    checks.push(("delete", delete));
+
+    checks
+}
+
+/// Creates a throwaway demo customer, runs get / update / attributes / users operations on it,
+/// deletes it and reports which operations the token is allowed to perform.
+/// Exits with 1 if any operation is denied or fails.
+pub async fn selftest(
+    url_or_alias: &str,
+    token: Option<String>,
+    client_opts: &ClientOptions,
+    yes: bool,
+    json: bool,
+) {
+    confirm_mutation(url_or_alias);
+
+    let provider = match init_provisioning(url_or_alias, token, client_opts).await {
+        Ok(provider) => provider,
+        Err(ref e) => handle_errors(e),
+    };
+
+    let name = format!(
+        "dcprov-selftest-{}",
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+
+    if !yes {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Create (and delete) the demo customer {} on {}?",
+                name, url_or_alias
+            ))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+
+        if !confirmed {
+            println!("{} Aborted.", "Error".white().on_red());
+            super::exit(1)
+        }
+    }
+
+    let checks = run_checks(&provider, &name).await;
+    let passed = checks
+        .iter()
+        .all(|(_, check)| matches!(check, Check::Allowed));
+
+    if json || json_query_active() {
+        let operations: Vec<_> = checks
+            .iter()
+            .map(|(operation, check)| {
+                json!({
+                    "operation": operation,
+                    "result": check.as_str(),
+                    "detail": check.detail(),
+                })
+            })
+            .collect();
+        print_json(json!({
+            "customer": name,
+            "passed": passed,
+            "operations": operations,
+        }));
+    } else {
+        println!("Selftest customer: {}", name);
+        for (operation, check) in &checks {
+            let status = match check {
+                Check::Allowed => " OK ".black().on_green(),
+                Check::Denied(_) => "DENY".white().on_red(),
+                Check::Failed(_) => "FAIL".white().on_red(),
+                Check::Skipped => "SKIP".black().on_yellow(),
+            };
+            match check.detail() {
+                Some(detail) => println!("{} {}: {}", status, operation, detail),
+                None => println!("{} {}: {}", status, operation, check.as_str()),
+            }
+        }
+    }
+
+    if !passed {
+        super::exit(1)
+    }
+}
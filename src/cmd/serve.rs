@@ -0,0 +1,177 @@
+use super::{dracoon_error_to_string, try_create_customer, NewCustomerRequest};
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use hyper::{
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde_json::{json, Value};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+// endpoint to provision new customers
+const TENANTS_PATH: &str = "/tenants";
+
+struct ServeState {
+    provider: Dracoon<Provisioning>,
+    auth_token: String,
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("valid response")
+}
+
+// compares without short-circuiting to not leak matching prefixes via timing
+pub(super) fn token_matches(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn is_authorized(req: &Request<Body>, auth_token: &str) -> bool {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token_matches(token, auth_token))
+        .unwrap_or(false)
+}
+
+/// Returns a list of validation errors for a new customer request.
+fn validate_new_customer(customer: &NewCustomerRequest) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if customer.quota_max == 0 {
+        errors.push("quotaMax must be positive".to_string());
+    }
+    if customer.user_max == 0 {
+        errors.push("userMax must be positive".to_string());
+    }
+    if customer.first_admin_user.first_name.trim().is_empty() {
+        errors.push("firstAdminUser.firstName is required".to_string());
+    }
+    if customer.first_admin_user.last_name.trim().is_empty() {
+        errors.push("firstAdminUser.lastName is required".to_string());
+    }
+    if customer.first_admin_user.email.is_none() && customer.first_admin_user.user_name.is_none() {
+        errors.push("firstAdminUser.email or firstAdminUser.userName is required".to_string());
+    }
+
+    errors
+}
+
+async fn create_tenant(state: &ServeState, req: Request<Body>) -> Response<Body> {
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) => body,
+        Err(e) => return json_response(StatusCode::BAD_REQUEST, json!({ "error": e.to_string() })),
+    };
+
+    let new_customer = match serde_json::from_slice::<NewCustomerRequest>(&body) {
+        Ok(customer) => customer,
+        Err(e) => return json_response(StatusCode::BAD_REQUEST, json!({ "error": e.to_string() })),
+    };
+
+    let errors = validate_new_customer(&new_customer);
+    if !errors.is_empty() {
+        return json_response(StatusCode::BAD_REQUEST, json!({ "errors": errors }));
+    }
+
+    let company_name = new_customer.company_name.clone().unwrap_or_default();
+
+    match try_create_customer(&state.provider, new_customer.into()).await {
+        Ok(customer) => {
+            println!(
+                "{}Created customer {} with id {}",
+                "Success ".green(),
+                customer.company_name,
+                customer.id
+            );
+            json_response(
+                StatusCode::CREATED,
+                json!({ "id": customer.id, "companyName": customer.company_name }),
+            )
+        }
+        Err(e) => {
+            let status = match &e {
+                dco3::DracoonClientError::Http(err) => {
+                    StatusCode::from_u16(err.code() as u16).unwrap_or(StatusCode::BAD_GATEWAY)
+                }
+                _ => StatusCode::BAD_GATEWAY,
+            };
+            let error = dracoon_error_to_string(e);
+            println!(
+                "{} Could not create customer {}: {}",
+                "Error".white().on_red(),
+                company_name,
+                error
+            );
+            json_response(status, json!({ "error": error }))
+        }
+    }
+}
+
+async fn handle_request(
+    state: Arc<ServeState>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if !is_authorized(&req, &state.auth_token) {
+        return Ok(json_response(
+            StatusCode::UNAUTHORIZED,
+            json!({ "error": "unauthorized" }),
+        ));
+    }
+
+    let res = match (req.method(), req.uri().path()) {
+        (&Method::POST, TENANTS_PATH) => create_tenant(&state, req).await,
+        (_, TENANTS_PATH) => json_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            json!({ "error": "method not allowed" }),
+        ),
+        _ => json_response(StatusCode::NOT_FOUND, json!({ "error": "not found" })),
+    };
+
+    Ok(res)
+}
+
+/// Serves a minimal REST API (`POST /tenants`) creating customers with the configured DRACOON client.
+/// Requests need to pass the auth token as bearer token.
+pub async fn serve(provider: Dracoon<Provisioning>, port: u16, auth_token: String) {
+    let state = Arc::new(ServeState {
+        provider,
+        auth_token,
+    });
+
+    let make_svc = make_service_fn(move |_| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle_request(state.clone(), req))) }
+    });
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+    let server = match Server::try_bind(&addr) {
+        Ok(server) => server.serve(make_svc),
+        Err(e) => {
+            println!(
+                "{} Could not bind to port {}: {}",
+                "Error".white().on_red(),
+                port,
+                e
+            );
+            super::exit(1)
+        }
+    };
+
+    println!("Listening on {} (POST {})", addr, TENANTS_PATH);
+
+    if let Err(e) = server.await {
+        println!("{} Server error: {}", "Error".white().on_red(), e);
+        super::exit(1)
+    }
+}
@@ -0,0 +1,227 @@
+use super::http_client;
+use crate::config;
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use colored::*;
+use reqwest::Url;
+use ring::{
+    rand::SystemRandom,
+    signature::{RsaKeyPair, RSA_PKCS1_SHA256},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::Read;
+
+const SHEETS_API_URL: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+const GOOGLE_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+
+// lifetime of the signed assertion (max. allowed by Google)
+const ASSERTION_TTL_SECS: i64 = 3600;
+
+/// JSON key file of a Google service account (only the fields needed for the JWT grant)
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    token_uri: Option<String>,
+}
+
+impl ServiceAccountKey {
+    fn load(path: &str) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        serde_json::from_str(&raw).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    fn token_uri(&self) -> &str {
+        self.token_uri.as_deref().unwrap_or(GOOGLE_TOKEN_URI)
+    }
+
+    /// Returns the signed JWT (RS256) exchanged for an access token.
+    fn assertion(&self) -> Result<String, String> {
+        let der: String = self
+            .private_key
+            .lines()
+            .filter(|line| !line.starts_with("-----"))
+            .collect();
+        let der = STANDARD
+            .decode(der.trim())
+            .map_err(|e| format!("invalid private key: {}", e))?;
+        let key_pair =
+            RsaKeyPair::from_pkcs8(&der).map_err(|e| format!("invalid private key: {}", e))?;
+
+        let now = chrono::Utc::now().timestamp();
+        let header = json!({ "alg": "RS256", "typ": "JWT" });
+        let claims = json!({
+            "iss": self.client_email,
+            "scope": SHEETS_SCOPE,
+            "aud": self.token_uri(),
+            "iat": now,
+            "exp": now + ASSERTION_TTL_SECS,
+        });
+        let message = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+
+        let mut signature = vec![0; key_pair.public().modulus_len()];
+        key_pair
+            .sign(
+                &RSA_PKCS1_SHA256,
+                &SystemRandom::new(),
+                message.as_bytes(),
+                &mut signature,
+            )
+            .map_err(|_| "could not sign the token request".to_string())?;
+
+        Ok(format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature)))
+    }
+
+    async fn access_token(&self) -> Result<String, String> {
+        let response = http_client()
+            .post(self.token_uri())
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", &self.assertion()?),
+            ])
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let body: Value = checked_json(response).await?;
+        body["access_token"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| "no access token in response".to_string())
+    }
+}
+
+/// Returns the JSON body of a Google API response or the error message of a failed request.
+async fn checked_json(response: reqwest::Response) -> Result<Value, String> {
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+
+    if status.is_success() {
+        return Ok(body);
+    }
+
+    let message = body["error"]["message"]
+        .as_str()
+        .or(body["error_description"].as_str())
+        .unwrap_or_default();
+    Err(format!("{} {}", status, message).trim_end().to_string())
+}
+
+/// Reads all CSV rows (header included) from a file or stdin.
+fn read_rows(input: Option<&str>) -> Result<Vec<Vec<String>>, String> {
+    let mut builder = csv::ReaderBuilder::new();
+    builder.has_headers(false).flexible(true);
+
+    let reader: Box<dyn Read> = match input {
+        Some(path) => Box::new(std::fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?),
+        None => Box::new(std::io::stdin()),
+    };
+
+    builder
+        .from_reader(reader)
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(String::from).collect())
+                .map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+/// Returns the values url of a range (`.../values/{range}` plus an optional `:action`).
+fn values_url(spreadsheet_id: &str, range: &str, action: &str) -> Url {
+    let mut url = Url::parse(SHEETS_API_URL).expect("valid sheets url");
+    url.path_segments_mut()
+        .expect("base url")
+        .push(spreadsheet_id)
+        .push("values")
+        .push(&format!("{}{}", range, action));
+    url
+}
+
+/// Replaces the content of a sheet with the rows (cleared first, so no stale rows remain).
+async fn replace_sheet(
+    token: &str,
+    spreadsheet_id: &str,
+    sheet: &str,
+    rows: &[Vec<String>],
+) -> Result<(), String> {
+    let range = format!("'{}'", sheet.replace('\'', "''"));
+    let client = http_client();
+
+    let response = client
+        .post(values_url(spreadsheet_id, &range, ":clear"))
+        .bearer_auth(token)
+        .json(&json!({}))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    checked_json(response).await?;
+
+    let mut url = values_url(spreadsheet_id, &format!("{}!A1", range), "");
+    // numbers and dates are parsed as if typed in – sums and filters work in the sheet
+    url.query_pairs_mut()
+        .append_pair("valueInputOption", "USER_ENTERED");
+
+    let response = client
+        .put(url)
+        .bearer_auth(token)
+        .json(&json!({ "range": format!("{}!A1", range), "values": rows }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    checked_json(response).await?;
+
+    Ok(())
+}
+
+fn fail(msg: String) -> ! {
+    println!("{} {}", "Error".white().on_red(), msg);
+    super::exit(1)
+}
+
+/// Pushes CSV output (e.g. of `list --csv` or a report) from a file or stdin to a sheet of a
+/// Google spreadsheet – the sheet is replaced. Authenticates with the service account of the
+/// [sheets] config. Exits on error.
+pub async fn push_to_sheet(spreadsheet_id: Option<String>, sheet: String, input: Option<String>) {
+    let sheets = config::load_config().unwrap_or_default().sheets;
+    let Some(spreadsheet_id) = spreadsheet_id.or(sheets.spreadsheet_id) else {
+        fail("No spreadsheet – pass the spreadsheet id or set spreadsheet_id in [sheets].".into())
+    };
+    let Some(credentials) = sheets.credentials else {
+        fail("No service account – set credentials in [sheets] (path to the JSON key file).".into())
+    };
+
+    let rows = read_rows(input.as_deref())
+        .unwrap_or_else(|e| fail(format!("Could not read CSV input: {}", e)));
+    if rows.is_empty() {
+        fail("No rows to push (empty input).".into())
+    }
+
+    let key = ServiceAccountKey::load(&credentials)
+        .unwrap_or_else(|e| fail(format!("Could not load service account key {}", e)));
+    let token = key
+        .access_token()
+        .await
+        .unwrap_or_else(|e| fail(format!("Could not authenticate with Google: {}", e)));
+
+    if let Err(e) = replace_sheet(&token, &spreadsheet_id, &sheet, &rows).await {
+        fail(format!("Could not update sheet {}: {}", sheet, e))
+    }
+
+    println!(
+        "{}Pushed {} rows to sheet {} of spreadsheet {}",
+        "Success ".green(),
+        rows.len(),
+        sheet,
+        spreadsheet_id
+    );
+}
@@ -0,0 +1,265 @@
+use super::{
+    guard, handle_errors, otel::traceparent, resolve_token, ClientOptions, DcProv, TOKEN_ENV,
+};
+use crate::config;
+use clap::CommandFactory;
+use colored::*;
+use rustyline::{
+    completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter,
+    history::DefaultHistory, validate::Validator, Context, Editor, Helper,
+};
+use std::process::Command;
+
+// history of all shell sessions (next to the config file)
+const HISTORY_FILE: &str = "shell_history";
+
+// commands handled by the shell itself
+const EXIT_COMMANDS: [&str; 2] = ["exit", "quit"];
+
+/// Completes command names, subcommands and long flags from the CLI definition.
+struct ShellHelper {
+    cli: clap::Command,
+}
+
+impl ShellHelper {
+    fn candidates(&self, words: &[&str]) -> Vec<String> {
+        let Some((first, rest)) = words.split_first() else {
+            return self
+                .cli
+                .get_subcommands()
+                .filter(|cmd| !cmd.is_hide_set())
+                .map(|cmd| cmd.get_name().to_string())
+                .chain(EXIT_COMMANDS.map(String::from))
+                .collect();
+        };
+
+        let Some(mut cmd) = self.cli.find_subcommand(first) else {
+            return Vec::new();
+        };
+        if let Some(nested) = rest.first().and_then(|name| cmd.find_subcommand(name)) {
+            cmd = nested;
+        }
+
+        cmd.get_subcommands()
+            .map(|cmd| cmd.get_name().to_string())
+            .chain(
+                cmd.get_arguments()
+                    .filter(|arg| arg.get_id() != "url")
+                    .filter_map(|arg| arg.get_long())
+                    .map(|long| format!("--{}", long)),
+            )
+            .collect()
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |idx| idx + 1);
+        let words: Vec<_> = line[..start].split_whitespace().collect();
+        let prefix = &line[start..];
+
+        let candidates = self
+            .candidates(&words)
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(prefix))
+            .collect();
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+fn has_url_positional(cmd: &clap::Command) -> bool {
+    cmd.get_arguments()
+        .any(|arg| arg.get_id() == "url" && arg.is_positional())
+}
+
+fn has_url_option(cmd: &clap::Command) -> bool {
+    cmd.get_arguments().any(|arg| arg.get_long() == Some("url"))
+}
+
+/// Inserts the shell's url where the command expects it (e.g. `list`, `users diff`, `run --url`).
+fn inject_url(cli: &clap::Command, mut args: Vec<String>, url: &str) -> Vec<String> {
+    let Some(cmd) = args.first().and_then(|name| cli.find_subcommand(name)) else {
+        return args;
+    };
+
+    if has_url_positional(cmd) {
+        args.insert(1, url.into());
+        return args;
+    }
+
+    if let Some(nested) = args.get(1).and_then(|name| cmd.find_subcommand(name)) {
+        if has_url_positional(nested) {
+            args.insert(2, url.into());
+            return args;
+        }
+    }
+
+    if has_url_option(cmd) {
+        args.extend(["--url".into(), url.into()]);
+    }
+
+    args
+}
+
+/// Global options of the shell passed on to every command.
+fn global_args(client_opts: &ClientOptions) -> Vec<String> {
+    let mut args = vec![
+        "--keyring-service".into(),
+        client_opts.keyring_service.clone(),
+    ];
+    if client_opts.verbose {
+        args.push("--verbose".into());
+    }
+
+    args
+}
+
+/// Interactive shell for a single DRACOON url – the token is resolved (or prompted for) once and
+/// every command runs as own process with that token, so a failing command doesn't end the session.
+pub fn run_shell(
+    url_or_alias: &str,
+    token: Option<String>,
+    client_opts: &ClientOptions,
+    output_args: Vec<String>,
+) {
+    guard::print_production_banner(url_or_alias);
+
+    let url = match config::resolve_url(url_or_alias) {
+        Ok(url) => url,
+        Err(ref e) => handle_errors(e),
+    };
+    let token = match resolve_token(&url, token, client_opts) {
+        Ok(token) => token,
+        Err(ref e) => handle_errors(e),
+    };
+
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            println!(
+                "{} Could not determine dcprov executable: {}",
+                "Error".white().on_red(),
+                e
+            );
+            super::exit(1)
+        }
+    };
+
+    let cli = DcProv::command();
+    let mut editor = match Editor::<ShellHelper, DefaultHistory>::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            println!("{} Could not start shell: {}", "Error".white().on_red(), e);
+            super::exit(1)
+        }
+    };
+    editor.set_helper(Some(ShellHelper { cli: cli.clone() }));
+
+    let history = config::config_path()
+        .ok()
+        .map(|path| path.with_file_name(HISTORY_FILE));
+    if let Some(history) = &history {
+        // no history yet on first start
+        let _ = editor.load_history(history);
+    }
+
+    // Ctrl-C interrupts the running command, not the shell
+    tokio::spawn(async { while tokio::signal::ctrl_c().await.is_ok() {} });
+
+    let mut global = global_args(client_opts);
+    global.extend(output_args);
+
+    println!(
+        "dcprov shell for {} – commands run without url (e.g. 'list --all'), 'help' for help, 'exit' to quit",
+        url.bold()
+    );
+    let prompt = format!("dcprov ({})> ", url_or_alias);
+
+    loop {
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                println!("{} {}", "Error".white().on_red(), e);
+                break;
+            }
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        let Some(args) = shlex::split(line) else {
+            println!("{} Unbalanced quotes.", "Error".white().on_red());
+            continue;
+        };
+
+        match args.first().map(String::as_str) {
+            Some(cmd) if EXIT_COMMANDS.contains(&cmd) => break,
+            Some("shell") => {
+                println!("{} Already in a shell.", "Error".white().on_red());
+                continue;
+            }
+            _ => (),
+        }
+
+        let args = inject_url(&cli, args, &url);
+        if client_opts.verbose {
+            eprintln!("Running: dcprov {}", args.join(" "));
+        }
+
+        let mut cmd = Command::new(&exe);
+        if let Some(traceparent) = traceparent() {
+            cmd.env("TRACEPARENT", traceparent);
+        }
+
+        let status = cmd
+            .args(&global)
+            .args(&args)
+            .env(TOKEN_ENV, &token)
+            .status();
+
+        match status {
+            Ok(status) if !status.success() => {
+                eprintln!(
+                    "{}",
+                    format!("exit code: {}", status.code().unwrap_or(1)).dimmed()
+                )
+            }
+            Ok(_) => (),
+            Err(e) => println!("{} Could not run command: {}", "Error".white().on_red(), e),
+        }
+    }
+
+    if let Some(history) = &history {
+        if let Err(e) = editor.save_history(history) {
+            eprintln!(
+                "{} Could not save shell history: {}",
+                "Warning".black().on_yellow(),
+                e
+            );
+        }
+    }
+}
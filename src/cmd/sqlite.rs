@@ -0,0 +1,306 @@
+use super::attributes::fetch_all_attributes;
+use super::{
+    dracoon_error_to_string, fetch_all_customers, fetch_all_users, install_cancel_handler,
+    is_cancelled, redact_user,
+};
+use clap::ValueEnum;
+use colored::*;
+use dco3::{auth::Provisioning, users::UserItem, Dracoon, KeyValueEntry};
+use futures_util::{stream, StreamExt};
+use std::{
+    fmt::Write as _,
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+// databases are written via the sqlite3 CLI (no SQLite library linked into dcprov)
+const SQLITE_BIN: &str = "sqlite3";
+
+const SCHEMA: &str = "CREATE TABLE customers (
+    id INTEGER PRIMARY KEY,
+    company_name TEXT NOT NULL,
+    contract_type TEXT NOT NULL,
+    quota_max INTEGER NOT NULL,
+    quota_used INTEGER NOT NULL,
+    user_max INTEGER NOT NULL,
+    user_used INTEGER NOT NULL,
+    is_locked INTEGER,
+    created_at TEXT NOT NULL,
+    updated_at TEXT,
+    last_login_at TEXT,
+    trial_days_left INTEGER,
+    customer_uuid TEXT
+);
+CREATE TABLE users (
+    id INTEGER NOT NULL,
+    customer_id INTEGER NOT NULL REFERENCES customers(id),
+    user_name TEXT NOT NULL,
+    first_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    email TEXT,
+    phone TEXT,
+    is_locked INTEGER NOT NULL,
+    expire_at TEXT,
+    last_login_success_at TEXT,
+    is_encryption_enabled INTEGER,
+    PRIMARY KEY (customer_id, id)
+);
+CREATE TABLE attributes (
+    customer_id INTEGER NOT NULL REFERENCES customers(id),
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (customer_id, key)
+);
+";
+
+// export formats of list (--format)
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// SQLite database with the tables customers, users and attributes
+    Sqlite,
+}
+
+/// SQL literal of a value (NULL if missing).
+pub(super) trait SqlValue {
+    fn sql(&self) -> String;
+}
+
+impl SqlValue for str {
+    fn sql(&self) -> String {
+        format!("'{}'", self.replace('\'', "''"))
+    }
+}
+
+impl SqlValue for String {
+    fn sql(&self) -> String {
+        self.as_str().sql()
+    }
+}
+
+impl SqlValue for u64 {
+    fn sql(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl SqlValue for i32 {
+    fn sql(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl SqlValue for bool {
+    fn sql(&self) -> String {
+        u8::from(*self).to_string()
+    }
+}
+
+impl<T: SqlValue> SqlValue for Option<T> {
+    fn sql(&self) -> String {
+        self.as_ref().map_or("NULL".into(), SqlValue::sql)
+    }
+}
+
+fn insert(script: &mut String, table: &str, values: &[String]) {
+    let _ = writeln!(
+        script,
+        "INSERT INTO {} VALUES ({});",
+        table,
+        values.join(", ")
+    );
+}
+
+fn insert_users(script: &mut String, customer_id: u64, users: Vec<UserItem>) {
+    for user in users.into_iter().map(redact_user) {
+        insert(
+            script,
+            "users",
+            &[
+                user.id.sql(),
+                customer_id.sql(),
+                user.user_name.sql(),
+                user.first_name.sql(),
+                user.last_name.sql(),
+                user.email.sql(),
+                user.phone.sql(),
+                user.is_locked.sql(),
+                user.expire_at.sql(),
+                user.last_login_success_at.sql(),
+                user.is_encryption_enabled.sql(),
+            ],
+        );
+    }
+}
+
+fn insert_attributes(script: &mut String, customer_id: u64, attribs: Vec<KeyValueEntry>) {
+    for attrib in attribs {
+        insert(
+            script,
+            "attributes",
+            &[customer_id.sql(), attrib.key.sql(), attrib.value.sql()],
+        );
+    }
+}
+
+/// Returns true if the sqlite3 CLI can be run (checked once).
+pub(super) fn sqlite_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+
+    *AVAILABLE.get_or_init(|| {
+        Command::new(SQLITE_BIN)
+            .arg("-version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// Runs the script against a database file (created if missing) and returns its output – returns
+/// the error output of sqlite3 on failure.
+pub(super) fn run_sqlite(path: &Path, script: &str) -> Result<String, String> {
+    let mut child = Command::new(SQLITE_BIN)
+        .arg("-bail")
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run {} (is it installed?): {}", SQLITE_BIN, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(script.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+        false => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+    }
+}
+
+/// Exports all (filtered) customers with their users and attributes into a SQLite database (tables
+/// customers, users, attributes) for ad-hoc SQL analysis. Users and attributes are fetched per
+/// customer (in parallel). The file is replaced only if the export is complete – exits on error.
+/// Requires the sqlite3 CLI on `PATH` (checked before anything is fetched).
+pub async fn export_sqlite(
+    provider: Dracoon<Provisioning>,
+    filter: Option<String>,
+    sort: Option<String>,
+    output: String,
+    concurrency: usize,
+) {
+    if !sqlite_available() {
+        println!(
+            "{} {} not found – install the sqlite3 CLI (on PATH) to export with --format sqlite.",
+            "Error".white().on_red(),
+            SQLITE_BIN
+        );
+        super::exit(1)
+    }
+
+    install_cancel_handler();
+    let customers = fetch_all_customers(&provider, filter, sort).await;
+
+    let provider = &provider;
+    let details: Vec<_> = stream::iter(&customers)
+        .map(|customer| async move {
+            if is_cancelled() {
+                return Err("not processed (interrupted)".to_string());
+            }
+            let users = fetch_all_users(provider, customer.id).await;
+            let attribs = fetch_all_attributes(provider, customer.id).await;
+            match (users, attribs) {
+                (Ok(users), Ok(attribs)) => Ok((customer.id, users, attribs)),
+                (Err(e), _) | (_, Err(e)) => Err(format!(
+                    "customer {}: {}",
+                    customer.id,
+                    dracoon_error_to_string(e)
+                )),
+            }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+
+    // a partial export would silently understate users and attributes
+    if is_cancelled() {
+        eprintln!(
+            "{} Interrupted – no database written.",
+            "Warning".black().on_yellow()
+        );
+        super::exit(1)
+    }
+
+    let mut script = format!("BEGIN;\n{}", SCHEMA);
+    for customer in &customers {
+        insert(
+            &mut script,
+            "customers",
+            &[
+                customer.id.sql(),
+                customer.company_name.sql(),
+                customer.customer_contract_type.sql(),
+                customer.quota_max.sql(),
+                customer.quota_used.sql(),
+                customer.user_max.sql(),
+                customer.user_used.sql(),
+                customer.is_locked.sql(),
+                customer.created_at.sql(),
+                customer.updated_at.sql(),
+                customer.last_login_at.sql(),
+                customer.trial_days_left.sql(),
+                customer.customer_uuid.sql(),
+            ],
+        );
+    }
+
+    let mut users_count = 0;
+    for detail in details {
+        let (id, users, attribs) = match detail {
+            Ok(detail) => detail,
+            Err(e) => {
+                println!(
+                    "{} Could not fetch users / attributes of {} – no database written.",
+                    "Error".white().on_red(),
+                    e
+                );
+                super::exit(1)
+            }
+        };
+        users_count += users.len();
+        insert_users(&mut script, id, users);
+        insert_attributes(&mut script, id, attribs);
+    }
+    script.push_str("COMMIT;\n");
+
+    // written next to the target and moved into place – an existing export stays intact on failure
+    let tmp_path = format!("{}.tmp", output);
+    let _ = fs::remove_file(&tmp_path);
+    let result = run_sqlite(Path::new(&tmp_path), &script)
+        .and_then(|_| fs::rename(&tmp_path, &output).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path);
+        println!(
+            "{} Could not write {}: {}",
+            "Error".white().on_red(),
+            output,
+            e
+        );
+        super::exit(1)
+    }
+
+    println!(
+        "{}Exported {} customers and {} users to {}",
+        "Success ".green(),
+        customers.len(),
+        users_count,
+        output
+    );
+}
@@ -0,0 +1,153 @@
+use super::{
+    build_params, fetch_all_customers, format_number, handle_dracoon_errors, print_csv_header,
+    print_json, PrintType,
+};
+use colored::*;
+use dco3::{auth::Provisioning, provisioning::Customer, CustomerProvisioning, Dracoon};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+const STATS_CSV_HEADER: &str = "metric,value,estimated";
+
+/// Sums of the usage values of a set of customers
+#[derive(Default)]
+struct Totals {
+    users_used: u64,
+    users_max: u64,
+    quota_used: u64,
+    quota_max: u64,
+}
+
+impl Totals {
+    fn of(customers: &[Customer]) -> Self {
+        customers
+            .iter()
+            .fold(Totals::default(), |totals, customer| Totals {
+                users_used: totals.users_used + customer.user_used,
+                users_max: totals.users_max + customer.user_max,
+                quota_used: totals.quota_used + customer.quota_used,
+                quota_max: totals.quota_max + customer.quota_max,
+            })
+    }
+
+    /// Extrapolates the totals of a sample to all customers.
+    fn scaled(self, sampled: u64, total: u64) -> Self {
+        let scale = |value: u64| match sampled {
+            0 => 0,
+            sampled => (value as f64 * total as f64 / sampled as f64).round() as u64,
+        };
+
+        Totals {
+            users_used: scale(self.users_used),
+            users_max: scale(self.users_max),
+            quota_used: scale(self.quota_used),
+            quota_max: scale(self.quota_max),
+        }
+    }
+}
+
+/// Prints an instance overview: the total number of customers (single request with limit 1) and
+/// user / quota totals – extrapolated from one page of customers unless exact totals are requested.
+pub async fn customer_stats(
+    provider: Dracoon<Provisioning>,
+    sample: u64,
+    exact: bool,
+    print_type: PrintType,
+) {
+    let total = match provider
+        .get_customers(Some(build_params(None, None, None, Some(1))))
+        .await
+    {
+        Ok(customers) => customers.range.total,
+        Err(ref e) => {
+            handle_dracoon_errors(e, Some("Could not list customers."));
+            super::exit(1)
+        }
+    };
+
+    let customers = if exact || total <= sample {
+        fetch_all_customers(&provider, None, Some("id:asc".into())).await
+    } else {
+        match provider
+            .get_customers(Some(build_params(None, None, None, Some(sample))))
+            .await
+        {
+            Ok(customers) => customers.items,
+            Err(ref e) => {
+                handle_dracoon_errors(e, Some("Could not list customers."));
+                super::exit(1)
+            }
+        }
+    };
+
+    let sampled = customers.len() as u64;
+    let estimated = sampled < total;
+    let totals = Totals::of(&customers).scaled(sampled, total);
+
+    let mut contract_types: BTreeMap<&str, u64> = BTreeMap::new();
+    for customer in &customers {
+        *contract_types
+            .entry(customer.customer_contract_type.as_str())
+            .or_default() += 1;
+    }
+
+    let metrics = [
+        ("usersUsed", totals.users_used),
+        ("usersMax", totals.users_max),
+        ("quotaUsed", totals.quota_used),
+        ("quotaMax", totals.quota_max),
+    ];
+
+    match print_type {
+        PrintType::Csv => {
+            print_csv_header(STATS_CSV_HEADER);
+            println!("customers,{},false", total);
+            for (metric, value) in metrics {
+                println!("{},{},{}", metric, value, estimated);
+            }
+        }
+        PrintType::Pretty => {
+            println!("customers: {}", format_number(total).bold());
+            let suffix = match estimated {
+                true => format!(" (estimated from {} customers)", sampled),
+                false => String::new(),
+            };
+            println!(
+                "users used: {} | users max: {}{}",
+                format_number(totals.users_used),
+                format_number(totals.users_max),
+                suffix
+            );
+            println!(
+                "quota used: {} | quota max: {}{}",
+                format_number(totals.quota_used),
+                format_number(totals.quota_max),
+                suffix
+            );
+            println!(
+                "contract types{}: {}",
+                match estimated {
+                    true => " (sample)",
+                    false => "",
+                },
+                contract_types
+                    .iter()
+                    .map(|(contract_type, count)| format!("{}: {}", contract_type, count))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            );
+        }
+        PrintType::Json => {
+            let mut stats = json!({
+                "customers": total,
+                "sampled": sampled,
+                "estimated": estimated,
+                "contractTypes": contract_types,
+            });
+            for (metric, value) in metrics {
+                stats[metric] = value.into();
+            }
+            print_json(stats)
+        }
+    }
+}
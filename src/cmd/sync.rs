@@ -0,0 +1,378 @@
+use super::{
+    customer_field, exclude::apply_exclusions, fetch_all_customers, handle_dracoon_errors,
+    http_client, include_attributes, install_cancel_handler, is_cancelled, log_payload,
+    BatchReport, ATTRIBUTES_CONCURRENCY, ATTRIBUTE_PREFIX,
+};
+use clap::ValueEnum;
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use reqwest::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::{collections::BTreeMap, fs};
+
+// supported external CMDB / ITSM systems
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SyncTarget {
+    /// ServiceNow Table API
+    Servicenow,
+    /// Jira Service Management Assets (Insight) object API
+    JiraAssets,
+}
+
+#[derive(Clone, Copy, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+enum SyncAuth {
+    #[default]
+    Bearer,
+    /// token is passed as `user:password`
+    Basic,
+}
+
+#[derive(Deserialize)]
+struct SyncTargetConfig {
+    /// base url of the table (ServiceNow) or object schema API (Jira Assets)
+    url: String,
+    /// environment variable holding the API token
+    token_env: String,
+    #[serde(default)]
+    auth: SyncAuth,
+    /// external field used to match records
+    key_field: String,
+    /// customer field holding the matching value (default: id)
+    #[serde(default = "default_key_source")]
+    key_source: String,
+    /// Jira Assets only: object type to create objects in
+    object_type_id: Option<String>,
+}
+
+fn default_key_source() -> String {
+    "id".into()
+}
+
+/// Mapping of customer fields / attributes to external CMDB record fields
+#[derive(Deserialize)]
+struct SyncMapping {
+    target: SyncTargetConfig,
+    /// external field -> customer field (or `attribute:<key>`)
+    fields: BTreeMap<String, String>,
+}
+
+enum SyncOutcome {
+    Created,
+    Updated,
+}
+
+fn parse_mapping(path: &str) -> SyncMapping {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) => {
+            println!(
+                "{} Could not open mapping from path {}",
+                "Error".white().on_red(),
+                path
+            );
+            println!("{:?}", e);
+            super::exit(1)
+        }
+    };
+
+    match toml::from_str::<SyncMapping>(&raw) {
+        Ok(mapping) => mapping,
+        Err(e) => {
+            println!(
+                "{} Could not parse mapping from file {}",
+                "Error".white().on_red(),
+                path
+            );
+            println!("{}", e);
+            super::exit(1)
+        }
+    }
+}
+
+/// Escapes a value for a ServiceNow encoded query – `^` separates conditions and is doubled (`=` in
+/// the value is literal, the operator is the first one after the field name).
+fn servicenow_query_value(value: &str) -> String {
+    value.replace('^', "^^")
+}
+
+/// Quotes a value for a Jira Assets AQL query – backslashes and double quotes are escaped.
+fn aql_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+struct SyncClient {
+    http: Client,
+    target: SyncTarget,
+    config: SyncTargetConfig,
+    token: String,
+    verbose: bool,
+}
+
+impl SyncClient {
+    /// Sends a request – logs the payload sizes with --verbose.
+    async fn send(&self, req: RequestBuilder) -> Result<Response, String> {
+        let req = req.build().map_err(|e| e.to_string())?;
+        let logged = self.verbose.then(|| req.try_clone()).flatten();
+        let res = self.http.execute(req).await.map_err(|e| e.to_string())?;
+        if let Some(logged) = &logged {
+            log_payload(logged, &res);
+        }
+
+        res.error_for_status().map_err(|e| e.to_string())
+    }
+
+    fn authorize(&self, req: RequestBuilder) -> RequestBuilder {
+        match self.config.auth {
+            SyncAuth::Bearer => req.bearer_auth(&self.token),
+            SyncAuth::Basic => {
+                let (user, password) = self
+                    .token
+                    .split_once(':')
+                    .unwrap_or((self.token.as_str(), ""));
+                req.basic_auth(user, Some(password))
+            }
+        }
+    }
+
+    fn record_body(&self, record: &BTreeMap<String, String>) -> Value {
+        match self.target {
+            SyncTarget::Servicenow => Value::Object(
+                record
+                    .iter()
+                    .map(|(field, value)| (field.clone(), Value::String(value.clone())))
+                    .collect::<Map<_, _>>(),
+            ),
+            SyncTarget::JiraAssets => {
+                let attributes: Vec<Value> = record
+                    .iter()
+                    .map(|(field, value)| {
+                        json!({
+                            "objectTypeAttributeId": field,
+                            "objectAttributeValues": [{ "value": value }]
+                        })
+                    })
+                    .collect();
+                json!({
+                    "objectTypeId": self.config.object_type_id,
+                    "attributes": attributes
+                })
+            }
+        }
+    }
+
+    /// Looks up the external record id matching the given key value.
+    async fn find_record(&self, key: &str) -> Result<Option<String>, String> {
+        let (req, pointer) = match self.target {
+            SyncTarget::Servicenow => (
+                self.http.get(&self.config.url).query(&[
+                    (
+                        "sysparm_query",
+                        format!("{}={}", self.config.key_field, servicenow_query_value(key)),
+                    ),
+                    ("sysparm_fields", "sys_id".into()),
+                    ("sysparm_limit", "1".into()),
+                ]),
+                "/result/0/sys_id",
+            ),
+            SyncTarget::JiraAssets => (
+                self.http
+                    .post(format!("{}/object/aql", self.config.url))
+                    .json(&json!({
+                        "qlQuery": format!(
+                            "objectTypeId = {} AND {} = {}",
+                            self.config.object_type_id.as_deref().unwrap_or_default(),
+                            aql_string(&self.config.key_field),
+                            aql_string(key)
+                        )
+                    })),
+                "/values/0/id",
+            ),
+        };
+
+        let res = self.send(self.authorize(req)).await?;
+        let body = res.json::<Value>().await.map_err(|e| e.to_string())?;
+
+        Ok(body.pointer(pointer).map(|id| match id {
+            Value::String(id) => id.clone(),
+            id => id.to_string(),
+        }))
+    }
+
+    async fn push_record(
+        &self,
+        key: &str,
+        record: &BTreeMap<String, String>,
+    ) -> Result<SyncOutcome, String> {
+        let body = self.record_body(record);
+
+        let (req, outcome) = match (self.find_record(key).await?, self.target) {
+            (Some(id), SyncTarget::Servicenow) => (
+                self.http.patch(format!("{}/{}", self.config.url, id)),
+                SyncOutcome::Updated,
+            ),
+            (Some(id), SyncTarget::JiraAssets) => (
+                self.http.put(format!("{}/object/{}", self.config.url, id)),
+                SyncOutcome::Updated,
+            ),
+            (None, SyncTarget::Servicenow) => {
+                (self.http.post(&self.config.url), SyncOutcome::Created)
+            }
+            (None, SyncTarget::JiraAssets) => (
+                self.http.post(format!("{}/object/create", self.config.url)),
+                SyncOutcome::Created,
+            ),
+        };
+
+        self.send(self.authorize(req).json(&body)).await?;
+
+        Ok(outcome)
+    }
+}
+
+/// Pushes all customers (matching the filter) as records into an external CMDB / ITSM system.
+pub async fn sync_customers(
+    provider: Dracoon<Provisioning>,
+    target: SyncTarget,
+    mapping_path: &str,
+    filter: Option<String>,
+    resume_from: Option<u64>,
+    verbose: bool,
+) -> BatchReport {
+    let mapping = parse_mapping(mapping_path);
+
+    install_cancel_handler();
+
+    if let SyncTarget::JiraAssets = target {
+        if mapping.target.object_type_id.is_none() {
+            println!(
+                "{} Jira Assets mapping requires target.object_type_id.",
+                "Error".white().on_red()
+            );
+            super::exit(1)
+        }
+    }
+
+    let Ok(token) = std::env::var(&mapping.target.token_env) else {
+        println!(
+            "{} Environment variable {} with CMDB token not set.",
+            "Error".white().on_red(),
+            mapping.target.token_env
+        );
+        super::exit(1)
+    };
+
+    let needs_attributes = mapping
+        .fields
+        .values()
+        .chain(std::iter::once(&mapping.target.key_source))
+        .any(|field| field.starts_with(ATTRIBUTE_PREFIX));
+
+    // stable order by id allows resuming an interrupted sync
+    let customers: Vec<_> = fetch_all_customers(&provider, filter, Some("id:asc".into()))
+        .await
+        .into_iter()
+        .filter(|customer| resume_from.is_none_or(|id| customer.id >= id))
+        .collect();
+
+    if is_cancelled() {
+        println!("Interrupted while listing customers – nothing synced.");
+        super::exit(1)
+    }
+
+    let (mut customers, excluded) =
+        apply_exclusions(&provider, customers, |customer| customer.id).await;
+
+    if needs_attributes {
+        let complete =
+            match include_attributes(&provider, &mut customers, ATTRIBUTES_CONCURRENCY).await {
+                Ok(complete) => complete,
+                Err(ref e) => {
+                    handle_dracoon_errors(e, Some("Could not get customer attributes."));
+                    super::exit(1)
+                }
+            };
+        if !complete {
+            println!("Interrupted while fetching attributes – nothing synced.");
+            super::exit(1)
+        }
+    }
+
+    let client = SyncClient {
+        http: http_client(),
+        target,
+        config: mapping.target,
+        token,
+        verbose,
+    };
+
+    let (mut created, mut updated, mut failed) = (0, 0, 0);
+
+    for customer in &customers {
+        if is_cancelled() {
+            println!(
+                "{} Interrupted – resume with --resume-from {}",
+                "Warning".black().on_yellow(),
+                customer.id
+            );
+            break;
+        }
+
+        let Some(key) = customer_field(customer, &client.config.key_source) else {
+            println!(
+                "{} customer {} has no value for key {} – skipped",
+                "Warning".black().on_yellow(),
+                customer.id,
+                client.config.key_source
+            );
+            failed += 1;
+            continue;
+        };
+
+        let record: BTreeMap<String, String> = mapping
+            .fields
+            .iter()
+            .filter_map(|(external, field)| {
+                customer_field(customer, field).map(|value| (external.clone(), value))
+            })
+            .collect();
+
+        match client.push_record(&key, &record).await {
+            Ok(SyncOutcome::Created) => {
+                created += 1;
+                println!("created: {} (id: {})", customer.company_name, customer.id);
+            }
+            Ok(SyncOutcome::Updated) => {
+                updated += 1;
+                println!("updated: {} (id: {})", customer.company_name, customer.id);
+            }
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "{} {} (id: {}): {}",
+                    "Error".white().on_red(),
+                    customer.company_name,
+                    customer.id,
+                    e
+                );
+            }
+        }
+    }
+
+    println!(
+        "synced customers: {} of {} | created: {} | updated: {} | failed: {}",
+        created + updated,
+        customers.len(),
+        created,
+        updated,
+        failed
+    );
+
+    BatchReport::new(
+        created + updated,
+        failed,
+        customers.len() - created - updated - failed,
+    )
+    .with_excluded(excluded)
+}
@@ -0,0 +1,149 @@
+use super::{
+    http_client,
+    otel::{export_command_span, otlp_endpoint, set_traceparent, SpanContext},
+    DcProv,
+};
+use crate::config;
+use clap::{ArgMatches, CommandFactory};
+use serde_json::json;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
+
+// telemetry must never noticeably delay a command
+const TELEMETRY_TIMEOUT_SECS: u64 = 2;
+
+// exit code of a process ended by a panic
+const PANIC_EXIT_CODE: i32 = 101;
+
+/// Returns the (anonymized) command name, e.g. `list` or `report expiring-users` – plugins are
+/// reported as `plugin` as their names are not part of dcprov.
+pub fn command_name(matches: &ArgMatches) -> String {
+    let Some((name, sub)) = matches.subcommand() else {
+        return String::new();
+    };
+
+    if DcProv::command().find_subcommand(name).is_none() {
+        return "plugin".into();
+    }
+
+    match sub.subcommand_name() {
+        Some(nested) => format!("{} {}", name, nested),
+        None => name.to_string(),
+    }
+}
+
+/// Telemetry of the running command – reported once it finishes (see `finish_telemetry` and `exit`).
+struct CommandRun {
+    command: String,
+    endpoint: Option<String>,
+    otlp_endpoint: Option<String>,
+    span_context: SpanContext,
+    started: Instant,
+    started_at: SystemTime,
+}
+
+static COMMAND_RUN: Mutex<Option<CommandRun>> = Mutex::new(None);
+
+/// Starts measuring the command to report command name, duration, success and version to the
+/// configured endpoint – only if telemetry is enabled in the config (opt-in) – and to export a
+/// span of the command if an OTLP endpoint is set (see `otel`). Does nothing if neither is enabled.
+pub fn start_telemetry(command: String) {
+    let telemetry = config::load_config().unwrap_or_default().telemetry;
+    let endpoint = telemetry.endpoint.filter(|_| telemetry.enabled);
+    let otlp_endpoint = otlp_endpoint();
+    if endpoint.is_none() && otlp_endpoint.is_none() {
+        return;
+    }
+    let span_context = SpanContext::new();
+
+    // hooks, plugins and requests sent by dcprov itself continue the span of the command
+    set_traceparent(span_context.traceparent());
+
+    // a panic of the command (main thread) ends the process with exit code 101
+    let panic_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        panic_hook(info);
+        if std::thread::current().name() == Some("main") {
+            report_blocking(PANIC_EXIT_CODE);
+        }
+    }));
+
+    if let Ok(mut run) = COMMAND_RUN.lock() {
+        *run = Some(CommandRun {
+            command,
+            endpoint,
+            otlp_endpoint,
+            span_context,
+            started: Instant::now(),
+            started_at: SystemTime::now(),
+        });
+    }
+}
+
+fn take_command_run() -> Option<CommandRun> {
+    COMMAND_RUN.lock().ok().and_then(|mut run| run.take())
+}
+
+async fn report(run: CommandRun, exit_code: i32) {
+    if let Some(otlp_endpoint) = run.otlp_endpoint {
+        export_command_span(
+            &otlp_endpoint,
+            &run.span_context,
+            &run.command,
+            run.started_at,
+            exit_code,
+        )
+        .await;
+    }
+
+    let Some(endpoint) = run.endpoint else {
+        return;
+    };
+
+    let payload = json!({
+        "command": run.command,
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "durationMs": run.started.elapsed().as_millis() as u64,
+        "success": exit_code == 0,
+        "exitCode": exit_code,
+    });
+
+    // failing to report is never an error of the command
+    let _ = http_client()
+        .post(endpoint)
+        .timeout(Duration::from_secs(TELEMETRY_TIMEOUT_SECS))
+        .json(&payload)
+        .send()
+        .await;
+}
+
+/// Reports the telemetry of the finished command (if started).
+pub async fn finish_telemetry(exit_code: i32) {
+    if let Some(run) = take_command_run() {
+        report(run, exit_code).await;
+    }
+}
+
+// called from within the runtime (which can't block) – reports on a thread with its own runtime
+fn report_blocking(exit_code: i32) {
+    if let Some(run) = take_command_run() {
+        let _ = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map(|runtime| runtime.block_on(report(run, exit_code)))
+        })
+        .join();
+    }
+}
+
+/// Exits the process with the exit code after reporting the telemetry of the command (if started) –
+/// commands exit via this function instead of `std::process::exit`.
+pub fn exit(code: i32) -> ! {
+    report_blocking(code);
+
+    std::process::exit(code)
+}
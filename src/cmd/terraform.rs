@@ -0,0 +1,147 @@
+use super::{dracoon_error_to_string, find_customer_by_name, try_create_customer, ContractType};
+use dco3::{
+    auth::Provisioning,
+    provisioning::{Customer, FirstAdminUser, NewCustomerRequest},
+    users::{AuthMethod, UserAuthData},
+    CustomerProvisioning, Dracoon,
+};
+use std::{collections::BTreeMap, io::Read};
+
+// Terraform external data source protocol: flat JSON object of strings in and out
+type TfMap = BTreeMap<String, String>;
+
+fn required<'a>(query: &'a TfMap, key: &str) -> Result<&'a str, String> {
+    query
+        .get(key)
+        .map(String::as_str)
+        .ok_or_else(|| format!("missing query argument: {}", key))
+}
+
+fn required_u64(query: &TfMap, key: &str) -> Result<u64, String> {
+    required(query, key)?
+        .parse()
+        .map_err(|_| format!("query argument {} must be a positive number", key))
+}
+
+fn customer_to_tf_map(customer: Customer) -> TfMap {
+    let mut result = TfMap::new();
+    result.insert("id".into(), customer.id.to_string());
+    result.insert("company_name".into(), customer.company_name);
+    result.insert("contract_type".into(), customer.customer_contract_type);
+    result.insert("quota_max".into(), customer.quota_max.to_string());
+    result.insert("quota_used".into(), customer.quota_used.to_string());
+    result.insert("user_max".into(), customer.user_max.to_string());
+    result.insert("user_used".into(), customer.user_used.to_string());
+    result.insert("created_at".into(), customer.created_at);
+    result.insert(
+        "is_locked".into(),
+        customer.is_locked.unwrap_or_default().to_string(),
+    );
+    result
+}
+
+async fn handle_query(provider: &Dracoon<Provisioning>, query: TfMap) -> Result<TfMap, String> {
+    let operation = query.get("operation").map(String::as_str).unwrap_or("get");
+
+    let customer = match operation {
+        "get" => provider
+            .get_customer(required_u64(&query, "id")?, None)
+            .await
+            .map_err(dracoon_error_to_string)?,
+        "find" => {
+            let company_name = required(&query, "company_name")?;
+            find_customer_by_name(provider, company_name)
+                .await
+                .map_err(dracoon_error_to_string)?
+                .ok_or_else(|| format!("no customer found with company name {}", company_name))?
+        }
+        "create" => {
+            let company_name = required(&query, "company_name")?;
+
+            // data sources are read on every plan – only create if the customer is missing
+            if let Some(customer) = find_customer_by_name(provider, company_name)
+                .await
+                .map_err(dracoon_error_to_string)?
+            {
+                return Ok(customer_to_tf_map(customer));
+            }
+
+            let auth_data = UserAuthData::builder(AuthMethod::Basic)
+                .with_must_change_password(true)
+                .build();
+
+            let first_admin_user = FirstAdminUser {
+                first_name: required(&query, "admin_first_name")?.into(),
+                last_name: required(&query, "admin_last_name")?.into(),
+                user_name: query.get("admin_user_name").cloned(),
+                email: Some(required(&query, "admin_email")?.into()),
+                auth_data: Some(auth_data),
+                notify_user: Some(true),
+                receiver_language: None,
+                phone: None,
+            };
+
+            let contract_type = query
+                .get("contract_type")
+                .map(|contract_type| contract_type.parse::<ContractType>())
+                .transpose()?
+                .unwrap_or_default();
+
+            let req = NewCustomerRequest::builder(
+                contract_type.as_str(),
+                required_u64(&query, "quota_max")?,
+                required_u64(&query, "user_max")?,
+                first_admin_user,
+            )
+            .with_company_name(company_name)
+            .build();
+
+            let created = try_create_customer(provider, req)
+                .await
+                .map_err(dracoon_error_to_string)?;
+
+            provider
+                .get_customer(created.id, None)
+                .await
+                .map_err(dracoon_error_to_string)?
+        }
+        other => {
+            return Err(format!(
+                "unsupported operation {} (supported: get, find, create)",
+                other
+            ))
+        }
+    };
+
+    Ok(customer_to_tf_map(customer))
+}
+
+/// Implements the Terraform external data source protocol: reads a JSON query from stdin and
+/// writes the resulting customer as flat JSON object to stdout. Errors are reported on stderr.
+pub async fn handle_tf_resource(provider: Dracoon<Provisioning>) {
+    let mut raw = String::new();
+
+    let result = match std::io::stdin().read_to_string(&mut raw) {
+        Ok(_) => match serde_json::from_str::<TfMap>(&raw) {
+            Ok(query) => handle_query(&provider, query).await,
+            Err(e) => Err(format!(
+                "invalid query (expected JSON object of strings): {}",
+                e
+            )),
+        },
+        Err(e) => Err(format!("could not read query from stdin: {}", e)),
+    };
+
+    match result {
+        Ok(result) => {
+            println!(
+                "{}",
+                serde_json::to_string(&result).expect("string map serializes")
+            );
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            super::exit(1)
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::{fetch_all_customers, is_cancelled};
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use colored::*;
+use dco3::{auth::Provisioning, Dracoon};
+use serde_json::json;
+use std::{fs, io::Write, path::Path};
+
+// partition layout of usage exports (Hive-style directories, e.g. month=2024-05)
+#[derive(Clone, Copy, ValueEnum)]
+pub enum UsagePartition {
+    /// One directory per calendar month, e.g. month=2024-05
+    Month,
+    /// One directory per ISO week, e.g. week=2024-W19
+    Week,
+}
+
+impl UsagePartition {
+    fn directory(self, at: DateTime<Utc>) -> String {
+        match self {
+            UsagePartition::Month => format!("month={}", at.format("%Y-%m")),
+            UsagePartition::Week => format!("week={}", at.format("%G-W%V")),
+        }
+    }
+}
+
+/// Takes a usage snapshot of all customers and writes one fact per customer (NDJSON) into the
+/// partition of the snapshot time, e.g. `usage/month=2024-05/dracoon.team-20240512T060000Z.jsonl`.
+/// Scheduled runs build up the usage history – each run writes its own file.
+pub async fn export_usage(
+    provider: Dracoon<Provisioning>,
+    partition: UsagePartition,
+    out_dir: String,
+    filter: Option<String>,
+) {
+    let instance = provider
+        .build_api_url("/")
+        .host_str()
+        .unwrap_or_default()
+        .to_string();
+
+    let customers = fetch_all_customers(&provider, filter, Some("id:asc".into())).await;
+
+    // a partial snapshot would understate usage in the warehouse
+    if is_cancelled() {
+        eprintln!(
+            "{} Interrupted – no usage exported.",
+            "Warning".black().on_yellow()
+        );
+        super::exit(1)
+    }
+
+    let snapshot_at = Utc::now();
+    let dir = Path::new(&out_dir).join(partition.directory(snapshot_at));
+    let path = dir.join(format!(
+        "{}-{}.jsonl",
+        instance,
+        snapshot_at.format("%Y%m%dT%H%M%SZ")
+    ));
+
+    let result = fs::create_dir_all(&dir)
+        .and_then(|_| fs::File::create(&path))
+        .and_then(|mut file| {
+            for customer in &customers {
+                let fact = json!({
+                    "snapshotAt": snapshot_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    "instance": instance,
+                    "id": customer.id,
+                    "companyName": customer.company_name,
+                    "contractType": customer.customer_contract_type,
+                    "quotaMax": customer.quota_max,
+                    "quotaUsed": customer.quota_used,
+                    "userMax": customer.user_max,
+                    "userUsed": customer.user_used,
+                    "isLocked": customer.is_locked,
+                    "createdAt": customer.created_at,
+                });
+                writeln!(file, "{}", fact)?;
+            }
+            Ok(())
+        });
+
+    match result {
+        Ok(_) => println!(
+            "{}Exported usage of {} customers to {}",
+            "Success ".green(),
+            customers.len(),
+            path.display()
+        ),
+        Err(e) => {
+            println!(
+                "{} Could not write {}: {}",
+                "Error".white().on_red(),
+                path.display(),
+                e
+            );
+            super::exit(1)
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use super::retry::backoff_delay;
+use super::{
+    dracoon_error_to_string, exclude::apply_exclusions, fetch_all_customers, fetch_all_users,
+    install_cancel_handler, is_cancelled, print_csv_header, print_json, redact_user, user_field,
+    user_to_json, user_to_string, validate_columns, PrintType, CUSTOMER_USERS_CSV_HEADER,
+    USER_FIELDS,
+};
+use chrono::Utc;
+use colored::*;
+use dco3::{auth::Provisioning, users::UserItem, Dracoon};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+
+const USERS_DIFF_CSV_HEADER: &str = "side,key,userId";
+
+// keys are compared case-insensitive (emails, user names)
+fn normalize_key(key: &str) -> String {
+    key.trim().to_lowercase()
+}
+
+/// Reads the key column of a user export (CSV with header).
+fn read_export_keys(path: &str, column: &str) -> Result<BTreeSet<String>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("could not open {}: {}", path, e))?;
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("could not read header of {}: {}", path, e))?;
+    let idx = headers
+        .iter()
+        .position(|header| header.trim().eq_ignore_ascii_case(column))
+        .ok_or_else(|| {
+            format!(
+                "column {} not found in {} (available: {})",
+                column,
+                path,
+                headers.iter().collect::<Vec<_>>().join(", ")
+            )
+        })?;
+
+    let mut keys = BTreeSet::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("could not read {}: {}", path, e))?;
+        if let Some(key) = record
+            .get(idx)
+            .map(normalize_key)
+            .filter(|key| !key.is_empty())
+        {
+            keys.insert(key);
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Reports users missing in the tenant or in the external export (matched by key).
+/// Exits with 1 if there are differences.
+pub async fn diff_customer_users(
+    provider: Dracoon<Provisioning>,
+    id: u64,
+    against: String,
+    key: String,
+    column: Option<String>,
+    print_type: PrintType,
+) {
+    validate_columns(Some(&vec![key.clone()]), USER_FIELDS);
+
+    let column = column.unwrap_or_else(|| key.clone());
+    let export = match read_export_keys(&against, &column) {
+        Ok(keys) => keys,
+        Err(msg) => {
+            println!("{} {}", "Error".white().on_red(), msg);
+            super::exit(1)
+        }
+    };
+
+    let users = match fetch_all_users(&provider, id).await {
+        Ok(users) => users,
+        Err(e) => {
+            println!(
+                "{} Could not get users of customer {}: {}",
+                "Error".white().on_red(),
+                id,
+                dracoon_error_to_string(e)
+            );
+            super::exit(1)
+        }
+    };
+
+    // users without a value for the key cannot be matched
+    let mut without_key = 0;
+    let tenant: BTreeMap<_, _> = users
+        .iter()
+        .filter_map(|user| {
+            let value = user_field(user, &key).map(|value| normalize_key(&value));
+            if value.is_none() {
+                without_key += 1;
+            }
+            Some((value?, user.id))
+        })
+        .collect();
+
+    let missing_in_export: Vec<_> = tenant
+        .iter()
+        .filter(|(key, _)| !export.contains(*key))
+        .collect();
+    let missing_in_tenant: Vec<_> = export
+        .iter()
+        .filter(|key| !tenant.contains_key(*key))
+        .collect();
+
+    match print_type {
+        PrintType::Csv => {
+            print_csv_header(USERS_DIFF_CSV_HEADER);
+            for (key, user_id) in &missing_in_export {
+                println!("tenant,{},{}", key, user_id);
+            }
+            for key in &missing_in_tenant {
+                println!("export,{},", key);
+            }
+        }
+        PrintType::Pretty => {
+            println!("Only in tenant (missing in {}):", against);
+            for (key, user_id) in &missing_in_export {
+                println!("  {} | id: {}", key, user_id);
+            }
+            println!("Only in {} (missing in tenant):", against);
+            for key in &missing_in_tenant {
+                println!("  {}", key);
+            }
+            println!(
+                "tenant users: {} | export users: {} | only in tenant: {} | only in export: {} | without {}: {}",
+                users.len(),
+                export.len(),
+                missing_in_export.len(),
+                missing_in_tenant.len(),
+                key,
+                without_key
+            );
+        }
+        PrintType::Json => print_json(json!({
+            "tenantUsers": users.len(),
+            "exportUsers": export.len(),
+            "withoutKey": without_key,
+            "onlyInTenant": missing_in_export
+                .iter()
+                .map(|(key, user_id)| json!({ "key": key, "userId": user_id }))
+                .collect::<Vec<_>>(),
+            "onlyInExport": missing_in_tenant,
+        })),
+    }
+
+    if !missing_in_export.is_empty() || !missing_in_tenant.is_empty() {
+        super::exit(1)
+    }
+}
+
+/// Customer whose users could not be exported (entry of the export report)
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FailedExport {
+    id: u64,
+    company_name: String,
+    error: String,
+}
+
+/// Report of an export – re-run only the failed customers via --retry-failed.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportReport {
+    exported_at: String,
+    customers: usize,
+    exported: usize,
+    failed: Vec<FailedExport>,
+}
+
+fn read_export_report(path: &str) -> Result<ExportReport, String> {
+    let raw =
+        std::fs::read_to_string(path).map_err(|e| format!("could not open {}: {}", path, e))?;
+    serde_json::from_str(&raw).map_err(|e| format!("could not parse {}: {}", path, e))
+}
+
+/// Fetches the users of a customer – retried with backoff (1s, 2s, 4s, ...) up to `retries` times.
+async fn fetch_users_with_retries(
+    provider: &Dracoon<Provisioning>,
+    id: u64,
+    retries: u32,
+) -> Result<Vec<UserItem>, String> {
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        match fetch_all_users(provider, id).await {
+            Ok(users) => return Ok(users),
+            Err(e) if attempt > retries || is_cancelled() => {
+                return Err(dracoon_error_to_string(e))
+            }
+            Err(e) => {
+                eprintln!(
+                    "{} Could not get users of customer {} (attempt {}): {} – retrying",
+                    "Warning".black().on_yellow(),
+                    id,
+                    attempt,
+                    dracoon_error_to_string(e)
+                );
+                tokio::time::sleep(backoff_delay(attempt)).await;
+            }
+        }
+    }
+}
+
+/// Exports the users of all (filtered) customers – or only of the failed customers of a previous
+/// report (`retry_failed`). Customers are exported in parallel and retried on their own: a failing
+/// customer never aborts the export, failures are listed at the end and written to the report
+/// (default: the retried report). Exits with 1 if any customer failed.
+pub async fn export_all_users(
+    provider: Dracoon<Provisioning>,
+    filter: Option<String>,
+    concurrency: usize,
+    retries: u32,
+    report: Option<String>,
+    retry_failed: Option<String>,
+    print_type: PrintType,
+) {
+    let customers: Vec<(u64, String)> = match &retry_failed {
+        Some(path) => match read_export_report(path) {
+            Ok(report) => report
+                .failed
+                .into_iter()
+                .map(|failed| (failed.id, failed.company_name))
+                .collect(),
+            Err(e) => {
+                println!("{} {}", "Error".white().on_red(), e);
+                super::exit(1)
+            }
+        },
+        None => fetch_all_customers(&provider, filter, Some("id:asc".into()))
+            .await
+            .into_iter()
+            .map(|customer| (customer.id, customer.company_name))
+            .collect(),
+    };
+    if is_cancelled() {
+        super::exit(1)
+    }
+    let (customers, excluded) = apply_exclusions(&provider, customers, |(id, _)| *id).await;
+
+    install_cancel_handler();
+
+    if let PrintType::Csv = print_type {
+        print_csv_header(&format!(
+            "customerId,companyName,{}",
+            CUSTOMER_USERS_CSV_HEADER
+        ));
+    }
+
+    let provider = &provider;
+    let mut results = stream::iter(&customers)
+        .map(|(id, company_name)| async move {
+            let users = match is_cancelled() {
+                true => Err("not processed (interrupted)".to_string()),
+                false => fetch_users_with_retries(provider, *id, retries).await,
+            };
+            (*id, company_name, users)
+        })
+        .buffer_unordered(concurrency.max(1));
+
+    let mut failed = Vec::new();
+    let (mut exported, mut users_total) = (0, 0);
+    let mut json_items = Vec::new();
+
+    while let Some((id, company_name, users)) = results.next().await {
+        let users: Vec<_> = match users {
+            Ok(users) => users.into_iter().map(redact_user).collect(),
+            Err(error) => {
+                eprintln!(
+                    "{} Could not export users of customer {}: {}",
+                    "Error".white().on_red(),
+                    id,
+                    error
+                );
+                failed.push(FailedExport {
+                    id,
+                    company_name: company_name.clone(),
+                    error,
+                });
+                continue;
+            }
+        };
+
+        exported += 1;
+        users_total += users.len();
+
+        match print_type {
+            PrintType::Csv => {
+                for user in users {
+                    println!(
+                        "{},{},{}",
+                        id,
+                        company_name,
+                        user_to_string(user, PrintType::Csv)
+                    );
+                }
+            }
+            PrintType::Pretty => {
+                println!(
+                    "{} (id: {}) – {} user(s)",
+                    company_name.bold(),
+                    id,
+                    users.len()
+                );
+                for user in users {
+                    println!("  {}", user_to_string(user, PrintType::Pretty));
+                }
+            }
+            PrintType::Json => json_items.push(json!({
+                "customerId": id,
+                "companyName": company_name,
+                "users": users.iter().map(user_to_json).collect::<Vec<_>>(),
+            })),
+        }
+    }
+
+    failed.sort_by_key(|failed| failed.id);
+
+    if let PrintType::Json = print_type {
+        print_json(json!({
+            "customers": customers.len(),
+            "exported": exported,
+            "users": users_total,
+            "failed": failed.iter().map(|failed| failed.id).collect::<Vec<_>>(),
+            "excluded": excluded,
+            "items": json_items,
+        }));
+    }
+
+    eprintln!(
+        "customers: {} | exported: {} | users: {} | failed: {} | excluded: {}",
+        customers.len(),
+        exported,
+        users_total,
+        failed.len(),
+        excluded.len()
+    );
+    for failed in &failed {
+        eprintln!(
+            "failed: {} (id: {}) – {}",
+            failed.company_name, failed.id, failed.error
+        );
+    }
+
+    if let Some(path) = report.or(retry_failed) {
+        let report = ExportReport {
+            exported_at: Utc::now().to_rfc3339(),
+            customers: customers.len(),
+            exported,
+            failed,
+        };
+        let written = serde_json::to_string_pretty(&report)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| std::fs::write(&path, raw).map_err(|e| e.to_string()));
+
+        match written {
+            Ok(_) if !report.failed.is_empty() => {
+                eprintln!("Retry the failed customers with --retry-failed {}", path)
+            }
+            Ok(_) => {}
+            Err(e) => {
+                println!(
+                    "{} Could not write report {}: {}",
+                    "Error".white().on_red(),
+                    path,
+                    e
+                );
+                super::exit(1)
+            }
+        }
+
+        if !report.failed.is_empty() {
+            super::exit(1)
+        }
+    } else if !failed.is_empty() {
+        super::exit(1)
+    }
+}
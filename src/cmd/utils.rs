@@ -12,3 +12,51 @@ where
         .ok_or_else(|| format!("invalid KEY=value: no `=` found in `{}`", s))?;
     Ok((s[..pos].parse()?, s[pos + 1..].parse()?))
 }
+
+/// Parses a size in bytes like `1073741824`, `500MB` or `1TB` (units are powers of 1024).
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let upper = s.trim().to_uppercase();
+    let (amount, unit) = upper.split_at(upper.trim_end_matches(char::is_alphabetic).len());
+    let amount = amount
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("invalid size `{}` (e.g. 1GB, 500MB)", s))?;
+
+    let factor: u64 = match unit.trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => {
+            return Err(format!(
+                "invalid size unit in `{}` (use KB, MB, GB or TB)",
+                s
+            ))
+        }
+    };
+
+    amount
+        .checked_mul(factor)
+        .ok_or_else(|| format!("size `{}` too large", s))
+}
+
+/// Parses a duration like `30s`, `5m`, `12h`, `30d` or `2w`.
+pub fn parse_duration(s: &str) -> Result<chrono::Duration, String> {
+    let (amount, unit) = s.split_at(s.trim_end_matches(char::is_alphabetic).len());
+    let amount = amount
+        .parse::<i64>()
+        .map_err(|_| format!("invalid duration `{}` (e.g. 12h, 30d, 2w)", s))?;
+
+    match unit {
+        "s" => Ok(chrono::Duration::seconds(amount)),
+        "m" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        "w" => Ok(chrono::Duration::weeks(amount)),
+        _ => Err(format!(
+            "invalid duration unit in `{}` (use s, m, h, d or w)",
+            s
+        )),
+    }
+}
@@ -0,0 +1,87 @@
+use super::{
+    dracoon_error_to_string, find_token, handle_errors, json_query_active, print_json,
+    ClientOptions,
+};
+use crate::config;
+use colored::*;
+use dco3::{CustomerProvisioning, Dracoon, ListAllParams};
+
+/// Prints which token is used for a url and whether DRACOON accepts it.
+/// Exits with 1 if no token is found or the token is rejected.
+pub async fn whoami(
+    url_or_alias: &str,
+    token: Option<String>,
+    client_opts: &ClientOptions,
+    json: bool,
+) {
+    let url = match config::resolve_url(url_or_alias) {
+        Ok(url) => url,
+        Err(ref e) => handle_errors(e),
+    };
+    let alias = config::find_profile(url_or_alias)
+        .ok()
+        .flatten()
+        .map(|(alias, _)| alias);
+    let found = find_token(&url, token, client_opts);
+    let source = found.as_ref().map(|(_, source)| source.to_string());
+
+    let probe = match found {
+        Some((token, _)) => Some(probe_token(&url, token).await),
+        None => None,
+    };
+
+    let (valid, detail) = match &probe {
+        Some(Ok(customers)) => (true, format!("valid ({} customers)", customers)),
+        Some(Err(msg)) => (false, msg.clone()),
+        None => (
+            false,
+            "no token found (use --token, DCPROV_TOKEN or config set)".to_string(),
+        ),
+    };
+
+    if json || json_query_active() {
+        let result = serde_json::json!({
+            "url": url,
+            "alias": alias,
+            "tokenSource": source,
+            "valid": valid,
+            "detail": detail,
+        });
+        print_json(result);
+    } else {
+        println!("URL: {}", url);
+        if let Some(alias) = alias {
+            println!("Alias: {}", alias);
+        }
+        println!("Token source: {}", source.as_deref().unwrap_or("none"));
+        let status = match valid {
+            true => " OK ".black().on_green(),
+            false => "FAIL".white().on_red(),
+        };
+        println!("Token: {} {}", status, detail);
+    }
+
+    if !valid {
+        super::exit(1)
+    }
+}
+
+/// Lists a single customer to check the token – returns the total customer count.
+async fn probe_token(url: &str, token: String) -> Result<u64, String> {
+    let provider = Dracoon::builder()
+        .with_base_url(url)
+        .with_provisioning_token(token)
+        .build_provisioning()
+        .map_err(dracoon_error_to_string)?;
+
+    let params = ListAllParams::builder().with_limit(1).build();
+
+    match provider.get_customers(Some(params)).await {
+        Ok(customers) => Ok(customers.range.total),
+        Err(err) if err.is_unauthorized() => Err(format!(
+            "rejected – token invalid or expired ({})",
+            dracoon_error_to_string(err)
+        )),
+        Err(err) => Err(dracoon_error_to_string(err)),
+    }
+}
@@ -0,0 +1,393 @@
+use super::{
+    dracoon_error_to_string, find_customer_by_name, install_cancel_handler, is_cancelled,
+    retry::backoff_delay, try_create_customer, try_delete_customer, try_update_customer,
+    try_update_customer_attributes, NewCustomerRequest, UpdateCustomerPatch, UpdateType,
+};
+use colored::*;
+use dco3::{auth::Provisioning, provisioning::CustomerAttributes, Dracoon, DracoonClientError};
+use futures_util::StreamExt;
+use lapin::{
+    options::{BasicAckOptions, BasicConsumeOptions, BasicPublishOptions, QueueDeclareOptions},
+    types::FieldTable,
+    BasicProperties, Channel, Connection, ConnectionProperties,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, time::Duration};
+
+// max. time to block waiting for jobs before checking for interrupts
+const IDLE_POLL_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Deserialize)]
+#[serde(tag = "operation", rename_all = "kebab-case")]
+enum JobOperation {
+    Create {
+        customer: Box<NewCustomerRequest>,
+    },
+    Update {
+        #[serde(rename = "customerId")]
+        customer_id: u64,
+        update: UpdateCustomerPatch,
+    },
+    Delete {
+        #[serde(rename = "customerId")]
+        customer_id: u64,
+    },
+    SetAttributes {
+        #[serde(rename = "customerId")]
+        customer_id: u64,
+        attributes: BTreeMap<String, String>,
+    },
+}
+
+/// Provisioning job as consumed from the queue
+#[derive(Deserialize)]
+struct ProvisioningJob {
+    id: Option<String>,
+    #[serde(flatten)]
+    operation: JobOperation,
+}
+
+// supported queue backends
+pub enum QueueBackend {
+    Amqp(String),
+    Redis(String),
+}
+
+/// Failure of a job attempt – only failed DRACOON requests are retried
+enum JobError {
+    Invalid(String),
+    Client(DracoonClientError),
+}
+
+impl From<DracoonClientError> for JobError {
+    fn from(err: DracoonClientError) -> Self {
+        JobError::Client(err)
+    }
+}
+
+impl JobError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            JobError::Client(DracoonClientError::ConnectionFailed(_)) => true,
+            JobError::Client(DracoonClientError::Http(err)) => {
+                err.code() == 429 || err.code() >= 500
+            }
+            _ => false,
+        }
+    }
+
+    fn message(self) -> String {
+        match self {
+            JobError::Invalid(msg) => msg,
+            JobError::Client(err) => dracoon_error_to_string(err),
+        }
+    }
+}
+
+async fn execute_job(
+    provider: &Dracoon<Provisioning>,
+    job: &JobOperation,
+    attempt: u32,
+) -> Result<u64, JobError> {
+    match job {
+        JobOperation::Create { customer } => {
+            // a failed attempt (e.g. timeout, 5xx) may have created the customer anyway – retries
+            // only create it if no customer with the company name exists yet
+            if attempt > 1 {
+                let Some(company_name) = customer.company_name.as_deref() else {
+                    return Err(JobError::Invalid(
+                        "create without company name can't be retried safely".into(),
+                    ));
+                };
+                if let Some(existing) = find_customer_by_name(provider, company_name).await? {
+                    return Ok(existing.id);
+                }
+            }
+
+            // dco3 request types are not Clone – roundtrip via JSON to reuse the job on retries
+            let customer: NewCustomerRequest = serde_json::from_value(json!(customer))
+                .map_err(|e| JobError::Invalid(format!("invalid customer: {}", e)))?;
+            Ok(try_create_customer(provider, customer.into()).await?.id)
+        }
+        JobOperation::Update {
+            customer_id,
+            update,
+        } => Ok(
+            try_update_customer(provider, *customer_id, UpdateType::Patch(update.clone()))
+                .await?
+                .id,
+        ),
+        JobOperation::Delete { customer_id } => {
+            try_delete_customer(provider, *customer_id).await?;
+            Ok(*customer_id)
+        }
+        JobOperation::SetAttributes {
+            customer_id,
+            attributes,
+        } => {
+            let mut customer_attribs = CustomerAttributes::new();
+            attributes.iter().for_each(|(key, value)| {
+                customer_attribs.add_attribute(key, value);
+            });
+            Ok(
+                try_update_customer_attributes(provider, *customer_id, customer_attribs)
+                    .await?
+                    .id,
+            )
+        }
+    }
+}
+
+/// Parses and executes a job (with retries and exponential backoff) and returns the result message.
+async fn process_job(provider: &Dracoon<Provisioning>, payload: &[u8], max_retries: u32) -> Value {
+    let job = match serde_json::from_slice::<ProvisioningJob>(payload) {
+        Ok(job) => job,
+        Err(e) => {
+            println!("{} Invalid job: {}", "Error".white().on_red(), e);
+            return json!({ "status": "failed", "error": format!("invalid job: {}", e), "attempts": 0 });
+        }
+    };
+
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+
+        match execute_job(provider, &job.operation, attempts).await {
+            Ok(customer_id) => {
+                println!(
+                    "{}Job {} succeeded (customer id: {})",
+                    "Success ".green(),
+                    job.id.as_deref().unwrap_or("-"),
+                    customer_id
+                );
+                return json!({
+                    "jobId": job.id,
+                    "status": "succeeded",
+                    "customerId": customer_id,
+                    "attempts": attempts,
+                });
+            }
+            Err(e) if e.is_retryable() && attempts <= max_retries => {
+                let backoff = backoff_delay(attempts);
+                println!(
+                    "Job {} failed (attempt {}), retrying in {}s: {}",
+                    job.id.as_deref().unwrap_or("-"),
+                    attempts,
+                    backoff.as_secs(),
+                    e.message()
+                );
+                tokio::time::sleep(backoff).await;
+            }
+            Err(e) => {
+                let error = e.message();
+                println!(
+                    "{} Job {} failed: {}",
+                    "Error".white().on_red(),
+                    job.id.as_deref().unwrap_or("-"),
+                    error
+                );
+                return json!({
+                    "jobId": job.id,
+                    "status": "failed",
+                    "error": error,
+                    "attempts": attempts,
+                });
+            }
+        }
+    }
+}
+
+async fn declare_queue(channel: &Channel, queue: &str) -> Result<(), lapin::Error> {
+    channel
+        .queue_declare(
+            queue,
+            QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await
+        .map(|_| ())
+}
+
+async fn run_amqp_worker(
+    provider: &Dracoon<Provisioning>,
+    uri: &str,
+    queue: &str,
+    reply_queue: &str,
+    max_retries: u32,
+) -> Result<(), lapin::Error> {
+    let conn = Connection::connect(uri, ConnectionProperties::default()).await?;
+    let channel = conn.create_channel().await?;
+
+    declare_queue(&channel, queue).await?;
+    declare_queue(&channel, reply_queue).await?;
+    channel
+        .basic_qos(1, lapin::options::BasicQosOptions::default())
+        .await?;
+
+    let mut consumer = channel
+        .basic_consume(
+            queue,
+            "dcprov-worker",
+            BasicConsumeOptions::default(),
+            FieldTable::default(),
+        )
+        .await?;
+
+    println!("Consuming jobs from AMQP queue {}", queue);
+
+    loop {
+        if is_cancelled() {
+            println!("Interrupted – stopped consuming jobs.");
+            break;
+        }
+
+        // wake up periodically to notice interrupts while idle
+        let Ok(delivery) =
+            tokio::time::timeout(Duration::from_secs(IDLE_POLL_TIMEOUT_SECS), consumer.next())
+                .await
+        else {
+            continue;
+        };
+
+        let Some(delivery) = delivery else {
+            break;
+        };
+        let delivery = delivery?;
+        let result = process_job(provider, &delivery.data, max_retries).await;
+
+        // explicit reply_to of the job takes precedence over the configured reply queue
+        let reply_to = delivery
+            .properties
+            .reply_to()
+            .as_ref()
+            .map(|reply_to| reply_to.to_string())
+            .unwrap_or_else(|| reply_queue.to_string());
+
+        let mut props = BasicProperties::default().with_delivery_mode(2);
+        if let Some(correlation_id) = delivery.properties.correlation_id() {
+            props = props.with_correlation_id(correlation_id.clone());
+        }
+
+        channel
+            .basic_publish(
+                "",
+                &reply_to,
+                BasicPublishOptions::default(),
+                result.to_string().as_bytes(),
+                props,
+            )
+            .await?
+            .await?;
+
+        // only ack once the result is published – unacked jobs are redelivered
+        delivery.ack(BasicAckOptions::default()).await?;
+    }
+
+    Ok(())
+}
+
+async fn run_redis_worker(
+    provider: &Dracoon<Provisioning>,
+    uri: &str,
+    queue: &str,
+    reply_queue: &str,
+    max_retries: u32,
+) -> Result<(), redis::RedisError> {
+    let client = redis::Client::open(uri)?;
+    let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+    // jobs are moved into a processing list until done to survive worker crashes
+    let processing_queue = format!("{}.processing", queue);
+
+    // requeue jobs left over by a crashed worker (newest first, so the oldest is consumed first)
+    let mut requeued = 0;
+    while redis::cmd("LMOVE")
+        .arg(&processing_queue)
+        .arg(queue)
+        .arg("LEFT")
+        .arg("RIGHT")
+        .query_async::<_, Option<Vec<u8>>>(&mut conn)
+        .await?
+        .is_some()
+    {
+        requeued += 1;
+    }
+    if requeued > 0 {
+        println!(
+            "Requeued {} unfinished job(s) from {}",
+            requeued, processing_queue
+        );
+    }
+
+    println!("Consuming jobs from Redis list {}", queue);
+
+    loop {
+        if is_cancelled() {
+            println!("Interrupted – stopped consuming jobs.");
+            return Ok(());
+        }
+
+        let payload: Option<Vec<u8>> = redis::cmd("BRPOPLPUSH")
+            .arg(queue)
+            .arg(&processing_queue)
+            .arg(IDLE_POLL_TIMEOUT_SECS)
+            .query_async(&mut conn)
+            .await?;
+
+        let Some(payload) = payload else {
+            continue;
+        };
+
+        let result = process_job(provider, &payload, max_retries).await;
+
+        redis::cmd("LPUSH")
+            .arg(reply_queue)
+            .arg(result.to_string())
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+
+        redis::cmd("LREM")
+            .arg(&processing_queue)
+            .arg(1)
+            .arg(payload)
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+    }
+}
+
+/// Consumes JSON provisioning jobs from a queue (RabbitMQ or Redis), executes them with
+/// retries and publishes the results to the reply queue.
+pub async fn run_worker(
+    provider: Dracoon<Provisioning>,
+    backend: QueueBackend,
+    queue: String,
+    reply_queue: Option<String>,
+    max_retries: u32,
+) {
+    let reply_queue = reply_queue.unwrap_or_else(|| format!("{}.results", queue));
+
+    install_cancel_handler();
+
+    let result = match backend {
+        QueueBackend::Amqp(uri) => {
+            run_amqp_worker(&provider, &uri, &queue, &reply_queue, max_retries)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        QueueBackend::Redis(uri) => {
+            run_redis_worker(&provider, &uri, &queue, &reply_queue, max_retries)
+                .await
+                .map_err(|e| e.to_string())
+        }
+    };
+
+    if let Err(e) = result {
+        println!("{} Queue error: {}", "Error".white().on_red(), e);
+        super::exit(1)
+    }
+}
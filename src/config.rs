@@ -0,0 +1,285 @@
+use crate::cmd::{DcProvError, PrintType};
+use crate::credentials::{CredentialBackend, SERVICE_NAME};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use toml_edit::{value, DocumentMut, Item, Table};
+
+// config file name (in the user config dir)
+const CONFIG_FILE: &str = "config.toml";
+// environment variable to override the config file path
+const CONFIG_PATH_ENV: &str = "DCPROV_CONFIG";
+
+/// Named list query (invoked via `list --query <name>`)
+#[derive(Deserialize, Clone, Default)]
+pub struct SavedQuery {
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    /// customer fields (API names) to print
+    pub columns: Option<Vec<String>>,
+    pub format: Option<PrintType>,
+}
+
+/// Named DRACOON instance (the alias can be used instead of the url)
+#[derive(Deserialize, Clone)]
+pub struct Profile {
+    pub url: String,
+    /// marks the instance as production (banner is printed for every command)
+    #[serde(default)]
+    pub production: bool,
+    /// require typing the alias to confirm mutating commands
+    #[serde(default)]
+    pub confirm: bool,
+    /// host mutating commands must target (guards against a changed url)
+    pub expect_host: Option<String>,
+    /// default flags added to every command targeting this instance (e.g. `["--all", "--csv"]`)
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// contract ceiling: max. quota (bytes) allocated across all customers (checked by create)
+    pub quota_ceiling: Option<u64>,
+    /// contract ceiling: max. users allocated across all customers (checked by create)
+    pub user_ceiling: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub queries: BTreeMap<String, SavedQuery>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// url (or alias) used by commands run without url (set via config set-default-url)
+    pub default_url: Option<String>,
+    /// keyring service name to store tokens under (default: dcprov)
+    pub keyring_service: Option<String>,
+    #[serde(default)]
+    pub credentials: CredentialsConfig,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub list: ListConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// schema for attributes set via set-attributes (not validated if missing)
+    pub attributes: Option<AttributeSchema>,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub sheets: SheetsConfig,
+    /// command aliases expanded by the CLI, e.g. `audit = "list {{url}} --all --csv"`
+    #[serde(default)]
+    pub alias: BTreeMap<String, String>,
+}
+
+/// Fields (JSON keys, e.g. `email`) redacted in all JSON output – reports, audits, run results
+#[derive(Deserialize, Default)]
+pub struct RedactionConfig {
+    /// replaced by a stable pseudonym (joins across reports still work)
+    #[serde(default)]
+    pub hash: Vec<String>,
+    /// replaced by a placeholder
+    #[serde(default)]
+    pub mask: Vec<String>,
+}
+
+/// Google Sheets export (`sheets push`)
+#[derive(Deserialize, Default)]
+pub struct SheetsConfig {
+    /// path to the JSON key file of a Google service account (the sheet must be shared with it)
+    pub credentials: Option<String>,
+    /// spreadsheet pushed to if no id is passed
+    pub spreadsheet_id: Option<String>,
+}
+
+/// Opt-in usage telemetry (command name, duration, success, version – no urls, ids or tokens)
+#[derive(Deserialize, Default)]
+pub struct TelemetryConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// endpoint the JSON reports are POSTed to
+    pub endpoint: Option<String>,
+}
+
+/// Allowed customer attribute keys and values
+#[derive(Deserialize, Default)]
+pub struct AttributeSchema {
+    /// reject keys not listed in `keys`
+    #[serde(default)]
+    pub strict: bool,
+    #[serde(default)]
+    pub keys: BTreeMap<String, AttributeRule>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct AttributeRule {
+    /// allowed values (any value if empty)
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+/// Commands run after successful create / update / delete (e.g. `notify.sh {{id}} {{company_name}}`)
+#[derive(Deserialize, Default)]
+pub struct HooksConfig {
+    pub on_create: Option<String>,
+    pub on_update: Option<String>,
+    pub on_delete: Option<String>,
+}
+
+/// Limits of list --all
+#[derive(Deserialize, Default)]
+pub struct ListConfig {
+    /// max. customers fetched with --all (default: 100000, --no-limit to lift)
+    pub max_items: Option<u64>,
+}
+
+/// Thresholds of the audit commands
+#[derive(Deserialize, Default)]
+pub struct AuditConfig {
+    /// quota_max is expected to be a multiple of this value (bytes)
+    pub quota_granularity: Option<u64>,
+    /// quota_max below this value is flagged (bytes)
+    pub min_quota: Option<u64>,
+}
+
+/// Rendering of timestamps in pretty and CSV output
+#[derive(Deserialize, Default)]
+pub struct OutputConfig {
+    /// IANA timezone, e.g. Europe/Berlin
+    pub tz: Option<String>,
+    /// strftime format, e.g. %Y-%m-%d
+    pub date_format: Option<String>,
+    /// start CSV output with a UTF-8 byte order mark (Excel on Windows)
+    #[serde(default)]
+    pub bom: bool,
+    /// locale for thousands separators in pretty output, e.g. de_DE (default: LC_ALL / LC_NUMERIC / LANG)
+    pub locale: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct CredentialsConfig {
+    #[serde(default)]
+    pub backend: CredentialBackend,
+}
+
+/// Returns the config file path (`DCPROV_CONFIG` or `<config dir>/dcprov/config.toml`).
+pub fn config_path() -> Result<PathBuf, DcProvError> {
+    if let Ok(path) = std::env::var(CONFIG_PATH_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+
+    Ok(dirs::config_dir()
+        .ok_or(DcProvError::Io)?
+        .join(SERVICE_NAME)
+        .join(CONFIG_FILE))
+}
+
+/// Loads the config file – a missing file is an empty config.
+pub fn load_config() -> Result<Config, DcProvError> {
+    let path = config_path()?;
+
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let raw = fs::read_to_string(&path).map_err(|_| DcProvError::Io)?;
+
+    toml::from_str(&raw)
+        .map_err(|e| DcProvError::InvalidConfig(format!("{}: {}", path.display(), e.message())))
+}
+
+/// Returns a saved query by name.
+pub fn get_query(name: &str) -> Result<SavedQuery, DcProvError> {
+    load_config()?
+        .queries
+        .remove(name)
+        .ok_or_else(|| DcProvError::InvalidConfig(format!("unknown query {}", name)))
+}
+
+/// Resolves a profile alias to its url – anything else is treated as url.
+pub fn resolve_url(url_or_alias: &str) -> Result<String, DcProvError> {
+    let url = load_config()?
+        .profiles
+        .remove(url_or_alias)
+        .map(|profile| profile.url)
+        .unwrap_or_else(|| url_or_alias.to_string());
+
+    Ok(crate::cmd::normalize_url(&url))
+}
+
+/// Returns the alias and profile for an alias or a url (if configured).
+pub fn find_profile(url_or_alias: &str) -> Result<Option<(String, Profile)>, DcProvError> {
+    let profiles = load_config()?.profiles;
+
+    if let Some(profile) = profiles.get(url_or_alias) {
+        return Ok(Some((url_or_alias.to_string(), profile.clone())));
+    }
+
+    let url = crate::cmd::normalize_url(url_or_alias);
+
+    Ok(profiles
+        .into_iter()
+        .find(|(_, profile)| crate::cmd::normalize_url(&profile.url) == url))
+}
+
+/// Stores (or updates) the url of a profile alias – other settings and comments are preserved.
+pub fn set_profile_url(alias: &str, url: &str) -> Result<(), DcProvError> {
+    let path = config_path()?;
+
+    let raw = match path.exists() {
+        true => fs::read_to_string(&path).map_err(|_| DcProvError::Io)?,
+        false => String::new(),
+    };
+
+    let mut doc = raw
+        .parse::<DocumentMut>()
+        .map_err(|e| DcProvError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+
+    let profiles = doc
+        .entry("profiles")
+        .or_insert_with(|| {
+            let mut profiles = Table::new();
+            profiles.set_implicit(true);
+            Item::Table(profiles)
+        })
+        .as_table_mut()
+        .ok_or_else(|| DcProvError::InvalidConfig("profiles must be a table".into()))?;
+
+    let profile = profiles
+        .entry(alias)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| DcProvError::InvalidConfig(format!("profile {} must be a table", alias)))?;
+
+    profile["url"] = value(url);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|_| DcProvError::Io)?;
+    }
+
+    fs::write(&path, doc.to_string()).map_err(|_| DcProvError::Io)
+}
+
+/// Stores the url (or alias) used by commands run without url – other settings and comments are
+/// preserved.
+pub fn set_default_url(url_or_alias: &str) -> Result<(), DcProvError> {
+    let path = config_path()?;
+
+    let raw = match path.exists() {
+        true => fs::read_to_string(&path).map_err(|_| DcProvError::Io)?,
+        false => String::new(),
+    };
+
+    let mut doc = raw
+        .parse::<DocumentMut>()
+        .map_err(|e| DcProvError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+
+    doc["default_url"] = value(url_or_alias);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|_| DcProvError::Io)?;
+    }
+
+    fs::write(&path, doc.to_string()).map_err(|_| DcProvError::Io)
+}
@@ -1,9 +1,152 @@
 use crate::cmd::DcProvError;
+use crate::config;
 use keyring::Entry;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    path::PathBuf,
+};
 
 // service name to store
 pub const SERVICE_NAME: &str = env!("CARGO_PKG_NAME");
 
+// file holding tokens for the file backend (next to the config file)
+const CREDENTIALS_FILE: &str = "credentials.json";
+
+// lock file coordinating parallel dcprov processes (next to the config file)
+const LOCK_FILE: &str = "credentials.lock";
+
+// supported credential stores
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialBackend {
+    /// OS keyring (macOS Keychain, Windows Credential Manager, Secret Service)
+    #[default]
+    Keyring,
+    /// plain file readable only by the current user – for hosts without keyring
+    File,
+    /// no credential store – tokens only via --token or env (hardened hosts)
+    None,
+}
+
+/// Token storage for a single DRACOON url
+pub enum CredentialStore {
+    Keyring(Entry),
+    File { path: PathBuf, key: String },
+}
+
+impl CredentialStore {
+    pub fn new(backend: CredentialBackend, service: &str, url: &str) -> Result<Self, DcProvError> {
+        match backend {
+            CredentialBackend::Keyring => Entry::new(service, url)
+                .map(CredentialStore::Keyring)
+                .map_err(|_| DcProvError::CredentialStorageFailed),
+            CredentialBackend::File => Ok(CredentialStore::File {
+                path: credentials_file_path()?,
+                key: format!("{}|{}", service, url),
+            }),
+            CredentialBackend::None => Err(DcProvError::CredentialStoreDisabled),
+        }
+    }
+
+    pub fn set(&self, secret: &str) -> Result<(), DcProvError> {
+        match self {
+            CredentialStore::Keyring(entry) => set_dracoon_env(entry, secret),
+            CredentialStore::File { path, key } => {
+                let mut tokens = load_tokens(path)?;
+                tokens.insert(key.clone(), secret.to_string());
+                write_tokens(path, &tokens)
+            }
+        }
+    }
+
+    pub fn get(&self) -> Result<String, DcProvError> {
+        match self {
+            CredentialStore::Keyring(entry) => get_dracoon_env(entry),
+            CredentialStore::File { path, key } => load_tokens(path)?
+                .remove(key)
+                .ok_or(DcProvError::InvalidAccount),
+        }
+    }
+
+    pub fn delete(&self) -> Result<(), DcProvError> {
+        match self {
+            CredentialStore::Keyring(entry) => delete_dracoon_env(entry),
+            CredentialStore::File { path, key } => {
+                let mut tokens = load_tokens(path)?;
+                if tokens.remove(key).is_none() {
+                    return Err(DcProvError::InvalidAccount);
+                }
+                write_tokens(path, &tokens)
+            }
+        }
+    }
+}
+
+/// Exclusive OS-level lock on the credential store – held while looking up, prompting for and storing
+/// a token, so parallel invocations don't prompt simultaneously or overwrite each other. Released on drop.
+pub struct CredentialLock {
+    _file: fs::File,
+}
+
+impl CredentialLock {
+    pub fn acquire() -> Result<Self, DcProvError> {
+        let path = config::config_path()?.with_file_name(LOCK_FILE);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|_| DcProvError::Io)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|_| DcProvError::Io)?;
+
+        if file.try_lock().is_err() {
+            eprintln!("Waiting for another dcprov process (credential store locked)...");
+            file.lock().map_err(|_| DcProvError::Io)?;
+        }
+
+        Ok(Self { _file: file })
+    }
+}
+
+pub fn credentials_file_path() -> Result<PathBuf, DcProvError> {
+    Ok(config::config_path()?.with_file_name(CREDENTIALS_FILE))
+}
+
+fn load_tokens(path: &PathBuf) -> Result<BTreeMap<String, String>, DcProvError> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let raw = fs::read_to_string(path).map_err(|_| DcProvError::Io)?;
+    serde_json::from_str(&raw).map_err(|_| DcProvError::CredentialStorageFailed)
+}
+
+fn write_tokens(path: &PathBuf, tokens: &BTreeMap<String, String>) -> Result<(), DcProvError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|_| DcProvError::Io)?;
+    }
+
+    // written to a temp file and renamed – concurrent readers never see a partial file
+    let tmp_path = path.with_extension("json.tmp");
+    let raw = serde_json::to_string_pretty(tokens).map_err(|_| DcProvError::Io)?;
+    fs::write(&tmp_path, raw).map_err(|_| DcProvError::CredentialStorageFailed)?;
+
+    // tokens are only readable by the current user
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o600))
+            .map_err(|_| DcProvError::CredentialStorageFailed)?;
+    }
+
+    fs::rename(&tmp_path, path).map_err(|_| DcProvError::CredentialStorageFailed)
+}
+
 pub fn set_dracoon_env(entry: &Entry, secret: &str) -> Result<(), DcProvError> {
     match entry.set_password(secret) {
         Ok(_) => Ok(()),
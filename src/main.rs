@@ -1,19 +1,101 @@
 pub(crate) mod cmd;
+mod config;
 mod credentials;
+mod state;
 use cmd::{
-    handle_errors, print_version, ConfigCommand, CreateCommand, DCProvCommand, DcProv, DcProvError,
-    PrintType, UpdateCommand, UpdateType,
+    handle_errors, print_version, AttributesCommand, AuditCommand, ClientOptions, ConfigCommand,
+    CreateCommand, DCProvCommand, DcProv, LifecycleCommand, LifecycleState, ListOptions, PrintType,
+    QueueBackend, ReportCommand, SheetsCommand, UpdateCommand, UpdateType, UsageCommand,
+    UserListOptions, UsersCommand,
 };
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use colored::*;
-use credentials::SERVICE_NAME;
-use keyring::Entry;
+use config::SavedQuery;
 
 #[tokio::main]
 async fn main() {
-    let opt = DcProv::parse();
+    let args = cmd::expand_alias(std::env::args_os().collect());
+    let matches = DcProv::command().get_matches_from(args.clone());
+    let matches = cmd::apply_profile_args(args, matches);
+    let opt = DcProv::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command = cmd::command_name(&matches);
+    if let Some(expr) = opt.jmespath.clone() {
+        cmd::set_json_query(expr);
+    }
+    if opt.redact_pii {
+        cmd::enable_pii_redaction();
+    }
+    if opt.no_compression {
+        cmd::disable_compression();
+    }
+    if opt.auto_sort {
+        cmd::enable_auto_sort();
+    }
+    if opt.raw {
+        cmd::enable_raw_output();
+    }
+    cmd::set_timestamp_format(opt.tz.clone(), opt.date_format.clone());
+    cmd::set_csv_bom(opt.bom);
+    cmd::set_number_locale(opt.locale.clone());
+    let output_args = opt.output_args();
+    if let Some(host) = opt.expect_host.clone() {
+        cmd::set_expected_host(host);
+    }
+    if opt.no_lock {
+        cmd::disable_batch_lock();
+    }
+    if opt.dry_run {
+        // commands ignoring the flag would send their requests (and skip the confirmation)
+        if !opt.cmd.supports_dry_run() {
+            DcProv::command()
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "--dry-run is not supported by this command",
+                )
+                .exit()
+        }
+        cmd::enable_dry_run();
+    }
+    if !opt.exclude_attribute.is_empty() || opt.exclude_ids.is_some() {
+        // commands ignoring the flags would touch excluded customers
+        if !opt.cmd.supports_exclusions() {
+            DcProv::command()
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "--exclude-attribute / --exclude-ids are not supported by this command",
+                )
+                .exit()
+        }
+        cmd::set_exclusions(opt.exclude_attribute.clone(), opt.exclude_ids.clone());
+    }
+    if let Some(profile) = opt.profile.clone() {
+        cmd::set_profile(profile);
+    }
+    if let Some(external_id) = opt.external_id.clone() {
+        cmd::set_external_id(external_id);
+    }
+    if opt.refresh_mapping {
+        cmd::enable_mapping_refresh();
+    }
+    if let Some(deadline) = opt.deadline {
+        cmd::set_deadline(deadline.to_std().unwrap_or_default());
+    }
+    let client_opts = ClientOptions::new(opt.verbose, opt.keyring_service.clone());
+
+    // duration and result of the command are measured in-process (early exits report via cmd::exit)
+    cmd::start_telemetry(command);
 
+    run_command(opt, client_opts, output_args).await;
+
+    // commands stopped by the deadline return partial results
+    if cmd::deadline_exceeded() {
+        cmd::exit(cmd::DEADLINE_EXIT_CODE)
+    }
+    cmd::finish_telemetry(0).await;
+}
+
+async fn run_command(opt: DcProv, client_opts: ClientOptions, output_args: Vec<String>) {
     match opt.cmd {
         DCProvCommand::List {
             url,
@@ -22,72 +104,220 @@ async fn main() {
             offset,
             limit,
             csv,
+            json,
             all,
+            no_limit,
+            changed_since_last_run,
+            only_over_limit,
+            top,
+            by,
+            include_attributes,
+            concurrency,
+            query,
+            lifecycle,
+            format,
+            output,
         } => {
-            let provider = cmd::init_provisioning(&url, opt.token).await;
-            let print_type = match csv {
-                true => Some(PrintType::Csv),
-                false => Some(PrintType::Pretty),
+            let url = cmd::target_url(url);
+            let query = match query.as_deref().map(config::get_query) {
+                Some(Err(ref e)) => handle_errors(e),
+                Some(Ok(query)) => query,
+                None => SavedQuery::default(),
+            };
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            let print_type = match (csv || json, query.format) {
+                (false, Some(format)) if opt.jmespath.is_none() => format,
+                _ => PrintType::from_flags(csv, json),
             };
             if let Err(ref e) = provider {
                 handle_errors(e)
             }
             let provider = provider.unwrap();
-            cmd::list_customers(provider, filter, sort, offset, limit, print_type, all).await
+            // top consumers: sorted by the server, limited to the top
+            let (sort, limit) = match top {
+                Some(top) => (Some(format!("{}:desc", by)), Some(top)),
+                None => (sort.or(query.sort), limit),
+            };
+            let filter = match (filter.or(query.filter), lifecycle) {
+                (Some(filter), Some(state)) => Some(format!("{}|{}", filter, state.filter())),
+                (filter, state) => filter.or(state.map(LifecycleState::filter)),
+            };
+            if let (Some(cmd::ExportFormat::Sqlite), Some(output)) = (format, output) {
+                cmd::export_sqlite(provider, filter, sort, output, concurrency).await;
+                return;
+            }
+            let opts = ListOptions {
+                filter,
+                sort,
+                offset,
+                limit,
+                print_type,
+                all,
+                no_limit,
+                changed_since_last_run,
+                columns: query.columns,
+                only_over_limit,
+                top,
+                include_attributes: include_attributes || lifecycle.is_some(),
+                concurrency,
+            };
+            cmd::list_customers(provider, opts).await
         }
 
         DCProvCommand::Config { url, cmd } => {
-            let entry =
-                Entry::new(SERVICE_NAME, &url).map_err(|_| DcProvError::CredentialStorageFailed);
+            if let ConfigCommand::Doctor = cmd {
+                cmd::config_doctor(url.as_deref(), &client_opts);
+                return;
+            }
+            if let ConfigCommand::SetDefaultUrl = cmd {
+                let Some(url) = url else {
+                    println!(
+                        "{} DRACOON url (or alias) required.",
+                        "Error".white().on_red()
+                    );
+                    cmd::exit(1)
+                };
+                match config::set_default_url(&url) {
+                    Ok(_) => println!("{}Default url set to {}", "Success ".green(), url),
+                    Err(ref e) => handle_errors(e),
+                }
+                return;
+            }
+            let url = match config::resolve_url(&cmd::target_url(url)) {
+                Ok(url) => url,
+                Err(ref e) => handle_errors(e),
+            };
+            let entry = client_opts.credential_store(&url);
             if let Err(ref e) = entry {
                 handle_errors(e)
             }
 
             let entry = entry.unwrap();
+
+            // parallel invocations must not overwrite each other's changes
+            let _lock = match credentials::CredentialLock::acquire() {
+                Ok(lock) => lock,
+                Err(ref e) => handle_errors(e),
+            };
+
             match cmd {
-                ConfigCommand::Set { token } => {
-                    match credentials::set_dracoon_env(&entry, &token) {
-                        Ok(_) => {
-                            println!("{}{}{}", "Success ".green(), "Credentials saved for ", url)
+                ConfigCommand::Set { token, alias, ttl } => {
+                    if let Err(ref e) = entry.set(&token) {
+                        handle_errors(e)
+                    }
+                    match cmd::set_token_ttl(&url, ttl, &client_opts) {
+                        Ok(Some(expiry)) => println!(
+                            "{}Credentials saved for {} (expire at {})",
+                            "Success ".green(),
+                            url,
+                            expiry.to_rfc3339()
+                        ),
+                        Ok(None) => println!("{}Credentials saved for {}", "Success ".green(), url),
+                        Err(ref e) => {
+                            // a token meant to be short-lived must not stay without expiry
+                            let _ = entry.delete();
+                            handle_errors(e)
+                        }
+                    }
+
+                    if let Some(alias) = alias {
+                        match config::set_profile_url(&alias, &url) {
+                            Ok(_) => {
+                                println!("{}Alias {} set for {}", "Success ".green(), alias, url)
+                            }
+                            Err(ref e) => handle_errors(e),
                         }
-                        Err(ref e) => handle_errors(e),
                     }
                 }
-                ConfigCommand::Get => match credentials::get_dracoon_env(&entry) {
-                    Ok(token) => println!(
-                        "{}{}{}{}{}",
-                        "Success ".green(),
-                        "Credentials for ",
-                        url,
-                        ": ",
-                        token
-                    ),
+                ConfigCommand::Get if cmd::expire_stored_token(&url, &client_opts) => {
+                    handle_errors(&cmd::DcProvError::TokenExpired)
+                }
+                ConfigCommand::Get => match entry.get() {
+                    Ok(token) => {
+                        println!("{}Credentials for {}: {}", "Success ".green(), url, token)
+                    }
                     Err(e) => println!(
-                        "{} {}{}\n{:?}",
+                        "{} Could not get credentials – account not found for {}\n{:?}",
                         "Error".white().on_red(),
-                        "Could not get credentials – account not found for ",
                         url,
                         e
                     ),
                 },
-                ConfigCommand::Delete => match credentials::delete_dracoon_env(&entry) {
-                    Ok(_) => println!(
-                        "{}{}{}",
-                        "Success ".green(),
-                        "Credentials deleted for ",
-                        url
-                    ),
+                ConfigCommand::Delete => match entry.delete() {
+                    Ok(_) => {
+                        let _ = cmd::set_token_ttl(&url, None, &client_opts);
+                        println!("{}Credentials deleted for {}", "Success ".green(), url)
+                    }
                     Err(ref e) => handle_errors(e),
                 },
+                ConfigCommand::Doctor | ConfigCommand::SetDefaultUrl => {
+                    unreachable!("handled above")
+                }
             }
         }
 
-        DCProvCommand::Create { url, cmd } => {
-            let provider = cmd::init_provisioning(&url, opt.token).await;
-            let new_customer = match cmd {
-                CreateCommand::FromFile { path } => cmd::parse_customer_json_from_file(&path),
-
-                CreateCommand::Prompt => cmd::prompt_new_customer(),
+        DCProvCommand::Create {
+            url,
+            verify,
+            verify_timeout,
+            quota_per_user,
+            contract_type,
+            admin_user_name,
+            admin_phone,
+            yes,
+            json,
+            cmd,
+        } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let (None, Some(CreateCommand::FromFile { path })) = (&json, &cmd) {
+                if cmd::is_customer_batch_file(path) {
+                    if verify || admin_user_name.is_some() || admin_phone.is_some() {
+                        DcProv::command()
+                            .error(
+                                clap::error::ErrorKind::ArgumentConflict,
+                                "--verify, --admin-user-name and --admin-phone cannot be used with a batch file",
+                            )
+                            .exit()
+                    }
+                    let mut new_customers = cmd::parse_customers_from_file(path);
+                    if let Err(ref e) = provider {
+                        handle_errors(e)
+                    }
+                    let provider = provider.unwrap();
+                    cmd::confirm_mutation(&url);
+                    for new_customer in new_customers.iter_mut() {
+                        if let Some(contract_type) = contract_type {
+                            new_customer.customer_contract_type = contract_type.to_string();
+                        }
+                        if let Some(quota_per_user) = quota_per_user {
+                            new_customer.quota_max =
+                                cmd::quota_for_users(quota_per_user, new_customer.user_max);
+                        }
+                    }
+                    cmd::check_capacity(&provider, &url, &new_customers, yes).await;
+                    cmd::finish_batch(cmd::create_customers(&provider, new_customers).await);
+                    return;
+                }
+            }
+            let new_customer = match (json, cmd) {
+                (Some(payload), None) => cmd::parse_customer_json_from_arg(&payload),
+                (None, Some(CreateCommand::FromFile { path })) => {
+                    cmd::parse_customer_json_from_file(&path)
+                }
+                (None, Some(CreateCommand::Prompt)) => cmd::prompt_new_customer(),
+                (Some(_), Some(_)) => DcProv::command()
+                    .error(
+                        clap::error::ErrorKind::ArgumentConflict,
+                        "--json cannot be used with from-file or prompt",
+                    )
+                    .exit(),
+                (None, None) => DcProv::command()
+                    .error(
+                        clap::error::ErrorKind::MissingRequiredArgument,
+                        "create requires --json, from-file or prompt",
+                    )
+                    .exit(),
             };
             if let Err(ref e) = provider {
                 handle_errors(e)
@@ -96,49 +326,100 @@ async fn main() {
                 handle_errors(e)
             }
             let provider = provider.unwrap();
-            let new_customer = new_customer.unwrap();
-            cmd::create_customer(provider, new_customer).await;
+            cmd::confirm_mutation(&url);
+            let mut new_customer = new_customer.unwrap();
+            if let Some(contract_type) = contract_type {
+                new_customer.customer_contract_type = contract_type.to_string();
+            }
+            if let Some(user_name) = admin_user_name {
+                new_customer.first_admin_user.user_name = Some(user_name);
+            }
+            if let Some(phone) = admin_phone {
+                new_customer.first_admin_user.phone = Some(phone);
+            }
+            if let Some(quota_per_user) = quota_per_user {
+                new_customer.quota_max =
+                    cmd::quota_for_users(quota_per_user, new_customer.user_max);
+            }
+            cmd::check_capacity(&provider, &url, std::slice::from_ref(&new_customer), yes).await;
+            let verify_timeout = verify.then(|| verify_timeout.to_std().unwrap_or_default());
+            cmd::create_customer(provider, new_customer, verify_timeout).await;
         }
 
-        DCProvCommand::Get { url, id, csv } => {
-            let provider = cmd::init_provisioning(&url, opt.token).await;
-            let print_type = match csv {
-                true => Some(PrintType::Csv),
-                false => Some(PrintType::Pretty),
-            };
+        DCProvCommand::Get { url, id, csv, json } => {
+            let (url, id) = cmd::target_url_and_id(url, id);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            let print_type = Some(PrintType::from_flags(csv, json));
             if let Err(ref e) = provider {
                 handle_errors(e)
             }
             let provider = provider.unwrap();
+            let id = cmd::pick_customer_id(&provider, id).await;
             cmd::get_customer(provider, id, print_type).await;
         }
 
-        DCProvCommand::Update { url, id, cmd } => {
-            let provider = cmd::init_provisioning(&url, opt.token).await;
+        DCProvCommand::Update { url, id, json, cmd } => {
+            let (url, id) = cmd::target_url_and_id(url, id);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
 
+            let check_unique = matches!(
+                cmd,
+                UpdateCommand::CompanyName {
+                    check_unique: true,
+                    ..
+                }
+            );
             let update_type = match cmd {
-                UpdateCommand::CompanyName { company_name } => {
+                UpdateCommand::CompanyName { company_name, .. } => {
                     UpdateType::CompanyName(company_name)
                 }
-                UpdateCommand::QuotaMax { quota_max } => UpdateType::QuotaMax(quota_max),
-                UpdateCommand::UserMax { user_max } => UpdateType::UserMax(user_max),
+                UpdateCommand::QuotaMax {
+                    quota_max,
+                    quota_per_user,
+                } => match (quota_max, quota_per_user) {
+                    (Some(quota_max), _) => UpdateType::QuotaMax(quota_max),
+                    (None, Some(quota_per_user)) => UpdateType::QuotaPerUser(quota_per_user),
+                    (None, None) => unreachable!("clap requires quota max or --quota-per-user"),
+                },
+                UpdateCommand::ContractType { contract_type } => {
+                    UpdateType::ContractType(contract_type)
+                }
+                UpdateCommand::UserMax {
+                    user_max,
+                    quota_per_user: None,
+                } => UpdateType::UserMax(user_max),
+                UpdateCommand::UserMax {
+                    user_max,
+                    quota_per_user: Some(quota_per_user),
+                } => UpdateType::UserMaxWithQuotaPerUser(user_max, quota_per_user),
+                UpdateCommand::FromFile { path } => {
+                    UpdateType::Patch(cmd::parse_update_json_from_file(&path))
+                }
             };
 
             if let Err(ref e) = provider {
                 handle_errors(e)
             }
             let provider = provider.unwrap();
+            let id = cmd::pick_customer_id(&provider, id).await;
+            if let (true, UpdateType::CompanyName(company_name)) = (check_unique, &update_type) {
+                cmd::warn_duplicate_company_name(&provider, id, company_name).await;
+            }
+            cmd::confirm_mutation(&url);
 
-            cmd::update_customer(provider, id, update_type).await;
+            cmd::update_customer(provider, id, update_type, json).await;
         }
 
-        DCProvCommand::Delete { url, id } => {
-            let provider = cmd::init_provisioning(&url, opt.token).await;
+        DCProvCommand::Delete { url, id, yes } => {
+            let (url, id) = cmd::target_url_and_id(url, id);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
             if let Err(ref e) = provider {
                 handle_errors(e)
             }
             let provider = provider.unwrap();
-            cmd::delete_customer(provider, id).await;
+            let id = cmd::pick_customer_id(&provider, id).await;
+            cmd::confirm_mutation(&url);
+            cmd::delete_customer(provider, id, yes).await;
         }
         DCProvCommand::GetAttributes {
             url,
@@ -148,27 +429,97 @@ async fn main() {
             offset,
             limit,
             csv,
+            json,
         } => {
-            let provider = cmd::init_provisioning(&url, opt.token).await;
+            let (url, id) = cmd::target_url_and_id(url, id);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
             if let Err(ref e) = provider {
                 handle_errors(e)
             }
             let provider = provider.unwrap();
-            let print_type = match csv {
-                true => Some(PrintType::Csv),
-                false => Some(PrintType::Pretty),
-            };
+            let id = cmd::resolve_customer_id(&provider, id).await;
+            let print_type = Some(PrintType::from_flags(csv, json));
             cmd::get_customer_attributes(provider, id, filter, sort, offset, limit, print_type)
                 .await
         }
-        DCProvCommand::SetAttributes { url, id, attribs } => {
-            let provider = cmd::init_provisioning(&url, opt.token).await;
+        DCProvCommand::SetAttributes {
+            url,
+            id,
+            attribs,
+            file,
+        } => {
+            let (url, id) = cmd::target_url_and_id(url, id);
+            let attribs = match file {
+                Some(path) => {
+                    let mut from_file = cmd::load_attributes_file(&path);
+                    from_file.retain(|(key, _)| !attribs.iter().any(|(k, _)| k == key));
+                    from_file.into_iter().chain(attribs).collect()
+                }
+                None => attribs,
+            };
+
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
             if let Err(ref e) = provider {
                 handle_errors(e)
             }
             let provider = provider.unwrap();
+            let id = cmd::resolve_customer_id(&provider, id).await;
+            let attribs = cmd::render_attribute_templates(&provider, id, attribs).await;
+            cmd::validate_attributes(&attribs);
+            cmd::confirm_mutation(&url);
             cmd::update_customer_attributes(provider, id, attribs).await;
         }
+        DCProvCommand::CopyAttributes {
+            url,
+            from,
+            to,
+            prefix,
+        } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            cmd::confirm_mutation(&url);
+            let report = cmd::copy_customer_attributes(provider, from, to, prefix).await;
+            cmd::finish_batch(report);
+        }
+        DCProvCommand::Attributes { cmd } => match cmd {
+            AttributesCommand::Migrate {
+                url,
+                rename,
+                customers,
+                all_customers,
+            } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                cmd::confirm_mutation(&url);
+                let report = cmd::migrate_customer_attributes(
+                    provider,
+                    rename,
+                    customers,
+                    all_customers,
+                    opt.dry_run,
+                )
+                .await;
+                cmd::finish_batch(report);
+            }
+            AttributesCommand::Keys { url, filter, csv } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                let print_type = PrintType::from_csv_flag(csv);
+                cmd::list_attribute_keys(provider, filter, print_type).await;
+            }
+        },
         DCProvCommand::GetUsers {
             url,
             id,
@@ -177,18 +528,479 @@ async fn main() {
             offset,
             limit,
             csv,
+            json,
+            columns,
+            with_home_rooms,
+            strict,
         } => {
-            let provider = cmd::init_provisioning(&url, opt.token).await;
-            let print_type = match csv {
-                true => Some(PrintType::Csv),
-                false => Some(PrintType::Pretty),
+            let (url, id) = cmd::target_url_and_id(url, id);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            let print_type = PrintType::from_flags(csv, json);
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            let id = cmd::resolve_customer_id(&provider, id).await;
+            let opts = UserListOptions {
+                filter,
+                sort,
+                offset,
+                limit,
+                print_type,
+                columns,
+                with_home_rooms,
+                strict,
             };
+            cmd::get_customer_users(provider, id, opts).await;
+        }
+        DCProvCommand::NormalizeQuotas {
+            url,
+            granularity,
+            mode,
+            filter,
+            yes,
+            csv,
+        } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
             if let Err(ref e) = provider {
                 handle_errors(e)
             }
             let provider = provider.unwrap();
-            cmd::get_customer_users(provider, id, filter, sort, offset, limit, print_type).await;
+            cmd::confirm_mutation(&url);
+            let print_type = PrintType::from_csv_flag(csv);
+            let report = cmd::normalize_quotas(
+                provider,
+                granularity,
+                mode,
+                filter,
+                opt.dry_run,
+                yes,
+                print_type,
+            )
+            .await;
+            cmd::finish_batch(report);
+        }
+        DCProvCommand::Report { cmd } => match cmd {
+            ReportCommand::Growth {
+                url,
+                since,
+                filter,
+                csv,
+            } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                let print_type = PrintType::from_csv_flag(csv);
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                cmd::report_growth(provider, since, filter, print_type).await;
+            }
+            ReportCommand::ExpiringUsers {
+                url,
+                within,
+                filter,
+                csv,
+            } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                let print_type = PrintType::from_csv_flag(csv);
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                cmd::report_expiring_users(provider, within, filter, print_type).await;
+            }
+            ReportCommand::Encryption {
+                url,
+                filter,
+                only_enabled,
+                csv,
+            } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                let print_type = PrintType::from_csv_flag(csv);
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                cmd::report_encryption(provider, filter, only_enabled, print_type).await;
+            }
+            ReportCommand::Duplicates {
+                url,
+                key,
+                filter,
+                csv,
+            } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                let print_type = PrintType::from_csv_flag(csv);
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                cmd::report_duplicates(provider, key, filter, print_type).await;
+            }
+        },
+        DCProvCommand::ConvertTrial {
+            url,
+            id,
+            quota,
+            users,
+            attribute_prefix,
+            webhook,
+            yes,
+        } => {
+            let (url, id) = cmd::target_url_and_id(url, id);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            let id = cmd::resolve_customer_id(&provider, id).await;
+            cmd::confirm_mutation(&url);
+            cmd::convert_trial(provider, id, quota, users, attribute_prefix, webhook, yes).await;
+        }
+        DCProvCommand::LockAll {
+            url,
+            filter,
+            unlock,
+            concurrency,
+            yes,
+            csv,
+        } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            let print_type = PrintType::from_csv_flag(csv);
+            cmd::confirm_mutation(&url);
+            let report =
+                cmd::lock_customers(provider, filter, unlock, concurrency, yes, print_type).await;
+            cmd::finish_batch(report);
+        }
+        DCProvCommand::Stats {
+            url,
+            sample,
+            exact,
+            csv,
+        } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            let print_type = PrintType::from_csv_flag(csv);
+            cmd::customer_stats(provider, sample, exact, print_type).await;
+        }
+        DCProvCommand::Changes {
+            url,
+            deleted_only,
+            csv,
+        } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            let print_type = PrintType::from_csv_flag(csv);
+            cmd::list_changes(provider, deleted_only, print_type).await;
+        }
+        DCProvCommand::Lifecycle { cmd } => match cmd {
+            LifecycleCommand::Set { args, force } => {
+                let (url, id, state) = cmd::lifecycle_set_args(args);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                cmd::confirm_mutation(&url);
+                cmd::set_lifecycle(provider, id, state, force).await;
+            }
+            LifecycleCommand::Get { url, id, csv } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                let print_type = PrintType::from_csv_flag(csv);
+                cmd::get_lifecycle(provider, id, print_type).await;
+            }
+            LifecycleCommand::List {
+                url,
+                state,
+                filter,
+                csv,
+            } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                let print_type = PrintType::from_csv_flag(csv);
+                cmd::list_lifecycle(provider, state, filter, print_type).await;
+            }
+        },
+        DCProvCommand::Sheets { cmd } => match cmd {
+            SheetsCommand::Push {
+                spreadsheet_id,
+                sheet,
+                input,
+            } => cmd::push_to_sheet(spreadsheet_id, sheet, input).await,
+        },
+        DCProvCommand::Usage { cmd } => match cmd {
+            UsageCommand::Export {
+                url,
+                partition,
+                out_dir,
+                filter,
+            } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                cmd::export_usage(provider, partition, out_dir, filter).await;
+            }
+        },
+        DCProvCommand::DriftCheck {
+            url,
+            baseline,
+            fail_on_drift,
+            ignore_unmanaged,
+            csv,
+        } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            let print_type = PrintType::from_csv_flag(csv);
+            cmd::drift_check(
+                provider,
+                baseline,
+                fail_on_drift,
+                ignore_unmanaged,
+                print_type,
+            )
+            .await;
+        }
+        DCProvCommand::Alerts {
+            url,
+            threshold,
+            webhook,
+            digest,
+            group_by,
+            filter,
+            csv,
+        } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            let print_type = PrintType::from_csv_flag(csv);
+            cmd::usage_alerts(
+                provider, threshold, webhook, digest, group_by, filter, print_type,
+            )
+            .await;
+        }
+        DCProvCommand::Audit { cmd } => match cmd {
+            AuditCommand::Quotas {
+                url,
+                min_quota,
+                granularity,
+                filter,
+                csv,
+            } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                let print_type = PrintType::from_csv_flag(csv);
+                cmd::audit_quotas(provider, min_quota, granularity, filter, print_type).await;
+            }
+        },
+        DCProvCommand::Users { cmd } => match cmd {
+            UsersCommand::Diff {
+                url,
+                id,
+                against,
+                key,
+                column,
+                csv,
+            } => {
+                let (url, id) = cmd::target_url_and_id(url, id);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                let id = cmd::resolve_customer_id(&provider, id).await;
+                let print_type = PrintType::from_csv_flag(csv);
+                cmd::diff_customer_users(provider, id, against, key, column, print_type).await;
+            }
+            UsersCommand::Export {
+                url,
+                filter,
+                concurrency,
+                retries,
+                report,
+                retry_failed,
+                csv,
+            } => {
+                let url = cmd::target_url(url);
+                let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+                if let Err(ref e) = provider {
+                    handle_errors(e)
+                }
+                let provider = provider.unwrap();
+                let print_type = PrintType::from_csv_flag(csv);
+                cmd::export_all_users(
+                    provider,
+                    filter,
+                    concurrency,
+                    retries,
+                    report,
+                    retry_failed,
+                    print_type,
+                )
+                .await;
+            }
+        },
+        DCProvCommand::Sync {
+            url,
+            target,
+            mapping,
+            filter,
+            resume_from,
+        } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            let report = cmd::sync_customers(
+                provider,
+                target,
+                &mapping,
+                filter,
+                resume_from,
+                client_opts.verbose,
+            )
+            .await;
+            cmd::finish_batch(report);
+        }
+        DCProvCommand::TfResource { url } => {
+            let url = cmd::target_url(url);
+            cmd::require_expected_host(&url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            cmd::handle_tf_resource(provider).await;
+        }
+        DCProvCommand::Ansible { args_file } => {
+            cmd::run_ansible_module(&args_file, &client_opts).await
+        }
+        DCProvCommand::Serve {
+            url,
+            port,
+            auth_token,
+            grpc,
+        } => {
+            let url = cmd::target_url(url);
+            cmd::require_expected_host(&url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            match grpc {
+                true => cmd::serve_grpc(provider, port, auth_token).await,
+                false => cmd::serve(provider, port, auth_token).await,
+            }
+        }
+        DCProvCommand::Worker {
+            url,
+            amqp,
+            redis,
+            queue,
+            reply_queue,
+            max_retries,
+        } => {
+            let url = cmd::target_url(url);
+            cmd::require_expected_host(&url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            let backend = match (amqp, redis) {
+                (Some(uri), _) => QueueBackend::Amqp(uri),
+                (None, Some(uri)) => QueueBackend::Redis(uri),
+                (None, None) => unreachable!("clap requires --amqp or --redis"),
+            };
+            cmd::run_worker(provider, backend, queue, reply_queue, max_retries).await;
+        }
+        DCProvCommand::Run {
+            playbook,
+            url,
+            vars,
+        } => {
+            let playbook = cmd::load_playbook(&playbook);
+            let Some(url) = url.or(playbook.url.clone()) else {
+                println!(
+                    "{} DRACOON url required (playbook url or --url).",
+                    "Error".white().on_red()
+                );
+                cmd::exit(1)
+            };
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            let provider = provider.unwrap();
+            cmd::confirm_mutation(&url);
+            let report = cmd::run_playbook(provider, playbook, vars).await;
+            cmd::finish_batch(report);
+        }
+        DCProvCommand::Whoami { url, json } => {
+            let url = cmd::target_url(url);
+            cmd::whoami(&url, opt.token, &client_opts, json).await
+        }
+        DCProvCommand::Capabilities { url, refresh, json } => {
+            let url = cmd::target_url(url);
+            let provider = cmd::init_provisioning(&url, opt.token, &client_opts).await;
+            if let Err(ref e) = provider {
+                handle_errors(e)
+            }
+            cmd::print_capabilities(provider.unwrap(), refresh, json).await
+        }
+        DCProvCommand::Selftest { url, yes, json } => {
+            let url = cmd::target_url(url);
+            cmd::selftest(&url, opt.token, &client_opts, yes, json).await
+        }
+        DCProvCommand::Shell { url } => {
+            cmd::run_shell(&cmd::target_url(url), opt.token, &client_opts, output_args)
         }
-        DCProvCommand::Version {} => print_version(),
+        DCProvCommand::Plugins => cmd::list_plugins(),
+        DCProvCommand::Version { json, short } => print_version(short, json),
+        DCProvCommand::Help { topic } => cmd::print_help(topic),
+        DCProvCommand::Plugin(args) => cmd::run_plugin(args, opt.token, &client_opts),
     }
 }
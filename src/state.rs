@@ -0,0 +1,253 @@
+use crate::cmd::DcProvError;
+use crate::credentials::SERVICE_NAME;
+use chrono::{DateTime, FixedOffset};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, OpenOptions},
+    path::PathBuf,
+};
+
+// lock file serializing read-modify-write cycles of the state files across processes
+const LOCK_FILE: &str = "state.lock";
+
+// file holding the per-url updatedAt watermarks
+const WATERMARKS_FILE: &str = "watermarks.json";
+
+/// Returns the directory used to persist dcprov state (created if missing).
+pub fn state_dir() -> Result<PathBuf, DcProvError> {
+    let dir = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .ok_or(DcProvError::Io)?
+        .join(SERVICE_NAME);
+
+    fs::create_dir_all(&dir).map_err(|_| DcProvError::Io)?;
+
+    Ok(dir)
+}
+
+/// Exclusive lock of the state files (released on drop) – parallel runs (e.g. cron jobs) must
+/// not overwrite each other's changes.
+struct StateLock {
+    _file: fs::File,
+}
+
+impl StateLock {
+    fn acquire() -> Result<Self, DcProvError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(state_dir()?.join(LOCK_FILE))
+            .map_err(|_| DcProvError::Io)?;
+
+        if file.try_lock().is_err() {
+            eprintln!("Waiting for another dcprov process (state locked)...");
+            file.lock().map_err(|_| DcProvError::Io)?;
+        }
+
+        Ok(Self { _file: file })
+    }
+}
+
+/// Loads a state file (default if missing).
+fn load_state<T: DeserializeOwned + Default>(file: &str) -> Result<T, DcProvError> {
+    let path = state_dir()?.join(file);
+
+    if !path.exists() {
+        return Ok(T::default());
+    }
+
+    let raw = fs::read_to_string(path).map_err(|_| DcProvError::Io)?;
+    serde_json::from_str(&raw).map_err(|_| DcProvError::Io)
+}
+
+/// Writes a state file via a temp file and rename – concurrent readers never see a partial file.
+fn save_state<T: Serialize>(file: &str, state: &T) -> Result<(), DcProvError> {
+    let path = state_dir()?.join(file);
+    let tmp_path = path.with_extension("json.tmp");
+
+    let raw = serde_json::to_string_pretty(state).map_err(|_| DcProvError::Io)?;
+    fs::write(&tmp_path, raw).map_err(|_| DcProvError::Io)?;
+    fs::rename(&tmp_path, path).map_err(|_| DcProvError::Io)
+}
+
+/// Loads, modifies and writes a state file while holding the state lock.
+fn update_state<T: Serialize + DeserializeOwned + Default>(
+    file: &str,
+    update: impl FnOnce(&mut T),
+) -> Result<(), DcProvError> {
+    let _lock = StateLock::acquire()?;
+
+    let mut state = load_state(file)?;
+    update(&mut state);
+
+    save_state(file, &state)
+}
+
+/// Returns the last seen updatedAt / createdAt timestamp for a DRACOON url.
+pub fn get_watermark(url: &str) -> Result<Option<DateTime<FixedOffset>>, DcProvError> {
+    let watermarks: HashMap<String, String> = load_state(WATERMARKS_FILE)?;
+
+    Ok(watermarks
+        .get(url)
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok()))
+}
+
+/// Stores the last seen updatedAt / createdAt timestamp for a DRACOON url.
+pub fn set_watermark(url: &str, watermark: DateTime<FixedOffset>) -> Result<(), DcProvError> {
+    update_state(
+        WATERMARKS_FILE,
+        |watermarks: &mut HashMap<String, String>| {
+            watermarks.insert(url.to_string(), watermark.to_rfc3339());
+        },
+    )
+}
+
+// file holding the per-url customer snapshots (changes command)
+const SNAPSHOTS_FILE: &str = "snapshots.json";
+
+/// Customer as recorded in a snapshot
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotCustomer {
+    pub company_name: String,
+    pub updated_at: Option<String>,
+}
+
+/// Customer missing from a later snapshot (deleted outside of or by dcprov)
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Tombstone {
+    pub company_name: String,
+    /// time of the last snapshot containing the customer
+    pub last_seen_at: String,
+    /// time of the snapshot the customer was missing from
+    pub detected_at: String,
+}
+
+/// Customers of a DRACOON url at a point in time (RFC 3339) and the tombstones of deleted customers.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub taken_at: String,
+    pub customers: BTreeMap<u64, SnapshotCustomer>,
+    #[serde(default)]
+    pub tombstones: BTreeMap<u64, Tombstone>,
+}
+
+/// Returns the last snapshot of a DRACOON url.
+pub fn get_snapshot(url: &str) -> Result<Option<Snapshot>, DcProvError> {
+    let mut snapshots: HashMap<String, Snapshot> = load_state(SNAPSHOTS_FILE)?;
+
+    Ok(snapshots.remove(url))
+}
+
+/// Stores the snapshot of a DRACOON url (replaces the previous one).
+pub fn set_snapshot(url: &str, snapshot: Snapshot) -> Result<(), DcProvError> {
+    update_state(
+        SNAPSHOTS_FILE,
+        |snapshots: &mut HashMap<String, Snapshot>| {
+            snapshots.insert(url.to_string(), snapshot);
+        },
+    )
+}
+
+// file holding the answers of an unfinished create prompt
+const PROMPT_DRAFT_FILE: &str = "prompt-draft.json";
+
+/// Answers given so far in the create prompt (persisted after each answer)
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptDraft {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub email: Option<String>,
+    /// empty if skipped
+    pub user_name: Option<String>,
+    /// empty if skipped
+    pub phone: Option<String>,
+    pub company_name: Option<String>,
+    pub quota_max: Option<u64>,
+    pub user_max: Option<u64>,
+}
+
+/// Returns the answers of an unfinished create prompt (if any).
+pub fn get_prompt_draft() -> Result<Option<PromptDraft>, DcProvError> {
+    load_state(PROMPT_DRAFT_FILE)
+}
+
+/// Stores the answers of the create prompt given so far.
+pub fn set_prompt_draft(draft: &PromptDraft) -> Result<(), DcProvError> {
+    save_state(PROMPT_DRAFT_FILE, draft)
+}
+
+/// Removes the answers of the create prompt (customer created or resume declined).
+pub fn remove_prompt_draft() -> Result<(), DcProvError> {
+    let path = state_dir()?.join(PROMPT_DRAFT_FILE);
+
+    if path.exists() {
+        fs::remove_file(path).map_err(|_| DcProvError::Io)?;
+    }
+
+    Ok(())
+}
+
+// file holding the probed capabilities per url
+const CAPABILITIES_FILE: &str = "capabilities.json";
+
+/// Capabilities probed for a DRACOON url (RFC 3339 timestamp of the probe)
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub api_version: Option<String>,
+    pub detected_at: String,
+    /// optional customer fields the server supports (none: not probed)
+    #[serde(default)]
+    pub fields: Option<Vec<String>>,
+}
+
+/// Returns the cached capabilities of a DRACOON url.
+pub fn get_capabilities(url: &str) -> Result<Option<Capabilities>, DcProvError> {
+    let mut all: HashMap<String, Capabilities> = load_state(CAPABILITIES_FILE)?;
+
+    Ok(all.remove(url))
+}
+
+/// Stores the capabilities of a DRACOON url.
+pub fn set_capabilities(url: &str, capabilities: Capabilities) -> Result<(), DcProvError> {
+    update_state(
+        CAPABILITIES_FILE,
+        |all: &mut HashMap<String, Capabilities>| {
+            all.insert(url.to_string(), capabilities);
+        },
+    )
+}
+
+// file holding the expiry of time-boxed tokens (config set --ttl) per credential key
+const TOKEN_EXPIRIES_FILE: &str = "token-expiries.json";
+
+/// Returns the expiry of a stored token (none if the token does not expire).
+pub fn get_token_expiry(key: &str) -> Result<Option<DateTime<FixedOffset>>, DcProvError> {
+    let expiries: HashMap<String, String> = load_state(TOKEN_EXPIRIES_FILE)?;
+
+    Ok(expiries
+        .get(key)
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok()))
+}
+
+/// Stores (or removes, if none) the expiry of a stored token.
+pub fn set_token_expiry(
+    key: &str,
+    expiry: Option<DateTime<FixedOffset>>,
+) -> Result<(), DcProvError> {
+    update_state(
+        TOKEN_EXPIRIES_FILE,
+        |expiries: &mut HashMap<String, String>| {
+            match expiry {
+                Some(expiry) => expiries.insert(key.to_string(), expiry.to_rfc3339()),
+                None => expiries.remove(key),
+            };
+        },
+    )
+}
@@ -0,0 +1,38 @@
+use std::{net::TcpListener, process::Command};
+
+/// Commands ignoring --dry-run must reject it – neither prompting for confirmation nor sending requests.
+#[test]
+fn dry_run_rejected_by_commands_without_support() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    server.set_nonblocking(true).unwrap();
+    let url = format!("http://{}", server.local_addr().unwrap());
+    let config = std::env::temp_dir().join(format!("dcprov-dry-run-{}.toml", std::process::id()));
+
+    for args in [
+        vec![
+            "lock-all",
+            url.as_str(),
+            "--filter",
+            "companyName:cn:test",
+            "--yes",
+        ],
+        vec!["copy-attributes", url.as_str(), "--from", "1", "--to", "2"],
+        vec!["lifecycle", "set", url.as_str(), "1", "active"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_dcprov"))
+            .args(["--token", "token", "--dry-run"])
+            .args(&args)
+            .env("DCPROV_CONFIG", &config)
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success(), "{:?} succeeded", args);
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("--dry-run is not supported"),
+            "{:?}: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(server.accept().is_err(), "{:?} sent a request", args);
+    }
+}